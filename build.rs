@@ -0,0 +1,36 @@
+//! Build script -- embeds the git revision into the generator so it can be
+//! recorded in world file metadata.
+//!
+//! `GIT_REVISION` takes precedence (useful for Docker builds without a
+//! `.git` directory); otherwise `git rev-parse` is tried. If neither is
+//! available the revision is simply left unset.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_REVISION");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+
+    let revision = env::var("GIT_REVISION")
+        .ok()
+        .filter(|rev| !rev.is_empty())
+        .or_else(git_revision);
+
+    if let Some(rev) = revision {
+        println!("cargo:rustc-env=WORLDGEN_GIT_REVISION={rev}");
+    }
+}
+
+fn git_revision() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let rev = String::from_utf8(output.stdout).ok()?;
+    Some(rev.trim().to_owned()).filter(|rev| !rev.is_empty())
+}
@@ -0,0 +1,144 @@
+//! Storage backends behind [`World`](crate::World).
+//!
+//! A [`WorldBackend`] answers the handful of questions `World` asks of its
+//! storage: the header, one chunk, one chunk summary, one LOD level. Two
+//! backends exist:
+//!
+//! - [`ChunkedWorldReader`] reads a `.world` file from disk.
+//! - [`MemoryBackend`] wraps freshly generated [`WorldData`] and cuts chunks,
+//!   summaries and LOD levels out of it on demand, so a world can be queried
+//!   (or served by the viewer) without ever being written to disk.
+//!
+//! Both produce identical chunks for the same world, so every `World` query
+//! behaves the same whichever backend is underneath.
+
+use std::io;
+use std::sync::OnceLock;
+
+use crate::lod::LodLevel;
+use crate::save::{
+    stored_elevation, ChunkData, ChunkSummary, ChunkedWorldHeader, ChunkedWorldReader, WorldData,
+};
+
+/// Read access to the contents of a world.
+pub trait WorldBackend: Send + Sync {
+    /// Header describing the world (config, dimensions, cities, villages...).
+    fn header(&self) -> &ChunkedWorldHeader;
+    /// Decompress or extract chunk `(cx, cy)`.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if the chunk is outside
+    /// the grid, or any error reading it.
+    fn load_chunk(&self, cx: u32, cy: u32) -> io::Result<ChunkData>;
+    /// Summary of chunk `(cx, cy)`, or `None` if the backend has none.
+    fn chunk_summary(&self, cx: u32, cy: u32) -> Option<&ChunkSummary>;
+    /// LOD level `level` (index into `header().lod_levels`).
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if there is no such
+    /// level, or any error reading it.
+    fn load_lod_level(&self, level: usize) -> io::Result<LodLevel>;
+}
+
+impl WorldBackend for ChunkedWorldReader {
+    fn header(&self) -> &ChunkedWorldHeader {
+        &self.header
+    }
+
+    fn load_chunk(&self, cx: u32, cy: u32) -> io::Result<ChunkData> {
+        Self::load_chunk(self, cx, cy)
+    }
+
+    fn chunk_summary(&self, cx: u32, cy: u32) -> Option<&ChunkSummary> {
+        Self::chunk_summary(self, cx, cy)
+    }
+
+    fn load_lod_level(&self, level: usize) -> io::Result<LodLevel> {
+        Self::load_lod_level(self, level)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// In-memory backend
+// ---------------------------------------------------------------------------
+
+/// A world held entirely in memory as flat [`WorldData`] grids.
+///
+/// Chunks are copied out of the grids when requested; chunk summaries are
+/// computed the first time each one is asked for. Elevations are rounded to
+/// the precision of the file format so queries match a saved copy exactly.
+pub struct MemoryBackend {
+    header: ChunkedWorldHeader,
+    data: WorldData,
+    /// Row-major, one slot per chunk.
+    summaries: Vec<OnceLock<ChunkSummary>>,
+}
+
+impl MemoryBackend {
+    #[must_use]
+    pub fn new(data: WorldData) -> Self {
+        let header = ChunkedWorldHeader::from_data(&data);
+        let num_chunks = (header.chunks_x * header.chunks_y) as usize;
+        Self {
+            header,
+            data,
+            summaries: (0..num_chunks).map(|_| OnceLock::new()).collect(),
+        }
+    }
+
+    /// The wrapped world data.
+    #[must_use]
+    pub const fn data(&self) -> &WorldData {
+        &self.data
+    }
+
+    fn check_chunk(&self, cx: u32, cy: u32) -> io::Result<usize> {
+        let h = &self.header;
+        if cx >= h.chunks_x || cy >= h.chunks_y {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("chunk ({cx}, {cy}) out of bounds"),
+            ));
+        }
+        Ok((cy * h.chunks_x + cx) as usize)
+    }
+}
+
+impl WorldBackend for MemoryBackend {
+    fn header(&self) -> &ChunkedWorldHeader {
+        &self.header
+    }
+
+    fn load_chunk(&self, cx: u32, cy: u32) -> io::Result<ChunkData> {
+        self.check_chunk(cx, cy)?;
+        let mut chunk = self.data.extract_chunk(cx, cy);
+        for e in &mut chunk.elevation {
+            *e = stored_elevation(*e);
+        }
+        Ok(chunk)
+    }
+
+    fn chunk_summary(&self, cx: u32, cy: u32) -> Option<&ChunkSummary> {
+        let idx = self.check_chunk(cx, cy).ok()?;
+        Some(
+            self.summaries[idx]
+                .get_or_init(|| ChunkSummary::from_chunk(&self.data.extract_chunk(cx, cy))),
+        )
+    }
+
+    fn load_lod_level(&self, level: usize) -> io::Result<LodLevel> {
+        let info = self.header.lod_levels.get(level).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("LOD level {level} out of range"),
+            )
+        })?;
+        let mut lod = LodLevel::from_data(&self.data, info.factor);
+        for e in &mut lod.elevation {
+            *e = stored_elevation(*e);
+        }
+        Ok(lod)
+    }
+}
@@ -1,11 +1,33 @@
 //! Interactive map viewer - serves tiles and overlays over HTTP.
+//!
+//! ```text
+//! viewer [path]     # serve a world file (default: world.world)
+//! viewer --preview  # generate a world from the current config in memory and serve it
+//! ```
+//!
+//! City diagnostics (`CITY_DIAGNOSTICS=true`) are served when available:
+//! from the `.citydiag` sidecar next to the world file, or straight from
+//! the generator in preview mode.
 
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::thread;
 
 use tiny_http::{Header, Request, Response, Server};
 
+use world_generator::backend::MemoryBackend;
+use world_generator::cache::CacheBudget;
+use world_generator::city_diagnostics::CityDiagnostics;
+use world_generator::config::WorldConfig;
+use world_generator::coords::{MapGeometry, TileXYZ, WorldPos};
+use world_generator::generate::generate_world;
 use world_generator::island::Island;
-use world_generator::tile::{render_debug_tile, render_tile, TILE_SIZE};
+use world_generator::navigation::{LandOptions, NavOptions, NavPath};
+use world_generator::rect::Layers;
+use world_generator::save;
+use world_generator::tile::{
+    render_city_diagnostics_tile, render_debug_tile, render_sector_tile, render_tile, TILE_SIZE,
+};
 use world_generator::World;
 
 // ---------------------------------------------------------------------------
@@ -26,26 +48,28 @@ const DEBUG_JS: &str = include_str!("../../static/debug.js");
 // Server state
 // ---------------------------------------------------------------------------
 
+/// Rendered PNG tiles keyed by `(z, x, y)`.
+type TileCache = HashMap<TileXYZ, Arc<Vec<u8>>>;
+
+/// Shared by every worker thread; all fields are safe to use through `&self`.
 struct ServerState {
     world: World,
-    tile_cache: HashMap<(u32, u32, u32), Vec<u8>>,
-    islands_json: Option<String>,
-    island_outlines: Option<HashMap<u32, String>>,
+    tile_cache: Mutex<TileCache>,
+    islands_json: OnceLock<String>,
+    island_outlines: OnceLock<HashMap<u32, String>>,
     world_fingerprint: String,
+    city_diagnostics: Option<CityDiagnostics>,
 }
 
 impl ServerState {
-    fn ensure_islands_json(&mut self) {
-        if self.islands_json.is_none() {
-            self.world.ensure_islands_computed();
-            self.islands_json = Some(islands_to_json(self.world.islands()));
-        }
+    fn islands_json(&self) -> &str {
+        self.islands_json
+            .get_or_init(|| islands_to_json(self.world.islands()))
     }
 
-    fn ensure_island_outlines(&mut self) {
-        if self.island_outlines.is_none() {
-            self.island_outlines = Some(build_island_outlines(&mut self.world));
-        }
+    fn island_outlines(&self) -> &HashMap<u32, String> {
+        self.island_outlines
+            .get_or_init(|| build_island_outlines(&self.world))
     }
 }
 
@@ -56,24 +80,59 @@ impl ServerState {
 fn main() {
     let _ = dotenvy::dotenv();
 
-    let path = std::env::args()
+    let arg = std::env::args()
         .nth(1)
         .unwrap_or_else(|| "world.world".into());
 
-    if !std::path::Path::new(&path).exists() {
-        eprintln!("File not found: {path}");
-        std::process::exit(1);
-    }
+    // CHUNK_CACHE_MB: decompressed chunk budget in MiB (0 = unbounded).
+    let budget = match std::env::var("CHUNK_CACHE_MB")
+        .ok()
+        .and_then(|v| v.parse().ok())
+    {
+        Some(0) => CacheBudget::Unbounded,
+        Some(mb) => CacheBudget::Bytes(mb * 1024 * 1024),
+        None => CacheBudget::default(),
+    };
 
-    let world = World::open(&path).unwrap_or_else(|e| {
-        eprintln!("Failed to open world: {e}");
-        std::process::exit(1);
-    });
+    let (world, source, city_diagnostics) = if arg == "--preview" {
+        // Instant preview: nothing is written to disk.
+        let config = WorldConfig::from_env();
+        eprintln!(
+            "Generating {}x{} preview with seed {} ...",
+            config.map_size, config.map_size, config.seed
+        );
+        let mut data = generate_world(&config);
+        eprintln!(
+            "Generated in {:.2?}: {} islands, {} cities, {} villages",
+            data.metadata.total_duration(),
+            data.metadata.counts.islands,
+            data.metadata.counts.cities,
+            data.metadata.counts.villages,
+        );
+        let city_diagnostics = data.city_diagnostics.take();
+        let backend = MemoryBackend::new(data);
+        (
+            World::from_backend(Box::new(backend), budget),
+            "preview".to_owned(),
+            city_diagnostics,
+        )
+    } else {
+        if !std::path::Path::new(&arg).exists() {
+            eprintln!("File not found: {arg}");
+            std::process::exit(1);
+        }
+        let world = World::open_with_cache(&arg, budget).unwrap_or_else(|e| {
+            eprintln!("Failed to open world: {e}");
+            std::process::exit(1);
+        });
+        let city_diagnostics = load_city_diagnostics(&arg, &world);
+        (world, arg, city_diagnostics)
+    };
 
     let fingerprint = format!("{:x}", world.config().seed);
 
     eprintln!(
-        "Loaded {path}: {}x{} world, {} cities, {} villages, seed {}",
+        "Loaded {source}: {}x{} world, {} cities, {} villages, seed {}",
         world.width(),
         world.height(),
         world.city_slots().len(),
@@ -81,26 +140,45 @@ fn main() {
         world.config().seed,
     );
 
-    let mut state = ServerState {
+    let state = Arc::new(ServerState {
         world,
-        tile_cache: HashMap::new(),
-        islands_json: None,
-        island_outlines: None,
+        tile_cache: Mutex::new(HashMap::new()),
+        islands_json: OnceLock::new(),
+        island_outlines: OnceLock::new(),
         world_fingerprint: fingerprint,
-    };
+        city_diagnostics,
+    });
 
     eprintln!("Pre-computing islands...");
-    state.ensure_islands_json();
+    state.islands_json();
     eprintln!("Ready.");
 
     let addr = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0:8080".into());
-    let server = Server::http(&addr).expect("Failed to bind");
-    eprintln!("Viewer running at http://{addr}");
-
-    for request in server.incoming_requests() {
-        let full_url = request.url().to_owned();
-        let url = full_url.split('?').next().unwrap_or(&full_url).to_owned();
-        handle_request(request, &url, &full_url, &mut state);
+    let server = Arc::new(Server::http(&addr).expect("Failed to bind"));
+
+    // VIEWER_THREADS: number of request worker threads (default: one per core).
+    let threads = std::env::var("VIEWER_THREADS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n: &usize| n > 0)
+        .unwrap_or_else(|| thread::available_parallelism().map_or(4, std::num::NonZero::get));
+    eprintln!("Viewer running at http://{addr} ({threads} threads)");
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let state = Arc::clone(&state);
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let full_url = request.url().to_owned();
+                    let url = full_url.split('?').next().unwrap_or(&full_url).to_owned();
+                    handle_request(request, &url, &full_url, &state);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
     }
 }
 
@@ -108,7 +186,7 @@ fn main() {
 // Request routing
 // ---------------------------------------------------------------------------
 
-fn handle_request(request: Request, url: &str, full_url: &str, state: &mut ServerState) {
+fn handle_request(request: Request, url: &str, full_url: &str, state: &ServerState) {
     match url {
         "/" => {
             let html = HTML.replace("{{WORLD_FINGERPRINT}}", &state.world_fingerprint);
@@ -139,18 +217,18 @@ fn handle_request(request: Request, url: &str, full_url: &str, state: &mut Serve
         "/cities" => handle_cities_viewport(request, full_url, state),
         "/villages" => handle_villages_viewport(request, full_url, state),
         "/islands.json" => {
-            state.ensure_islands_json();
-            respond(
-                request,
-                "application/json",
-                state.islands_json.as_deref().unwrap(),
-            );
+            respond(request, "application/json", state.islands_json());
         }
 
         _ if url.starts_with("/outline/") && url.ends_with(".json") => {
             handle_outline(request, url, state);
         }
+        "/city_diagnostics.json" => handle_city_diagnostics(request, full_url, state),
+        "/naval_path.json" => handle_naval_path(request, full_url, state),
+        "/land_path.json" => handle_land_path(request, full_url, state),
         _ if url.starts_with("/dtile/") => handle_debug_tile(request, url, state),
+        _ if url.starts_with("/ctile/") => handle_city_diagnostics_tile(request, url, state),
+        _ if url.starts_with("/stile/") => handle_sector_tile(request, url, state),
         _ if url.starts_with("/tile/") => handle_tile(request, url, state),
         _ => {
             let _ = request.respond(Response::from_string("Not Found").with_status_code(404));
@@ -162,7 +240,7 @@ fn handle_request(request: Request, url: &str, full_url: &str, state: &mut Serve
 // Tile handlers
 // ---------------------------------------------------------------------------
 
-fn parse_tile_coords(url: &str, prefix: &str) -> Option<(u32, u32, u32)> {
+fn parse_tile_coords(url: &str, prefix: &str) -> Option<TileXYZ> {
     let parts: Vec<&str> = url.trim_start_matches(prefix).split('/').collect();
     if parts.len() != 3 {
         return None;
@@ -170,42 +248,147 @@ fn parse_tile_coords(url: &str, prefix: &str) -> Option<(u32, u32, u32)> {
     let z: u32 = parts[0].parse().ok()?;
     let x: u32 = parts[1].parse().ok()?;
     let y: u32 = parts[2].trim_end_matches(".png").parse().ok()?;
-    Some((z, x, y))
+    Some(TileXYZ::new(z, x, y))
 }
 
-fn handle_tile(request: Request, url: &str, state: &mut ServerState) {
-    let Some((z, x, y)) = parse_tile_coords(url, "/tile/") else {
+fn handle_tile(request: Request, url: &str, state: &ServerState) {
+    let Some(tile) = parse_tile_coords(url, "/tile/") else {
         let _ = request.respond(Response::from_string("Bad Request").with_status_code(400));
         return;
     };
-    if !state.tile_cache.contains_key(&(z, x, y)) {
-        match render_tile(&mut state.world, z, x, y) {
-            Some(png) => {
-                state.tile_cache.insert((z, x, y), png);
-            }
-            None => {
+    let cached = lock(&state.tile_cache).get(&tile).cloned();
+    let png = match cached {
+        Some(png) => png,
+        // Render without holding the lock so other tiles render in parallel.
+        None => {
+            if let Some(png) = render_tile(&state.world, tile) {
+                let png = Arc::new(png);
+                lock(&state.tile_cache).insert(tile, Arc::clone(&png));
+                png
+            } else {
                 let _ = request.respond(Response::from_string("Not Found").with_status_code(404));
                 return;
             }
         }
-    }
-    let png = state.tile_cache.get(&(z, x, y)).unwrap();
+    };
     let header = Header::from_bytes("Content-Type", "image/png").unwrap();
     let cache =
         Header::from_bytes("Cache-Control", format!("public, max-age={}", 60 * 15)).unwrap();
     let _ = request.respond(
-        Response::from_data(png.clone())
+        Response::from_data(png.as_slice())
             .with_header(header)
             .with_header(cache),
     );
 }
 
-fn handle_debug_tile(request: Request, url: &str, state: &mut ServerState) {
-    let Some((z, x, y)) = parse_tile_coords(url, "/dtile/") else {
+fn handle_debug_tile(request: Request, url: &str, state: &ServerState) {
+    let Some(tile) = parse_tile_coords(url, "/dtile/") else {
+        let _ = request.respond(Response::from_string("Bad Request").with_status_code(400));
+        return;
+    };
+    match render_debug_tile(&state.world, tile) {
+        Some(png) => {
+            let header = Header::from_bytes("Content-Type", "image/png").unwrap();
+            let no_cache = Header::from_bytes("Cache-Control", "no-store").unwrap();
+            let _ = request.respond(
+                Response::from_data(png)
+                    .with_header(header)
+                    .with_header(no_cache),
+            );
+        }
+        None => {
+            let _ = request.respond(Response::from_string("Not Found").with_status_code(404));
+        }
+    }
+}
+
+fn handle_sector_tile(request: Request, url: &str, state: &ServerState) {
+    let Some(tile) = parse_tile_coords(url, "/stile/") else {
+        let _ = request.respond(Response::from_string("Bad Request").with_status_code(400));
+        return;
+    };
+    match render_sector_tile(&state.world, tile) {
+        Some(png) => {
+            let header = Header::from_bytes("Content-Type", "image/png").unwrap();
+            let no_cache = Header::from_bytes("Cache-Control", "no-store").unwrap();
+            let _ = request.respond(
+                Response::from_data(png)
+                    .with_header(header)
+                    .with_header(no_cache),
+            );
+        }
+        None => {
+            let _ = request.respond(Response::from_string("Not Found").with_status_code(404));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// City diagnostics
+// ---------------------------------------------------------------------------
+
+/// Largest box (in tiles) `/city_diagnostics.json` lists tile by tile.
+const MAX_DIAGNOSTICS_BBOX_TILES: u64 = 512 * 512;
+
+/// The `.citydiag` sidecar of the world file at `path`, if there is one
+/// and it matches the world's dimensions.
+fn load_city_diagnostics(path: &str, world: &World) -> Option<CityDiagnostics> {
+    let sidecar = save::city_diagnostics_path(path);
+    if !std::path::Path::new(&sidecar).exists() {
+        return None;
+    }
+    match save::load_city_diagnostics(&sidecar) {
+        Ok(d) if d.width == world.width() && d.height == world.height() => {
+            eprintln!("Loaded city diagnostics from {sidecar}");
+            Some(d)
+        }
+        Ok(d) => {
+            eprintln!(
+                "Ignoring {sidecar}: {}x{} does not match the world",
+                d.width, d.height
+            );
+            None
+        }
+        Err(e) => {
+            eprintln!("Ignoring {sidecar}: {e}");
+            None
+        }
+    }
+}
+
+/// The diagnostics report, or with `x0`/`y0`/`x1`/`y1` the outcome of
+/// every drawn tile in that box.
+fn handle_city_diagnostics(request: Request, full_url: &str, state: &ServerState) {
+    let Some(diagnostics) = &state.city_diagnostics else {
+        let _ = request.respond(Response::from_string("Not Found").with_status_code(404));
+        return;
+    };
+    let json = if full_url.contains('?') {
+        let (min, max) = parse_bbox(full_url, state.world.geometry());
+        let area = (u64::from(max.x.saturating_sub(min.x)) + 1)
+            * (u64::from(max.y.saturating_sub(min.y)) + 1);
+        if area > MAX_DIAGNOSTICS_BBOX_TILES {
+            let _ = request
+                .respond(Response::from_string("Bounding box too large").with_status_code(400));
+            return;
+        }
+        diagnostics.tiles_json(min, max)
+    } else {
+        diagnostics.report_json()
+    };
+    respond(request, "application/json", json);
+}
+
+fn handle_city_diagnostics_tile(request: Request, url: &str, state: &ServerState) {
+    let Some(tile) = parse_tile_coords(url, "/ctile/") else {
         let _ = request.respond(Response::from_string("Bad Request").with_status_code(400));
         return;
     };
-    match render_debug_tile(&mut state.world, z, x, y) {
+    let png = state
+        .city_diagnostics
+        .as_ref()
+        .and_then(|d| render_city_diagnostics_tile(&state.world, d, tile));
+    match png {
         Some(png) => {
             let header = Header::from_bytes("Content-Type", "image/png").unwrap();
             let no_cache = Header::from_bytes("Cache-Control", "no-store").unwrap();
@@ -225,9 +408,9 @@ fn handle_debug_tile(request: Request, url: &str, state: &mut ServerState) {
 // Viewport city handler
 // ---------------------------------------------------------------------------
 
-fn handle_cities_viewport(request: Request, full_url: &str, state: &mut ServerState) {
-    let (x0, y0, x1, y1) = parse_bbox(full_url, state.world.width(), state.world.height());
-    let json = build_cities_viewport_json(&mut state.world, x0, y0, x1, y1);
+fn handle_cities_viewport(request: Request, full_url: &str, state: &ServerState) {
+    let (min, max) = parse_bbox(full_url, state.world.geometry());
+    let json = build_cities_viewport_json(&state.world, min, max);
     let no_cache = Header::from_bytes("Cache-Control", "no-store").unwrap();
     let header = Header::from_bytes("Content-Type", "application/json").unwrap();
     let _ = request.respond(
@@ -237,52 +420,38 @@ fn handle_cities_viewport(request: Request, full_url: &str, state: &mut ServerSt
     );
 }
 
-fn build_cities_viewport_json(world: &mut World, x0: u32, y0: u32, x1: u32, y1: u32) -> String {
-    let city_slots = world.city_slots().to_vec();
-    let city_resources = world.city_resources().to_vec();
-    let cs = world.config().chunk_size as u32;
-
-    let in_bbox: Vec<(usize, u32, u32)> = city_slots
-        .iter()
-        .enumerate()
-        .filter(|(_, &(x, y))| x >= x0 && x <= x1 && y >= y0 && y <= y1)
-        .map(|(i, &(x, y))| (i, x, y))
-        .collect();
-
-    type ChunkEntry = Vec<(usize, u32, u32)>;
-    let mut by_chunk: HashMap<(u32, u32), ChunkEntry> = HashMap::new();
-    for &(i, x, y) in &in_bbox {
-        by_chunk
-            .entry((x / cs, y / cs))
-            .or_default()
-            .push((i, x, y));
-    }
-
-    let mut entries: Vec<(usize, String)> = Vec::with_capacity(in_bbox.len());
-
-    for ((cx, cy), cities) in &by_chunk {
-        let _ = world.ensure_chunk(*cx, *cy);
-        if let Some(chunk) = world.chunk(*cx, *cy) {
-            let ox = cx * cs;
-            let oy = cy * cs;
-            for &(i, x, y) in cities {
-                let lx = (x - ox) as usize;
-                let ly = (y - oy) as usize;
-                let idx = ly * chunk.width as usize + lx;
-                let rid = chunk.region_labels[idx];
-                let cr = city_resources.get(i).copied().unwrap_or_default();
-                let biome_name = world_generator::biome::Biome::from_u8(cr.dominant_biome).name();
-                entries.push((i, format!(
-                    "[{x},{y},{rid},{{\"wood\":{},\"stone\":{},\"food\":{},\"metal\":{},\"favor\":{},\"gold_nodes\":{},\"biome\":\"{biome_name}\"}}]",
-                    cr.wood, cr.stone, cr.food, cr.metal, cr.favor, cr.gold_nodes
-                )));
-            }
-        }
+fn build_cities_viewport_json(world: &World, min: WorldPos, max: WorldPos) -> String {
+    let city_resources = world.city_resources();
+
+    let city_slots = world.city_slots();
+    let city_regions = world.city_regions();
+    let city_sites = world.city_sites();
+
+    let mut entries: Vec<String> = Vec::new();
+    for i in world.city_index().in_bbox(min, max) {
+        let (x, y) = city_slots[i];
+        let rid = city_regions[i];
+        let cr = city_resources.get(i).copied().unwrap_or_default();
+        let biome_name = world_generator::biome::Biome::from_u8(cr.dominant_biome).name();
+        // Site fields are omitted for files older than version 7.
+        let site = city_sites.get(i).map_or(String::new(), |s| {
+            format!(
+                ",\"facing\":\"{}\",\"harbor\":{},\"coast\":\"{}\",\"elevation\":{:.4},\"hinterland\":{},\"water_body\":{}",
+                s.facing.abbrev(),
+                s.harbor_quality,
+                s.coast.name(),
+                s.elevation,
+                s.hinterland,
+                s.water_body_size
+            )
+        });
+        entries.push(format!(
+            "[{x},{y},{rid},{{\"wood\":{},\"stone\":{},\"food\":{},\"metal\":{},\"favor\":{},\"gold_nodes\":{},\"biome\":\"{biome_name}\"{site}}}]",
+            cr.wood, cr.stone, cr.food, cr.metal, cr.favor, cr.gold_nodes
+        ));
     }
 
-    entries.sort_by_key(|&(i, _)| i);
-    let parts: Vec<&str> = entries.iter().map(|(_, s)| s.as_str()).collect();
-    format!("[{}]", parts.join(","))
+    format!("[{}]", entries.join(","))
 }
 
 // ---------------------------------------------------------------------------
@@ -295,9 +464,9 @@ fn build_cities_viewport_json(world: &mut World, x0: u32, y0: u32, x1: u32, y1:
 ///
 /// Returns a JSON array:
 /// `[[x, y, region_id, offers_name, demands_name, biome_name], ...]`
-fn handle_villages_viewport(request: Request, full_url: &str, state: &mut ServerState) {
-    let (x0, y0, x1, y1) = parse_bbox(full_url, state.world.width(), state.world.height());
-    let json = build_villages_viewport_json(&state.world, x0, y0, x1, y1);
+fn handle_villages_viewport(request: Request, full_url: &str, state: &ServerState) {
+    let (min, max) = parse_bbox(full_url, state.world.geometry());
+    let json = build_villages_viewport_json(&state.world, min, max);
     let no_cache = Header::from_bytes("Cache-Control", "no-store").unwrap();
     let header = Header::from_bytes("Content-Type", "application/json").unwrap();
     let _ = request.respond(
@@ -307,25 +476,25 @@ fn handle_villages_viewport(request: Request, full_url: &str, state: &mut Server
     );
 }
 
-fn build_villages_viewport_json(world: &World, x0: u32, y0: u32, x1: u32, y1: u32) -> String {
+fn build_villages_viewport_json(world: &World, min: WorldPos, max: WorldPos) -> String {
+    let villages = world.villages();
     let entries: Vec<String> = world
-        .villages()
-        .iter()
-        .filter(|v| {
-            let vx = v.x as u32;
-            let vy = v.y as u32;
-            vx >= x0 && vx <= x1 && vy >= y0 && vy <= y1
-        })
-        .map(|v| {
+        .village_index()
+        .in_bbox(min, max)
+        .into_iter()
+        .map(|i| {
+            let v = &villages[i];
             let biome_name = world_generator::biome::Biome::from_u8(v.biome).name();
+            let name = world.village_names().get(i).map_or("", String::as_str);
             format!(
-                "[{},{},{},\"{}\",\"{}\",\"{}\"]",
+                "[{},{},{},\"{}\",\"{}\",\"{}\",\"{}\"]",
                 v.x,
                 v.y,
                 v.region_id,
                 v.trade.offers.name(),
                 v.trade.demands.name(),
                 biome_name,
+                name,
             )
         })
         .collect();
@@ -333,11 +502,75 @@ fn build_villages_viewport_json(world: &World, x0: u32, y0: u32, x1: u32, y1: u3
     format!("[{}]", entries.join(","))
 }
 
+// ---------------------------------------------------------------------------
+// Route handlers
+// ---------------------------------------------------------------------------
+
+/// Sea route between two cities: `?from=<city>&to=<city>`, plus
+/// `&diagonal=0` for straight moves only. Answers
+/// `{"distance":d,"travel_time":t,"path":[[x,y],...]}`, or `null` if the
+/// cities are not connected by sea.
+fn handle_naval_path(request: Request, full_url: &str, state: &ServerState) {
+    let Some((from, to, diagonal)) = parse_route_query(full_url) else {
+        let _ = request.respond(Response::from_string("Bad Request").with_status_code(400));
+        return;
+    };
+    let options = NavOptions {
+        diagonal,
+        ..NavOptions::default()
+    };
+    respond_route(request, state.world.city_naval_path(from, to, &options));
+}
+
+/// Overland route between two cities, with the same query and answer as
+/// [`handle_naval_path`]; `null` if the cities are on different islands.
+fn handle_land_path(request: Request, full_url: &str, state: &ServerState) {
+    let Some((from, to, diagonal)) = parse_route_query(full_url) else {
+        let _ = request.respond(Response::from_string("Bad Request").with_status_code(400));
+        return;
+    };
+    let options = LandOptions {
+        diagonal,
+        ..LandOptions::default()
+    };
+    respond_route(request, state.world.city_land_path(from, to, &options));
+}
+
+/// `(from, to, diagonal)` of a route query.
+fn parse_route_query(full_url: &str) -> Option<(usize, usize, bool)> {
+    let query = full_url.split_once('?').map_or("", |(_, q)| q);
+    let (mut from, mut to, mut diagonal) = (None, None, true);
+    for pair in query.split('&') {
+        let (key, val) = pair.split_once('=').unwrap_or((pair, ""));
+        match key {
+            "from" => from = val.parse::<usize>().ok(),
+            "to" => to = val.parse::<usize>().ok(),
+            "diagonal" => diagonal = !matches!(val, "0" | "false"),
+            _ => {}
+        }
+    }
+    Some((from?, to?, diagonal))
+}
+
+fn respond_route(request: Request, route: std::io::Result<Option<NavPath>>) {
+    match route {
+        Ok(path) => respond(request, "application/json", path_to_json(path.as_ref())),
+        Err(e) => {
+            let status = if e.kind() == std::io::ErrorKind::InvalidInput {
+                400
+            } else {
+                500
+            };
+            let _ = request.respond(Response::from_string(e.to_string()).with_status_code(status));
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Outline handler
 // ---------------------------------------------------------------------------
 
-fn handle_outline(request: Request, url: &str, state: &mut ServerState) {
+fn handle_outline(request: Request, url: &str, state: &ServerState) {
     let id_str = url
         .trim_start_matches("/outline/")
         .trim_end_matches(".json");
@@ -348,11 +581,8 @@ fn handle_outline(request: Request, url: &str, state: &mut ServerState) {
             return;
         }
     };
-    state.ensure_island_outlines();
     let json = state
-        .island_outlines
-        .as_ref()
-        .unwrap()
+        .island_outlines()
         .get(&rid)
         .map(String::as_str)
         .unwrap_or("[]");
@@ -363,12 +593,29 @@ fn handle_outline(request: Request, url: &str, state: &mut ServerState) {
 // JSON builders
 // ---------------------------------------------------------------------------
 
+fn path_to_json(path: Option<&NavPath>) -> String {
+    let Some(path) = path else {
+        return "null".to_string();
+    };
+    let tiles: Vec<String> = path
+        .tiles
+        .iter()
+        .map(|t| format!("[{},{}]", t.x, t.y))
+        .collect();
+    format!(
+        "{{\"distance\":{:.2},\"travel_time\":{:.2},\"path\":[{}]}}",
+        path.distance,
+        path.travel_time,
+        tiles.join(",")
+    )
+}
+
 fn islands_to_json(islands: &[Island]) -> String {
     let entries: Vec<String> = islands
         .iter()
         .map(|i| {
             format!(
-                "[{},{},{},{},{},{},{},{},{},{}]",
+                "[{},{},{},{},{},{},{},{},{},{},{},{},\"{}\"]",
                 i.id,
                 i.centroid.0,
                 i.centroid.1,
@@ -379,6 +626,9 @@ fn islands_to_json(islands: &[Island]) -> String {
                 i.bounds.max_y,
                 i.is_world_spawn as u8,
                 i.spawn_order,
+                i.area,
+                i.perimeter,
+                i.name,
             )
         })
         .collect();
@@ -389,11 +639,9 @@ fn islands_to_json(islands: &[Island]) -> String {
 // Island outline tracing
 // ---------------------------------------------------------------------------
 
-fn build_island_outlines(world: &mut World) -> HashMap<u32, String> {
-    world.ensure_islands_computed();
+fn build_island_outlines(world: &World) -> HashMap<u32, String> {
     let islands = world.islands().to_vec();
-    let map_w = world.width();
-    let map_h = world.height();
+    let geometry = world.geometry();
     let step = 4u32;
     let mut outlines = HashMap::new();
 
@@ -402,8 +650,10 @@ fn build_island_outlines(world: &mut World) -> HashMap<u32, String> {
         let pad = step * 2;
         let x0 = bb.min_x.saturating_sub(pad);
         let y0 = bb.min_y.saturating_sub(pad);
-        let x1 = (bb.max_x + pad).min(map_w - 1);
-        let y1 = (bb.max_y + pad).min(map_h - 1);
+        let WorldPos { x: x1, y: y1 } = geometry.clamp(WorldPos::new(
+            bb.max_x.saturating_add(pad),
+            bb.max_y.saturating_add(pad),
+        ));
         let gw = ((x1 - x0) / step + 1) as usize;
         let gh = ((y1 - y0) / step + 1) as usize;
 
@@ -412,14 +662,17 @@ fn build_island_outlines(world: &mut World) -> HashMap<u32, String> {
             continue;
         }
 
+        let origin = WorldPos::new(x0, y0);
+        let Ok(rect) = world.read_rect(origin, x1 - x0 + 1, y1 - y0 + 1, Layers::REGION_LABELS)
+        else {
+            outlines.insert(island.id, "[]".to_owned());
+            continue;
+        };
         let mut grid = vec![false; gw * gh];
         for gy in 0..gh {
             for gx in 0..gw {
-                let wx = x0 + gx as u32 * step;
-                let wy = y0 + gy as u32 * step;
-                if wx < map_w && wy < map_h {
-                    grid[gy * gw + gx] = world.region_label_at_cached(wx, wy) == island.id;
-                }
+                let pos = WorldPos::new(x0 + gx as u32 * step, y0 + gy as u32 * step);
+                grid[gy * gw + gx] = rect.region_labels[rect.index_of(pos)] == island.id;
             }
         }
 
@@ -455,8 +708,10 @@ fn build_island_outlines(world: &mut World) -> HashMap<u32, String> {
 // Shared helpers
 // ---------------------------------------------------------------------------
 
-/// Parse `x0`, `y0`, `x1`, `y1` query params, clamped to world bounds.
-fn parse_bbox(full_url: &str, world_w: u32, world_h: u32) -> (u32, u32, u32, u32) {
+/// Parse `x0`, `y0`, `x1`, `y1` query params into the inclusive corners of
+/// a box, clamped to world bounds.
+fn parse_bbox(full_url: &str, geometry: MapGeometry) -> (WorldPos, WorldPos) {
+    let (world_w, world_h) = (geometry.width, geometry.height);
     let query = full_url.split_once('?').map(|(_, q)| q).unwrap_or("");
     let mut x0 = 0u32;
     let mut y0 = 0u32;
@@ -474,7 +729,7 @@ fn parse_bbox(full_url: &str, world_w: u32, world_h: u32) -> (u32, u32, u32, u32
             _ => {}
         }
     }
-    (x0, y0, x1, y1)
+    (WorldPos::new(x0, y0), WorldPos::new(x1, y1))
 }
 
 fn inject_config(template: &str, state: &ServerState) -> String {
@@ -483,6 +738,19 @@ fn inject_config(template: &str, state: &ServerState) -> String {
         .replace("{{ MAP_SIZE }}", &cfg.map_size.to_string())
         .replace("{{ TILE_SIZE }}", &TILE_SIZE.to_string())
         .replace("{{ MAX_ZOOM }}", &cfg.max_zoom().to_string())
+        .replace(
+            "{{ CITY_DIAGNOSTICS }}",
+            &state.city_diagnostics.is_some().to_string(),
+        )
+        .replace(
+            "{{ SECTORS }}",
+            &state.world.sectors().is_some().to_string(),
+        )
+}
+
+/// Lock a mutex, ignoring poisoning (a panicked handler leaves the caches usable).
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
 fn respond(request: Request, content_type: &str, body: impl Into<String>) {
@@ -1,15 +1,20 @@
 //! Per-city aggregated resource profiles.
 //!
-//! [`CityResources`] is computed once during world generation by scanning a
-//! circular tile neighbourhood around each city slot and aggregating biome
-//! modifiers. The result is written into the world file header so the game
-//! and viewer can display resource previews at no runtime cost.
+//! [`CityResources`] is computed once during world generation by scanning the
+//! tiles around each city slot and aggregating biome modifiers. The result is
+//! written into the world file header so the game and viewer can display
+//! resource previews at no runtime cost.
+//!
+//! [`ResourceArea`] picks the tiles: a fixed circle around the slot, or the
+//! city's own [`territory`](crate::territory) so neighbouring cities do not
+//! count the same forest twice.
 
 use std::collections::HashMap;
 
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use crate::biome::{gold::GoldVeinSampler, Biome};
+use crate::territory::{self, UNOWNED};
 
 // ---------------------------------------------------------------------------
 // Constants
@@ -22,6 +27,43 @@ use crate::biome::{gold::GoldVeinSampler, Biome};
 /// a reasonable farmable hinterland for a single city.
 pub const CITY_SCAN_RADIUS: i32 = 6;
 
+// ---------------------------------------------------------------------------
+// ResourceArea
+// ---------------------------------------------------------------------------
+
+/// Which tiles a city's [`CityResources`] are aggregated over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResourceArea {
+    /// Every tile within [`CITY_SCAN_RADIUS`]. Circles of neighbouring
+    /// cities overlap, so shared tiles count for each of them.
+    #[default]
+    Circle,
+    /// The Land tiles of the city's territory, plus the water (and land no
+    /// city owns) within [`CITY_SCAN_RADIUS`]: fishing grounds are not
+    /// partitioned.
+    Territory,
+}
+
+impl ResourceArea {
+    /// Parse the `CITY_RESOURCE_AREA` value (`circle` or `territory`).
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "circle" => Some(Self::Circle),
+            "territory" => Some(Self::Territory),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Circle => "circle",
+            Self::Territory => "territory",
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // CityResources
 // ---------------------------------------------------------------------------
@@ -31,7 +73,7 @@ pub const CITY_SCAN_RADIUS: i32 = 6;
 /// Stored in the world file, parallel to `city_slots`.
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct CityResources {
-    /// Aggregate passive wood modifier (average of tile bonuses in the scan radius).
+    /// Aggregate passive wood modifier (average of tile bonuses in the scanned area).
     pub wood: i16,
     /// Aggregate passive stone modifier.
     pub stone: i16,
@@ -41,12 +83,12 @@ pub struct CityResources {
     pub metal: i16,
     /// Aggregate passive Favor modifier (scaled by island size, see below).
     pub favor: i16,
-    /// Number of gold-vein tiles within the scan radius.
+    /// Number of gold-vein tiles within the scanned area.
     ///
     /// Gold is **never** passive — each node must be actively farmed.
     /// This count tells the player how many farmable sources are available.
     pub gold_nodes: u8,
-    /// Dominant biome within the scan radius (for UI display).
+    /// Dominant biome within the scanned area (for UI display).
     pub dominant_biome: u8,
 }
 
@@ -56,6 +98,11 @@ pub struct CityResources {
 
 /// Compute [`CityResources`] for every city slot.
 ///
+/// With `territory` (the grid from
+/// [`compute_territory`](crate::territory::compute_territory)) resources
+/// are aggregated as [`ResourceArea::Territory`], otherwise as
+/// [`ResourceArea::Circle`].
+///
 /// # Island-size Favor multiplier
 ///
 /// Small islands (near `min_cities_per_island`) receive a large Favor bonus,
@@ -69,10 +116,12 @@ pub struct CityResources {
 /// ```
 ///
 /// Returns a `Vec` parallel to `city_slots`.
+#[allow(clippy::cast_possible_truncation)]
 pub fn compute_city_resources(
     city_slots: &[(usize, usize)],
     biomes: &[Vec<u8>],
     region_labels: &[Vec<usize>],
+    territory: Option<&[Vec<u32>]>,
     region_city_counts: &HashMap<usize, u32>,
     min_cities_per_island: u32,
     seed: u32,
@@ -82,18 +131,16 @@ pub fn compute_city_resources(
     let r = CITY_SCAN_RADIUS;
     let min_f = min_cities_per_island.max(1) as f64;
     let gold_sampler = GoldVeinSampler::new(seed);
+    let owned = territory.map(|t| OwnedTiles::new(t, city_slots.len()));
 
     city_slots
         .par_iter()
-        .map(|&(cx, cy)| {
-            let mut wood_sum: i32 = 0;
-            let mut stone_sum: i32 = 0;
-            let mut food_sum: i32 = 0;
-            let mut metal_sum: i32 = 0;
-            let mut favor_sum: i32 = 0;
-            let mut gold_nodes: u8 = 0;
-            let mut tile_count: u32 = 0;
-            let mut biome_counts = [0u32; 16];
+        .enumerate()
+        .map(|(i, &(cx, cy))| {
+            let mut tally = Tally::default();
+            let mut add = |tx: usize, ty: usize| {
+                tally.add(Biome::from_u8(biomes[ty][tx]), tx, ty, &gold_sampler);
+            };
 
             for dy in -r..=r {
                 for dx in -r..=r {
@@ -108,35 +155,27 @@ pub fn compute_city_resources(
                         continue;
                     }
                     let (tx, ty) = (tx as usize, ty as usize);
-
-                    let biome = Biome::from_u8(biomes[ty][tx]);
-                    let mods = biome.resource_modifiers();
-                    wood_sum += mods.wood as i32;
-                    stone_sum += mods.stone as i32;
-                    food_sum += mods.food as i32;
-                    metal_sum += mods.metal as i32;
-                    favor_sum += mods.favor as i32;
-                    tile_count += 1;
-
-                    let b = biome.to_u8() as usize;
-                    if b < 16 {
-                        biome_counts[b] += 1;
-                    }
-
-                    if gold_sampler.is_gold(tx, ty, biome) {
-                        gold_nodes = gold_nodes.saturating_add(1);
+                    // Owned land is counted below, by its owner only.
+                    if territory.is_some_and(|t| t[ty][tx] != UNOWNED) {
+                        continue;
                     }
+                    add(tx, ty);
+                }
+            }
+            if let Some(owned) = &owned {
+                for &(tx, ty) in owned.of(i) {
+                    add(tx as usize, ty as usize);
                 }
             }
 
-            let n = tile_count.max(1) as i32;
+            let n = i32::try_from(tally.tiles.max(1)).unwrap_or(i32::MAX);
 
-            let dominant = biome_counts
+            let dominant = tally
+                .biome_counts
                 .iter()
-                .enumerate()
-                .max_by_key(|&(_, &c)| c)
-                .map(|(i, _)| i as u8)
-                .unwrap_or(0);
+                .zip(0..=u8::MAX)
+                .max_by_key(|&(&c, _)| c)
+                .map_or(0, |(_, b)| b);
 
             // Island-size Favor multiplier.
             let region_id = region_labels[cy][cx];
@@ -145,18 +184,86 @@ pub fn compute_city_resources(
                 .copied()
                 .unwrap_or(min_cities_per_island) as f64;
             let ratio = (min_f / island_cities).min(1.0);
-            let favor_multiplier = 1.0 + 2.0 * ratio * ratio;
-            let scaled_favor = ((favor_sum / n) as f64 * favor_multiplier).round();
+            let favor_multiplier = (2.0 * ratio).mul_add(ratio, 1.0);
+            let scaled_favor = (f64::from(tally.favor / n) * favor_multiplier).round();
 
             CityResources {
-                wood: (wood_sum / n).clamp(-3200, 3200) as i16,
-                stone: (stone_sum / n).clamp(-3200, 3200) as i16,
-                food: (food_sum / n).clamp(-3200, 3200) as i16,
-                metal: (metal_sum / n).clamp(-3200, 3200) as i16,
+                wood: (tally.wood / n).clamp(-3200, 3200) as i16,
+                stone: (tally.stone / n).clamp(-3200, 3200) as i16,
+                food: (tally.food / n).clamp(-3200, 3200) as i16,
+                metal: (tally.metal / n).clamp(-3200, 3200) as i16,
                 favor: (scaled_favor as i32).clamp(-3200, 3200) as i16,
-                gold_nodes,
+                gold_nodes: tally.gold_nodes,
                 dominant_biome: dominant,
             }
         })
         .collect()
 }
+
+/// Running sums over the tiles a city draws resources from.
+#[derive(Default)]
+struct Tally {
+    wood: i32,
+    stone: i32,
+    food: i32,
+    metal: i32,
+    favor: i32,
+    gold_nodes: u8,
+    tiles: u32,
+    biome_counts: [u32; 16],
+}
+
+impl Tally {
+    fn add(&mut self, biome: Biome, x: usize, y: usize, gold_sampler: &GoldVeinSampler) {
+        let mods = biome.resource_modifiers();
+        self.wood += i32::from(mods.wood);
+        self.stone += i32::from(mods.stone);
+        self.food += i32::from(mods.food);
+        self.metal += i32::from(mods.metal);
+        self.favor += i32::from(mods.favor);
+        self.tiles += 1;
+
+        let b = biome.to_u8() as usize;
+        if b < 16 {
+            self.biome_counts[b] += 1;
+        }
+
+        if gold_sampler.is_gold(x, y, biome) {
+            self.gold_nodes = self.gold_nodes.saturating_add(1);
+        }
+    }
+}
+
+/// Every owned tile of a territory grid, grouped by owning city.
+struct OwnedTiles {
+    /// City `i` owns `tiles[start[i]..start[i + 1]]`.
+    start: Vec<usize>,
+    tiles: Vec<(u32, u32)>,
+}
+
+impl OwnedTiles {
+    #[allow(clippy::cast_possible_truncation)]
+    fn new(territory: &[Vec<u32>], num_cities: usize) -> Self {
+        // Counting sort of tiles by owner.
+        let sizes = territory::territory_sizes(territory, num_cities);
+        let mut start = vec![0usize; num_cities + 1];
+        for (i, &size) in sizes.iter().enumerate() {
+            start[i + 1] = start[i] + size as usize;
+        }
+        let mut fill = start.clone();
+        let mut tiles = vec![(0u32, 0u32); start[num_cities]];
+        for (y, row) in territory.iter().enumerate() {
+            for (x, &value) in row.iter().enumerate() {
+                if let Some(i) = territory::owner_index(value).filter(|&i| i < num_cities) {
+                    tiles[fill[i]] = (x as u32, y as u32);
+                    fill[i] += 1;
+                }
+            }
+        }
+        Self { start, tiles }
+    }
+
+    fn of(&self, city: usize) -> &[(u32, u32)] {
+        &self.tiles[self.start[city]..self.start[city + 1]]
+    }
+}
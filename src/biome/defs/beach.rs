@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Beach",
     modifiers: ResourceModifiers::new(-10, -5, 10, 0, 5),
     has_gold_veins: false,
+    movement_cost: Some(1.2),
     land_color: Some(|t| {
         [
             (210.0 + t * 25.0) as u8,
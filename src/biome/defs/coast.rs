@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Coast",
     modifiers: ResourceModifiers::new(0, -5, 15, 0, -10),
     has_gold_veins: false,
+    movement_cost: None,
     land_color: None,
     water_color: Some(|e, wt| {
         let t = ((e - 0.18) / (wt - 0.18)).clamp(0.0, 1.0).powf(0.7);
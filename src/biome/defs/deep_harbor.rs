@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Deep Harbor",
     modifiers: ResourceModifiers::new(-5, -10, 20, -5, 0),
     has_gold_veins: true,
+    movement_cost: None,
     land_color: None,
     water_color: Some(|e, wt| {
         let t = (e / wt).clamp(0.0, 1.0);
@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Desert",
     modifiers: ResourceModifiers::new(-20, 20, -25, 15, 10),
     has_gold_veins: true,
+    movement_cost: Some(1.6),
     land_color: Some(|t| {
         [
             (185.0 + t * 40.0) as u8,
@@ -8,6 +8,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Far Land",
     modifiers: ResourceModifiers::new(0, 0, 0, 0, 0),
     has_gold_veins: false,
+    movement_cost: None,
     land_color: Some(|t| {
         // Washed-out beige — matches the `Terrain::FarLand` branch in
         // `Biome::get_color`, but using the land-rescaled `t` here.
@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Forest",
     modifiers: ResourceModifiers::new(30, -5, 10, -20, -15),
     has_gold_veins: false,
+    movement_cost: Some(1.5),
     // Sinusoidal elevation variation gives a canopy texture.
     land_color: Some(|t| {
         // `t` is in [0,1] over the land elevation range; reconstruct raw `e`
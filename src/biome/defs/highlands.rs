@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Highlands",
     modifiers: ResourceModifiers::new(-15, 20, -10, 20, -15),
     has_gold_veins: true,
+    movement_cost: Some(1.6),
     land_color: Some(|t| {
         [
             (100.0 + t * 75.0) as u8,
@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Hills",
     modifiers: ResourceModifiers::new(-5, 25, 5, 5, -30),
     has_gold_veins: false,
+    movement_cost: Some(1.8),
     land_color: Some(|t| {
         [
             (100.0 + t * 55.0) as u8,
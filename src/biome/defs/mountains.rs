@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Mountains",
     modifiers: ResourceModifiers::new(-20, 25, -20, 25, -10),
     has_gold_veins: true,
+    movement_cost: Some(3.0),
     land_color: Some(|t| {
         [
             (115.0 + t * 50.0) as u8,
@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Ocean",
     modifiers: ResourceModifiers::new(0, 0, 15, 0, -15),
     has_gold_veins: false,
+    movement_cost: None,
     land_color: None,
     water_color: Some(|e, wt| {
         let t = ((e - 0.10) / (wt - 0.10)).clamp(0.0, 1.0).powf(0.7);
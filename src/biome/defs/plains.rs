@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Plains",
     modifiers: ResourceModifiers::new(5, -10, 25, -15, -5),
     has_gold_veins: false,
+    movement_cost: Some(1.0),
     land_color: Some(|t| {
         [
             (75.0 + t * 80.0) as u8,
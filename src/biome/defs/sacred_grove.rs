@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Sacred Grove",
     modifiers: ResourceModifiers::new(10, -20, 5, -25, 30),
     has_gold_veins: false,
+    movement_cost: Some(1.3),
     land_color: Some(|t| {
         [
             (15.0 + t * 65.0) as u8,
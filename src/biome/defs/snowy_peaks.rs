@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Snowy Peaks",
     modifiers: ResourceModifiers::new(-15, 5, -30, 35, 5),
     has_gold_veins: true,
+    movement_cost: Some(4.0),
     land_color: Some(|t| {
         [
             (170.0 + t * 70.0) as u8,
@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Swamp",
     modifiers: ResourceModifiers::new(5, -20, 15, -10, 10),
     has_gold_veins: false,
+    movement_cost: Some(2.5),
     land_color: Some(|t| {
         [
             (45.0 + t * 40.0) as u8,
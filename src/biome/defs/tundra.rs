@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Tundra",
     modifiers: ResourceModifiers::new(-15, 5, -20, 25, 5),
     has_gold_veins: false,
+    movement_cost: Some(1.7),
     land_color: Some(|t| {
         [
             (95.0 + t * 100.0) as u8,
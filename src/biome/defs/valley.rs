@@ -4,6 +4,7 @@ pub const DATA: BiomeData = BiomeData {
     name: "Valley",
     modifiers: ResourceModifiers::new(15, -5, 20, -15, -15),
     has_gold_veins: true,
+    movement_cost: Some(1.0),
     land_color: Some(|t| {
         [
             (55.0 + t * 55.0) as u8,
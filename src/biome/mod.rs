@@ -19,7 +19,7 @@ pub mod gold;
 
 // Re-export the most commonly used types so callers can write
 // `biome::generate_biomes` / `biome::CityResources` without extra path depth.
-pub use city_resources::{compute_city_resources, CityResources};
+pub use city_resources::{compute_city_resources, CityResources, ResourceArea};
 pub use generation::generate_biomes;
 pub use gold::GoldVeinSampler;
 
@@ -65,10 +65,15 @@ impl ResourceModifiers {
 ///   land range via `((raw_elevation - 0.5) / 0.5).clamp(0, 1)`.
 /// - `water_color`: receives `(raw_elevation, water_threshold)`.
 /// - `None` means the biome never appears in that terrain category.
+///
+/// `movement_cost` is the time an army needs to cross one tile, relative to
+/// open plains (`1.0`); `None` for biomes that never appear on land, which
+/// armies cannot enter. See [`crate::navigation`].
 pub struct BiomeData {
     pub name: &'static str,
     pub modifiers: ResourceModifiers,
     pub has_gold_veins: bool,
+    pub movement_cost: Option<f32>,
     pub land_color: Option<fn(f32) -> [u8; 3]>,
     pub water_color: Option<fn(f32, f32) -> [u8; 3]>,
 }
@@ -147,6 +152,11 @@ impl Biome {
         self.data().has_gold_veins
     }
 
+    #[must_use]
+    pub fn movement_cost(self) -> Option<f32> {
+        self.data().movement_cost
+    }
+
     // -----------------------------------------------------------------------
     // Color — replaces the old `color::get_color()` free function
     // -----------------------------------------------------------------------
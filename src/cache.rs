@@ -0,0 +1,613 @@
+//! Bounded LRU cache for decompressed chunks.
+//!
+//! [`World`](crate::world::World) keeps recently used chunks in a
+//! [`ChunkCache`] bounded by a [`CacheBudget`] (chunk count or bytes). When
+//! an insertion pushes the cache over budget, the least recently used
+//! chunks are evicted until it fits again.
+//!
+//! Chunks are handed out as `Arc<ChunkData>`: evicting a chunk only drops
+//! the cache's handle, so callers keep whatever they are using while the
+//! cache stays within budget. Chunks that are about to be used repeatedly
+//! (e.g. the area around a player) can be pinned to keep them resident;
+//! pinned chunks are never evicted and still count towards the budget.
+//!
+//! [`SharedChunkCache`] splits the cache into independently locked shards
+//! so many threads can query it at once; chunks are decompressed outside
+//! the locks.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::mem::size_of;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::save::ChunkData;
+
+/// Default budget: 256 MiB of decompressed chunk data.
+pub const DEFAULT_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Upper bound on what a [`ChunkCache`] may hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheBudget {
+    /// Keep at most this many chunks.
+    Chunks(usize),
+    /// Keep at most this many bytes of decompressed tile data.
+    Bytes(usize),
+    /// Never evict.
+    Unbounded,
+}
+
+impl Default for CacheBudget {
+    fn default() -> Self {
+        Self::Bytes(DEFAULT_CACHE_BYTES)
+    }
+}
+
+/// Counters describing cache behavior since it was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// Chunks currently resident.
+    pub chunks: usize,
+    /// Decompressed bytes currently resident.
+    pub bytes: usize,
+    /// Resident chunks that are pinned.
+    pub pinned: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from the cache (0 if there were none).
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+struct Entry {
+    chunk: Arc<ChunkData>,
+    bytes: usize,
+    last_used: u64,
+    pins: u32,
+}
+
+/// LRU cache of decompressed chunks keyed by chunk coordinates.
+pub struct ChunkCache {
+    budget: CacheBudget,
+    entries: HashMap<(u32, u32), Entry>,
+    /// `last_used` tick -> key, for unpinned entries only (oldest first).
+    lru: BTreeMap<u64, (u32, u32)>,
+    tick: u64,
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl ChunkCache {
+    /// Create an empty cache with the given budget.
+    #[must_use]
+    pub fn new(budget: CacheBudget) -> Self {
+        Self {
+            budget,
+            entries: HashMap::new(),
+            lru: BTreeMap::new(),
+            tick: 0,
+            bytes: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn budget(&self) -> CacheBudget {
+        self.budget
+    }
+
+    /// Change the budget, evicting immediately if the cache no longer fits.
+    pub fn set_budget(&mut self, budget: CacheBudget) {
+        self.budget = budget;
+        self.evict_to_budget();
+    }
+
+    /// Look up a chunk, marking it as most recently used.
+    ///
+    /// Counts as a hit or a miss in [`stats`](Self::stats).
+    pub fn get(&mut self, key: (u32, u32)) -> Option<Arc<ChunkData>> {
+        let tick = self.next_tick();
+        let Some(entry) = self.entries.get_mut(&key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        if entry.pins == 0 {
+            self.lru.remove(&entry.last_used);
+            self.lru.insert(tick, key);
+        }
+        entry.last_used = tick;
+        Some(Arc::clone(&entry.chunk))
+    }
+
+    /// Look up a chunk without touching recency or statistics.
+    #[must_use]
+    pub fn peek(&self, key: (u32, u32)) -> Option<&Arc<ChunkData>> {
+        self.entries.get(&key).map(|e| &e.chunk)
+    }
+
+    /// `true` if the chunk is resident.
+    #[must_use]
+    pub fn contains(&self, key: (u32, u32)) -> bool {
+        self.entries.contains_key(&key)
+    }
+
+    /// Return the cached chunk, or load it with `load` and insert it.
+    ///
+    /// # Errors
+    ///
+    /// Any error returned by `load`; nothing is inserted then.
+    pub fn get_or_load(
+        &mut self,
+        key: (u32, u32),
+        load: impl FnOnce() -> io::Result<ChunkData>,
+    ) -> io::Result<Arc<ChunkData>> {
+        if let Some(chunk) = self.get(key) {
+            return Ok(chunk);
+        }
+        Ok(self.insert(key, load()?))
+    }
+
+    /// Insert (or replace) a chunk as the most recently used entry, then
+    /// evict down to the budget.
+    pub fn insert(&mut self, key: (u32, u32), chunk: ChunkData) -> Arc<ChunkData> {
+        self.insert_with_pins(key, chunk, 0)
+    }
+
+    /// Like [`insert`](Self::insert), but also [`pin`](Self::pin) the chunk
+    /// before evicting, so it is resident even when it alone is over budget.
+    pub fn insert_pinned(&mut self, key: (u32, u32), chunk: ChunkData) -> Arc<ChunkData> {
+        self.insert_with_pins(key, chunk, 1)
+    }
+
+    fn insert_with_pins(
+        &mut self,
+        key: (u32, u32),
+        chunk: ChunkData,
+        new_pins: u32,
+    ) -> Arc<ChunkData> {
+        let tick = self.next_tick();
+        let chunk = Arc::new(chunk);
+        let bytes = chunk_bytes(&chunk);
+
+        let pins = match self.entries.remove(&key) {
+            Some(old) => {
+                self.bytes -= old.bytes;
+                self.lru.remove(&old.last_used);
+                old.pins + new_pins
+            }
+            None => new_pins,
+        };
+        if pins == 0 {
+            self.lru.insert(tick, key);
+        }
+        self.bytes += bytes;
+        self.entries.insert(
+            key,
+            Entry {
+                chunk: Arc::clone(&chunk),
+                bytes,
+                last_used: tick,
+                pins,
+            },
+        );
+
+        self.evict_to_budget();
+        chunk
+    }
+
+    /// Pin a resident chunk so it is never evicted. Pins nest; each call
+    /// needs a matching [`unpin`](Self::unpin).
+    ///
+    /// Returns `false` if the chunk is not resident.
+    pub fn pin(&mut self, key: (u32, u32)) -> bool {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return false;
+        };
+        if entry.pins == 0 {
+            self.lru.remove(&entry.last_used);
+        }
+        entry.pins += 1;
+        true
+    }
+
+    /// Release one pin on a chunk. Once fully unpinned it becomes eligible
+    /// for eviction again.
+    pub fn unpin(&mut self, key: (u32, u32)) {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return;
+        };
+        if entry.pins == 0 {
+            return;
+        }
+        entry.pins -= 1;
+        if entry.pins == 0 {
+            self.lru.insert(entry.last_used, key);
+            self.evict_to_budget();
+        }
+    }
+
+    /// Drop every unpinned chunk.
+    pub fn clear(&mut self) {
+        while self.evict_oldest() {}
+    }
+
+    /// Current counters and occupancy.
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            chunks: self.entries.len(),
+            bytes: self.bytes,
+            pinned: self.entries.values().filter(|e| e.pins > 0).count(),
+        }
+    }
+
+    // -- Internals ----------------------------------------------------------
+
+    const fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn over_budget(&self) -> bool {
+        match self.budget {
+            CacheBudget::Chunks(max) => self.entries.len() > max,
+            CacheBudget::Bytes(max) => self.bytes > max,
+            CacheBudget::Unbounded => false,
+        }
+    }
+
+    /// Evict least recently used chunks until the cache fits its budget or
+    /// only pinned chunks remain.
+    fn evict_to_budget(&mut self) {
+        while self.over_budget() && self.evict_oldest() {}
+    }
+
+    /// Evict the least recently used unpinned chunk. Returns `false` if
+    /// there was none.
+    fn evict_oldest(&mut self) -> bool {
+        let Some((_, key)) = self.lru.pop_first() else {
+            return false;
+        };
+        if let Some(entry) = self.entries.remove(&key) {
+            self.bytes -= entry.bytes;
+            self.evictions += 1;
+        }
+        true
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Thread-safe sharded cache
+// ---------------------------------------------------------------------------
+
+/// Number of independently locked shards in a [`SharedChunkCache`].
+const SHARDS: usize = 16;
+
+/// Thread-safe chunk cache made of [`SHARDS`] [`ChunkCache`]s.
+///
+/// Each chunk lives in one shard chosen from its coordinates, and the
+/// budget is split evenly between shards, so eviction order is only
+/// least-recently-used within a shard.
+pub struct SharedChunkCache {
+    shards: Vec<Mutex<ChunkCache>>,
+    budget: Mutex<CacheBudget>,
+}
+
+impl SharedChunkCache {
+    /// Create an empty cache with the given total budget.
+    #[must_use]
+    pub fn new(budget: CacheBudget) -> Self {
+        Self {
+            shards: (0..SHARDS)
+                .map(|_| Mutex::new(ChunkCache::new(shard_budget(budget))))
+                .collect(),
+            budget: Mutex::new(budget),
+        }
+    }
+
+    /// Total budget across all shards.
+    pub fn budget(&self) -> CacheBudget {
+        *lock(&self.budget)
+    }
+
+    /// Change the total budget, evicting immediately if needed.
+    pub fn set_budget(&self, budget: CacheBudget) {
+        *lock(&self.budget) = budget;
+        for shard in &self.shards {
+            lock(shard).set_budget(shard_budget(budget));
+        }
+    }
+
+    /// Return the cached chunk, or load it with `load` and insert it.
+    ///
+    /// `load` runs without holding any lock. If two threads miss on the
+    /// same chunk at once, both load it and the first insertion wins.
+    ///
+    /// # Errors
+    ///
+    /// Any error returned by `load`; nothing is inserted then.
+    pub fn get_or_load(
+        &self,
+        key: (u32, u32),
+        load: impl FnOnce() -> io::Result<ChunkData>,
+    ) -> io::Result<Arc<ChunkData>> {
+        let cached = lock(self.shard(key)).get(key);
+        if let Some(chunk) = cached {
+            return Ok(chunk);
+        }
+        let chunk = load()?;
+        let mut shard = lock(self.shard(key));
+        if let Some(existing) = shard.peek(key) {
+            return Ok(Arc::clone(existing));
+        }
+        Ok(shard.insert(key, chunk))
+    }
+
+    /// Like [`get_or_load`](Self::get_or_load), but also pin the chunk.
+    ///
+    /// The chunk is found or inserted and pinned under one lock, so no
+    /// other thread can evict it in between.
+    ///
+    /// # Errors
+    ///
+    /// Any error returned by `load`; nothing is inserted or pinned then.
+    pub fn get_or_load_pinned(
+        &self,
+        key: (u32, u32),
+        load: impl FnOnce() -> io::Result<ChunkData>,
+    ) -> io::Result<Arc<ChunkData>> {
+        {
+            let mut shard = lock(self.shard(key));
+            if let Some(chunk) = shard.get(key) {
+                if shard.pin(key) {
+                    return Ok(chunk);
+                }
+            }
+        }
+        let chunk = load()?;
+        let mut shard = lock(self.shard(key));
+        if let Some(existing) = shard.peek(key).cloned() {
+            if shard.pin(key) {
+                return Ok(existing);
+            }
+        }
+        Ok(shard.insert_pinned(key, chunk))
+    }
+
+    /// Look up a chunk without touching recency or statistics.
+    pub fn peek(&self, key: (u32, u32)) -> Option<Arc<ChunkData>> {
+        lock(self.shard(key)).peek(key).cloned()
+    }
+
+    /// See [`ChunkCache::pin`].
+    pub fn pin(&self, key: (u32, u32)) -> bool {
+        lock(self.shard(key)).pin(key)
+    }
+
+    /// See [`ChunkCache::unpin`].
+    pub fn unpin(&self, key: (u32, u32)) {
+        lock(self.shard(key)).unpin(key);
+    }
+
+    /// Drop every unpinned chunk.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            lock(shard).clear();
+        }
+    }
+
+    /// Counters and occupancy summed over all shards.
+    pub fn stats(&self) -> CacheStats {
+        self.shards
+            .iter()
+            .map(|shard| lock(shard).stats())
+            .fold(CacheStats::default(), |acc, s| CacheStats {
+                hits: acc.hits + s.hits,
+                misses: acc.misses + s.misses,
+                evictions: acc.evictions + s.evictions,
+                chunks: acc.chunks + s.chunks,
+                bytes: acc.bytes + s.bytes,
+                pinned: acc.pinned + s.pinned,
+            })
+    }
+
+    fn shard(&self, (cx, cy): (u32, u32)) -> &Mutex<ChunkCache> {
+        // Neighbouring chunks land in different shards.
+        let idx = (cx as usize).wrapping_mul(31).wrapping_add(cy as usize) % SHARDS;
+        &self.shards[idx]
+    }
+}
+
+/// Share of `budget` given to each shard (rounded up).
+const fn shard_budget(budget: CacheBudget) -> CacheBudget {
+    match budget {
+        CacheBudget::Chunks(n) => CacheBudget::Chunks(n.div_ceil(SHARDS)),
+        CacheBudget::Bytes(n) => CacheBudget::Bytes(n.div_ceil(SHARDS)),
+        CacheBudget::Unbounded => CacheBudget::Unbounded,
+    }
+}
+
+/// Lock a mutex, recovering the data if another thread panicked while
+/// holding it (the cache stays structurally valid between operations).
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Decompressed size of a chunk's tile layers.
+const fn chunk_bytes(chunk: &ChunkData) -> usize {
+    chunk.terrain.len()
+        + chunk.elevation.len() * size_of::<f32>()
+        + chunk.region_labels.len() * size_of::<u32>()
+        + chunk.biomes.len()
+        + chunk.territory.len() * size_of::<u32>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2 chunk whose every elevation is `id`, to tell chunks apart.
+    fn chunk(id: u8) -> ChunkData {
+        ChunkData {
+            width: 2,
+            height: 2,
+            terrain: vec![1; 4],
+            elevation: vec![f32::from(id); 4],
+            region_labels: vec![1; 4],
+            biomes: vec![0; 4],
+            territory: vec![0; 4],
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used_first() {
+        let mut cache = ChunkCache::new(CacheBudget::Chunks(2));
+        cache.insert((0, 0), chunk(0));
+        cache.insert((1, 0), chunk(1));
+        // Touch (0, 0) so (1, 0) becomes the oldest.
+        assert!(cache.get((0, 0)).is_some());
+        cache.insert((2, 0), chunk(2));
+        assert!(cache.contains((0, 0)));
+        assert!(!cache.contains((1, 0)));
+        assert!(cache.contains((2, 0)));
+
+        // peek does not count as a use.
+        assert!(cache.peek((0, 0)).is_some());
+        cache.insert((3, 0), chunk(3));
+        assert!(!cache.contains((0, 0)));
+        assert!(cache.contains((2, 0)));
+        assert_eq!(cache.stats().evictions, 2);
+    }
+
+    #[test]
+    fn byte_budget_counts_tile_layers() {
+        let bytes = chunk_bytes(&chunk(0));
+        assert_eq!(bytes, 4 + 16 + 16 + 4 + 16);
+        let mut cache = ChunkCache::new(CacheBudget::Bytes(bytes * 3));
+        for id in 0..5 {
+            cache.insert((u32::from(id), 0), chunk(id));
+        }
+        let stats = cache.stats();
+        assert_eq!((stats.chunks, stats.bytes), (3, bytes * 3));
+        assert!(!cache.contains((1, 0)) && cache.contains((2, 0)));
+
+        cache.set_budget(CacheBudget::Chunks(1));
+        assert!(cache.contains((4, 0)));
+        assert_eq!(cache.stats().chunks, 1);
+    }
+
+    #[test]
+    fn pinned_chunks_are_never_evicted() {
+        let mut cache = ChunkCache::new(CacheBudget::Chunks(1));
+        cache.insert((0, 0), chunk(0));
+        assert!(cache.pin((0, 0)));
+        assert!(cache.pin((0, 0)));
+        assert!(!cache.pin((9, 9)));
+
+        // Over budget with only the pinned chunk left: newcomers go first.
+        cache.insert((1, 0), chunk(1));
+        cache.insert((2, 0), chunk(2));
+        cache.clear();
+        assert!(cache.contains((0, 0)));
+        assert!(!cache.contains((1, 0)) && !cache.contains((2, 0)));
+
+        // Replacing a pinned chunk keeps its pins.
+        cache.insert((0, 0), chunk(7));
+        assert_eq!(cache.stats().pinned, 1);
+        assert!(cache.peek((0, 0)).is_some_and(|c| c.elevation[0] > 6.0));
+
+        // Pins nest: only the last unpin makes it evictable.
+        cache.unpin((0, 0));
+        cache.insert((1, 0), chunk(1));
+        assert!(cache.contains((0, 0)));
+        cache.unpin((0, 0));
+        assert_eq!(cache.stats().pinned, 0);
+        cache.insert((1, 0), chunk(1));
+        assert!(!cache.contains((0, 0)) && cache.contains((1, 0)));
+
+        // A pinned insertion stays even when it alone is over budget.
+        let mut tiny = ChunkCache::new(CacheBudget::Bytes(1));
+        tiny.insert_pinned((5, 5), chunk(5));
+        assert!(tiny.contains((5, 5)));
+        tiny.insert((6, 6), chunk(6));
+        assert!(tiny.contains((5, 5)) && !tiny.contains((6, 6)));
+    }
+
+    #[test]
+    fn stats_count_hits_misses_and_residents() -> io::Result<()> {
+        let mut cache = ChunkCache::new(CacheBudget::Unbounded);
+        assert!((cache.stats().hit_rate()).abs() < f64::EPSILON);
+
+        let mut loads = 0;
+        for _ in 0..3 {
+            cache.get_or_load((0, 0), || {
+                loads += 1;
+                Ok(chunk(0))
+            })?;
+        }
+        assert_eq!(loads, 1);
+        let failed = cache.get_or_load((1, 0), || Err(io::Error::other("unreadable")));
+        assert!(failed.is_err());
+        assert!(!cache.contains((1, 0)));
+        assert!(cache.pin((0, 0)));
+
+        let stats = cache.stats();
+        assert_eq!(
+            stats,
+            CacheStats {
+                hits: 2,
+                misses: 2,
+                evictions: 0,
+                chunks: 1,
+                bytes: chunk_bytes(&chunk(0)),
+                pinned: 1,
+            }
+        );
+        assert!((stats.hit_rate() - 0.5).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    fn shared_cache_sums_shards_and_keeps_pins() -> io::Result<()> {
+        let cache = SharedChunkCache::new(CacheBudget::Chunks(SHARDS));
+        for cx in 0..64 {
+            cache.get_or_load((cx, 0), || Ok(chunk(0)))?;
+        }
+        let pinned = cache.get_or_load_pinned((100, 0), || Ok(chunk(1)))?;
+        assert!(pinned.elevation[0] > 0.0);
+        // Later loads of the same chunk reuse the pinned one.
+        cache.get_or_load((100, 0), || Ok(chunk(2)))?;
+        for cx in 0..64 {
+            cache.get_or_load((cx, 1), || Ok(chunk(0)))?;
+        }
+        cache.clear();
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 129));
+        assert_eq!((stats.chunks, stats.pinned), (1, 1));
+        assert!(cache.peek((100, 0)).is_some_and(|c| c.elevation[0] > 0.0));
+        cache.unpin((100, 0));
+        cache.clear();
+        assert_eq!(cache.stats().chunks, 0);
+        Ok(())
+    }
+}
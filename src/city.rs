@@ -15,25 +15,356 @@
 //! 5. At least one water neighbor belongs to a "large" water body (ocean, not puddle)
 //! 6. It is far enough from all previously placed slots (minimum spacing grid)
 //!
+//! # Placement modes
+//!
+//! Rules 1-5 pick the candidate tiles; [`CityPlacement`] decides the order
+//! in which rule 6 accepts them:
+//!
+//! - [`Scan`](CityPlacement::Scan) (default) accepts the first qualifying
+//!   tile in row-major order. Slots drift toward the north-west of each
+//!   coastline.
+//! - [`Scored`](CityPlacement::Scored) rates every candidate on harbor
+//!   depth, hinterland size, coastline straightness and resource potential,
+//!   then visits candidates in a weighted random order derived from the
+//!   seed (weighted Poisson-disk sampling). Good sites are likely, but not
+//!   certain, to win over their neighbours, and slots spread evenly along
+//!   every coast.
+//!
+//! Both modes use the same spacing grid. A raster scan packs each coastline
+//! as tightly as the grid allows, so `Scored` finds fewer slots (roughly
+//! 15-20% on default settings); lower `CITY_SPACING` to compensate when
+//! switching. The mode is stored in the world file (version 13+).
+//!
+//! # Site descriptions
+//!
+//! Once the final slots are known, [`compute_city_sites`] describes the
+//! surroundings of each one in a [`CitySite`]: which way its water lies,
+//! how good a harbor it makes, what kind of shore it sits on, and how much
+//! land backs it. The game client uses these to orient harbors and pick
+//! art; they are stored in the world file parallel to the slots.
+//!
 //! # Island filtering
 //!
 //! After placement, islands with too few city slots are discarded entirely.
 //! This ensures every playable island has a minimum strategic value.
 //! Cities on small islands are removed along with their island.
 //!
+//! [`balance_island_city_slots`] can then thin over-full islands, to a hard
+//! cap and toward a target distribution of island sizes
+//! ([`IslandSizeTarget`]).
+//!
 //! # Example
 //!
 //! ```ignore
-//! let slots = find_city_slots(&terrain, &water_bodies, &config);
+//! let slots = find_city_slots(&terrain, &water_bodies, &elevation, &biomes, &config);
 //! let filtered = filter_city_slots_by_region(&slots, &region_labels, 6);
 //! // `filtered` contains only cities on islands with ≥ 6 slots
 //! ```
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+    ParallelSliceMut,
+};
+
+use crate::biome::city_resources::CITY_SCAN_RADIUS;
+use crate::biome::Biome;
+use crate::city_diagnostics::{CityDiagnostics, IslandDrop, IslandDropReason, SlotOutcome};
 use crate::config::WorldConfig;
 use crate::terrain::{Terrain, WaterBodies};
 
+// ---------------------------------------------------------------------------
+// Placement mode
+// ---------------------------------------------------------------------------
+
+/// How [`find_city_slots`] orders qualifying tiles before spacing them out.
+///
+/// Discriminants are part of the saved world format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum CityPlacement {
+    /// First-fit raster scan, top-left to bottom-right.
+    #[default]
+    Scan,
+    /// Quality-scored weighted Poisson-disk sampling.
+    Scored,
+}
+
+impl CityPlacement {
+    /// Parse the `CITY_PLACEMENT` value (`scored` or `scan`).
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "scored" => Some(Self::Scored),
+            "scan" => Some(Self::Scan),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Scored => "scored",
+            Self::Scan => "scan",
+        }
+    }
+
+    #[must_use]
+    pub const fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Unknown values fall back to the default mode.
+    #[must_use]
+    pub const fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Scored,
+            _ => Self::Scan,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Island size targets
+// ---------------------------------------------------------------------------
+
+/// One bucket of the island size distribution aimed for by
+/// [`balance_island_city_slots`]: islands with `min..=max` cities, as a
+/// share `weight` relative to the other buckets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IslandSizeTarget {
+    pub min: u32,
+    /// `None` for an open-ended bucket (`40+`), which never thins.
+    pub max: Option<u32>,
+    pub weight: f64,
+}
+
+impl IslandSizeTarget {
+    /// Parse the `ISLAND_SIZE_TARGETS` value: comma-separated `min-max:weight`
+    /// or `min+:weight` buckets, e.g. `8-16:70,17-39:25,40+:5`.
+    ///
+    /// Buckets are returned sorted by `min`. `None` if any bucket is
+    /// malformed, empty, has a zero or negative weight, or overlaps another.
+    #[must_use]
+    pub fn parse_list(s: &str) -> Option<Vec<Self>> {
+        let mut targets = Vec::new();
+        for bucket in s.split(',') {
+            let (range, weight) = bucket.trim().split_once(':')?;
+            let weight: f64 = weight.trim().parse().ok()?;
+            let (min, max) = if let Some(min) = range.trim().strip_suffix('+') {
+                (min.parse().ok()?, None)
+            } else {
+                let (min, max) = range.trim().split_once('-')?;
+                (min.parse().ok()?, Some(max.parse().ok()?))
+            };
+            if min == 0 || max.is_some_and(|max| max < min) || weight <= 0.0 || !weight.is_finite()
+            {
+                return None;
+            }
+            targets.push(Self { min, max, weight });
+        }
+        targets.sort_by_key(|t| t.min);
+        let disjoint = targets
+            .windows(2)
+            .all(|w| w[0].max.is_some_and(|max| max < w[1].min));
+        disjoint.then_some(targets)
+    }
+
+    /// `true` if an island with `cities` cities falls in this bucket.
+    #[must_use]
+    pub fn contains(&self, cities: u32) -> bool {
+        cities >= self.min && self.max.is_none_or(|max| cities <= max)
+    }
+
+    /// The bucket as written in `ISLAND_SIZE_TARGETS`, without the weight.
+    #[must_use]
+    pub fn label(&self) -> String {
+        self.max.map_or_else(
+            || format!("{}+", self.min),
+            |max| format!("{}-{max}", self.min),
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Site descriptions
+// ---------------------------------------------------------------------------
+
+/// Surroundings of one city slot, computed by [`compute_city_sites`].
+///
+/// Stored in the world file, parallel to `city_slots`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CitySite {
+    /// Direction from the city towards the bulk of its nearby water; the
+    /// way a harbor should open.
+    pub facing: Facing,
+    /// Harbor rating, 0 (shallow puddle) to 100 (deep open sea). Combines
+    /// the water depth next to the city, how close the nearest
+    /// [`DeepHarbor`](Biome::DeepHarbor) tile is, and the size of the
+    /// adjacent water body.
+    pub harbor_quality: u8,
+    /// Tiles in the largest water body touching the city tile.
+    pub water_body_size: u32,
+    /// Kind of shore the city sits on.
+    pub coast: CoastType,
+    /// Elevation of the city tile (at file precision).
+    pub elevation: f32,
+    /// Land tiles within 8 tiles of the city.
+    pub hinterland: u16,
+}
+
+/// One of the eight compass directions. North is up (towards `y = 0`).
+///
+/// Discriminants are part of the saved world format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Facing {
+    #[default]
+    North = 0,
+    NorthEast = 1,
+    East = 2,
+    SouthEast = 3,
+    South = 4,
+    SouthWest = 5,
+    West = 6,
+    NorthWest = 7,
+}
+
+impl Facing {
+    const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    /// The direction closest to the vector `(dx, dy)` in tile coordinates.
+    /// `None` for the zero vector.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn from_vector(dx: f64, dy: f64) -> Option<Self> {
+        if dx == 0.0 && dy == 0.0 {
+            return None;
+        }
+        // Clockwise from north, with y growing downwards.
+        let bearing = dx.atan2(-dy).to_degrees().rem_euclid(360.0);
+        Some(Self::ALL[((bearing / 45.0).round() as usize) % 8])
+    }
+
+    /// Compass bearing in degrees, clockwise from north.
+    #[must_use]
+    pub const fn degrees(self) -> u16 {
+        self as u16 * 45
+    }
+
+    /// Short compass label, e.g. `"NE"`.
+    #[must_use]
+    pub const fn abbrev(self) -> &'static str {
+        ["N", "NE", "E", "SE", "S", "SW", "W", "NW"][self as usize]
+    }
+
+    #[must_use]
+    pub const fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Deserialize from the stored `u8`. Unknown values fall back to North.
+    #[must_use]
+    pub fn from_u8(v: u8) -> Self {
+        Self::ALL.get(v as usize).copied().unwrap_or_default()
+    }
+}
+
+/// Kind of shore under a city slot.
+///
+/// Discriminants are part of the saved world format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum CoastType {
+    /// Low ground meeting the water.
+    #[default]
+    Beach = 0,
+    /// The city tile rises well above the water line.
+    Cliff = 1,
+    /// Swamp within a couple of tiles of the city.
+    Marsh = 2,
+}
+
+impl CoastType {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Beach => "Beach",
+            Self::Cliff => "Cliff",
+            Self::Marsh => "Marsh",
+        }
+    }
+
+    #[must_use]
+    pub const fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Deserialize from the stored `u8`. Unknown values fall back to Beach.
+    #[must_use]
+    pub const fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Cliff,
+            2 => Self::Marsh,
+            _ => Self::Beach,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scoring constants
+// ---------------------------------------------------------------------------
+
+/// Radius (tiles) of the water area whose depth rates a harbor.
+const HARBOR_RADIUS: i32 = 3;
+
+/// Radius (tiles) of the land area counted as a city's hinterland.
+const HINTERLAND_RADIUS: i32 = 8;
+
+/// Radius (tiles) of the square ring walked to measure coastline shape.
+const COAST_RING_RADIUS: i32 = 2;
+
+/// Relative weight of each score component. Sums to 1.
+const HARBOR_WEIGHT: f64 = 0.25;
+const HINTERLAND_WEIGHT: f64 = 0.30;
+const STRAIGHTNESS_WEIGHT: f64 = 0.20;
+const RESOURCE_WEIGHT: f64 = 0.25;
+
+/// Sampling weight of the worst candidate, so every candidate can win.
+const MIN_SAMPLING_WEIGHT: f64 = 0.05;
+
+// ---------------------------------------------------------------------------
+// Site description constants
+// ---------------------------------------------------------------------------
+
+/// Mean harbor depth (below the water threshold) that rates as fully deep:
+/// the depth at which [`Coast`](Biome::Coast) shallows give way to open water.
+const FULL_HARBOR_DEPTH: f64 = 0.06;
+
+/// Water body size (tiles) that rates as open sea; smaller bodies rate
+/// lower on a log scale.
+const OPEN_SEA_TILES: f64 = 1_000_000.0;
+
+/// Relative weight of each harbor quality component. Sums to 1.
+const HARBOR_DEPTH_WEIGHT: f64 = 0.4;
+const DEEP_HARBOR_WEIGHT: f64 = 0.3;
+const WATER_BODY_WEIGHT: f64 = 0.3;
+
+/// Height above the water threshold from which a coast counts as a cliff:
+/// the top of the [`Beach`](Biome::Beach) band.
+const CLIFF_RISE: f64 = 0.02;
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -51,6 +382,9 @@ use crate::terrain::{Terrain, WaterBodies};
 ///
 /// If all checks pass, add the tile to the result and mark a spacing
 /// radius around it as "taken" (no more cities within spacing distance).
+/// `config.city_placement` decides the order tiles are visited in; see
+/// the [module docs](self). `elevation` and `biomes` are only read by
+/// [`CityPlacement::Scored`].
 ///
 /// # Parameters
 ///
@@ -65,45 +399,80 @@ use crate::terrain::{Terrain, WaterBodies};
 pub fn find_city_slots(
     terrain: &[Vec<Terrain>],
     water: &WaterBodies,
+    elevation: &[Vec<f64>],
+    biomes: &[Vec<u8>],
     config: &WorldConfig,
 ) -> Vec<(usize, usize)> {
-    let map_size = config.map_len();
-    let spacing = config.city_spacing as usize;
-    let radius = config.playable_radius as f64;
-    let min_land = config.min_land_neighbors as usize;
-    let min_water = config.min_water_neighbors as usize;
-    let min_body = config.min_water_body_size as usize;
-    let center = map_size / 2;
+    let rules = SlotRules::new(config);
+    match config.city_placement {
+        CityPlacement::Scan => scan_city_slots(terrain, water, &rules),
+        CityPlacement::Scored => {
+            scored_city_slots(terrain, water, elevation, biomes, config, &rules)
+        }
+    }
+}
 
-    let mut taken = vec![vec![false; map_size]; map_size];
-    let mut slots = Vec::new();
+/// Describe the surroundings of every city slot.
+///
+/// Returns a `Vec` parallel to `city_slots`. `water` must label the same
+/// `terrain` grid; each slot is expected to be a valid coastal tile (see
+/// [`find_city_slots`]), though any in-bounds tile gets a description.
+#[must_use]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn compute_city_sites(
+    city_slots: &[(usize, usize)],
+    terrain: &[Vec<Terrain>],
+    elevation: &[Vec<f64>],
+    biomes: &[Vec<u8>],
+    water: &WaterBodies,
+    water_threshold: f32,
+) -> Vec<CitySite> {
+    let water_threshold = f64::from(water_threshold);
+    city_slots
+        .par_iter()
+        .map(|&(x, y)| {
+            let map_size = terrain.len();
+            let water_body_size = crate::terrain::neighbors_4(x, y, map_size)
+                .map(|(nx, ny)| water.sizes[water.labels[ny][nx] as usize])
+                .max()
+                .unwrap_or(0);
 
-    for y in spacing..(map_size - spacing) {
-        for x in spacing..(map_size - spacing) {
-            let dx = (x as isize - center as isize) as f64;
-            let dy = (y as isize - center as isize) as f64;
-            if (dx * dx + dy * dy).sqrt() > radius {
-                continue;
-            }
-            if terrain[y][x] != Terrain::Land {
-                continue;
-            }
+            let depth = harbor_depth(terrain, elevation, water_threshold, x, y);
+            let depth_score = (depth / FULL_HARBOR_DEPTH).min(1.0);
+            let deep_score = deep_harbor_proximity(biomes, x, y);
+            let body_score = if water_body_size > 0 {
+                (water_body_size as f64).log(OPEN_SEA_TILES).min(1.0)
+            } else {
+                0.0
+            };
+            let quality = HARBOR_DEPTH_WEIGHT.mul_add(
+                depth_score,
+                DEEP_HARBOR_WEIGHT.mul_add(deep_score, WATER_BODY_WEIGHT * body_score),
+            );
 
-            let (land, water_count, water_positions) = count_neighbors(terrain, x, y, map_size);
+            let rise = elevation[y][x] - water_threshold;
+            let coast = if near_biome(biomes, x, y, COAST_RING_RADIUS, Biome::Swamp) {
+                CoastType::Marsh
+            } else if rise >= CLIFF_RISE {
+                CoastType::Cliff
+            } else {
+                CoastType::Beach
+            };
 
-            if land >= min_land
-                && water_count >= min_water
-                && !is_area_taken(&taken, x, y, spacing, map_size)
-                && water_positions
-                    .iter()
-                    .any(|&(wx, wy)| water.is_large(wx, wy, min_body))
-            {
-                slots.push((x, y));
-                mark_area_taken(&mut taken, x, y, spacing, map_size);
+            CitySite {
+                facing: water_facing(terrain, x, y),
+                harbor_quality: (quality * 100.0).round() as u8,
+                water_body_size: u32::try_from(water_body_size).unwrap_or(u32::MAX),
+                coast,
+                elevation: crate::save::stored_elevation(elevation[y][x] as f32),
+                hinterland: u16::try_from(land_in_disk(terrain, x, y).0).unwrap_or(u16::MAX),
             }
-        }
-    }
-    slots
+        })
+        .collect()
 }
 
 /// Filter city slots by region, keeping only those on "large enough" islands.
@@ -143,6 +512,633 @@ pub fn filter_city_slots_by_region(
         .collect()
 }
 
+/// Thin over-full islands down to `max_city_slots_per_island` and toward
+/// the `island_size_targets` distribution.
+///
+/// Islands are ranked by their slot count. The island at rank `r` of `n`
+/// is assigned the city count at quantile `(r + 0.5) / n` of the target
+/// distribution, so bigger islands keep more cities than smaller ones and
+/// the island sizes follow the targets as far as the coastlines allow:
+/// islands are only ever thinned, never grown. The cap then applies on
+/// top, and no island drops below `min_city_slots_per_island`.
+///
+/// Thinned islands keep an evenly spread subset of their slots, chosen by
+/// farthest-point sampling. `city_slots` are the output of
+/// [`filter_city_slots_by_region`]; they are returned unchanged if
+/// [`WorldConfig::balances_islands`] is `false`.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn balance_island_city_slots(
+    city_slots: &[(usize, usize)],
+    region_map: &[Vec<usize>],
+    config: &WorldConfig,
+) -> Vec<(usize, usize)> {
+    if !config.balances_islands() {
+        return city_slots.to_vec();
+    }
+    let targets = &config.island_size_targets;
+    let max_slots = config.max_city_slots_per_island as usize;
+    let min_slots = config.min_city_slots_per_island as usize;
+
+    let mut by_region: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for &(x, y) in city_slots {
+        by_region.entry(region_map[y][x]).or_default().push((x, y));
+    }
+    let mut islands: Vec<(usize, Vec<(usize, usize)>)> = by_region.into_iter().collect();
+    islands.sort_unstable_by_key(|(region, slots)| (slots.len(), *region));
+
+    let num_islands = islands.len() as f64;
+    let mut balanced = Vec::with_capacity(city_slots.len());
+    for (rank, (_, mut slots)) in islands.into_iter().enumerate() {
+        let mut keep = slots.len();
+        if !targets.is_empty() {
+            keep = keep.min(target_quantile(targets, (rank as f64 + 0.5) / num_islands));
+        }
+        if max_slots > 0 {
+            keep = keep.min(max_slots);
+        }
+        // Targets may ask for fewer; a cap below the minimum is raised to
+        // it when the config is loaded.
+        keep = keep.max(min_slots);
+        if keep < slots.len() {
+            // Sorted so the result does not depend on the input order.
+            slots.sort_unstable_by_key(|&(x, y)| (y, x));
+            slots = thin_evenly(&slots, keep);
+        }
+        balanced.extend(slots);
+    }
+    balanced
+}
+
+/// Explain the placement outcome of every Land tile.
+///
+/// `city_slots` are the slots returned by [`find_city_slots`] and `placed`
+/// the final slots, after [`filter_city_slots_by_region`] and
+/// [`balance_island_city_slots`]. The filter is replayed here to tell slots
+/// dropped with their island from ones thinned away. See
+/// [`city_diagnostics`](crate::city_diagnostics) for the outcomes.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn diagnose_city_slots(
+    terrain: &[Vec<Terrain>],
+    water: &WaterBodies,
+    region_labels: &[Vec<usize>],
+    city_slots: &[(usize, usize)],
+    placed: &[(usize, usize)],
+    config: &WorldConfig,
+) -> CityDiagnostics {
+    let rules = SlotRules::new(config);
+    let map_size = rules.map_size;
+    let required = u32::from(config.min_city_slots_per_island);
+
+    let mut slots_per_region: HashMap<usize, u32> = HashMap::new();
+    for &(x, y) in city_slots {
+        *slots_per_region.entry(region_labels[y][x]).or_default() += 1;
+    }
+    let kept = |region: usize| {
+        slots_per_region
+            .get(&region)
+            .is_some_and(|&n| n >= required)
+    };
+
+    let mut outcomes = vec![0u8; map_size * map_size];
+    for &(x, y) in city_slots {
+        outcomes[y * map_size + x] = if kept(region_labels[y][x]) {
+            SlotOutcome::Thinned.to_u8()
+        } else {
+            SlotOutcome::IslandTooSmall.to_u8()
+        };
+    }
+    for &(x, y) in placed {
+        outcomes[y * map_size + x] = SlotOutcome::Placed.to_u8();
+    }
+    let span = rules.span();
+    outcomes
+        .par_chunks_mut(map_size)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if *cell != 0 || terrain[y][x] != Terrain::Land {
+                    continue;
+                }
+                let outcome = if !span.contains(&x) || !span.contains(&y) {
+                    SlotOutcome::MapEdge
+                } else {
+                    rules
+                        .rejection(terrain, water, x, y)
+                        .unwrap_or(SlotOutcome::Spacing)
+                };
+                *cell = outcome.to_u8();
+            }
+        });
+
+    // Outcome histogram of every island without cities.
+    let mut histograms: HashMap<usize, [u32; SlotOutcome::COUNT]> = HashMap::new();
+    for (y, row) in region_labels.iter().enumerate() {
+        for (x, &region) in row.iter().enumerate() {
+            if region == 0 || kept(region) {
+                continue;
+            }
+            if let Some(outcome) = SlotOutcome::from_u8(outcomes[y * map_size + x]) {
+                histograms.entry(region).or_insert([0; SlotOutcome::COUNT])
+                    [outcome as usize - 1] += 1;
+            }
+        }
+    }
+    let mut dropped_islands: Vec<IslandDrop> = histograms
+        .into_iter()
+        .map(|(region, outcomes)| {
+            let slots = slots_per_region.get(&region).copied().unwrap_or(0);
+            IslandDrop {
+                region_id: region as u32,
+                reason: if slots == 0 {
+                    IslandDropReason::NoQualifyingCoast
+                } else {
+                    IslandDropReason::TooFewSlots
+                },
+                slots,
+                required,
+                outcomes,
+            }
+        })
+        .collect();
+    dropped_islands.sort_unstable_by_key(|d| d.region_id);
+
+    CityDiagnostics {
+        width: u32::from(config.map_size),
+        height: u32::from(config.map_size),
+        outcomes,
+        dropped_islands,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Placement
+// ---------------------------------------------------------------------------
+
+/// Placement rules 1-5 and the spacing of rule 6, read from the config.
+struct SlotRules {
+    map_size: usize,
+    spacing: usize,
+    radius: f64,
+    center: usize,
+    min_land: usize,
+    min_water: usize,
+    min_body: usize,
+}
+
+impl SlotRules {
+    fn new(config: &WorldConfig) -> Self {
+        let map_size = config.map_len();
+        Self {
+            map_size,
+            spacing: config.city_spacing as usize,
+            radius: f64::from(config.playable_radius),
+            center: map_size / 2,
+            min_land: config.min_land_neighbors as usize,
+            min_water: config.min_water_neighbors as usize,
+            min_body: config.min_water_body_size as usize,
+        }
+    }
+
+    /// Rows and columns a slot may occupy (a spacing margin off each edge).
+    const fn span(&self) -> std::ops::Range<usize> {
+        self.spacing..self.map_size.saturating_sub(self.spacing)
+    }
+
+    /// `true` if `(x, y)` passes rules 1-5.
+    fn qualifies(&self, terrain: &[Vec<Terrain>], water: &WaterBodies, x: usize, y: usize) -> bool {
+        terrain[y][x] == Terrain::Land && self.rejection(terrain, water, x, y).is_none()
+    }
+
+    /// The first of rules 2-5 the Land tile `(x, y)` breaks, or `None` if
+    /// it passes them all.
+    #[allow(clippy::cast_precision_loss)]
+    fn rejection(
+        &self,
+        terrain: &[Vec<Terrain>],
+        water: &WaterBodies,
+        x: usize,
+        y: usize,
+    ) -> Option<SlotOutcome> {
+        let dx = x as f64 - self.center as f64;
+        let dy = y as f64 - self.center as f64;
+        if dx.hypot(dy) > self.radius {
+            return Some(SlotOutcome::OutsidePlayableArea);
+        }
+        let (land, water_count, water_positions) = count_neighbors(terrain, x, y, self.map_size);
+        if water_count == 0 {
+            Some(SlotOutcome::Inland)
+        } else if land < self.min_land {
+            Some(SlotOutcome::FewLandNeighbors)
+        } else if water_count < self.min_water {
+            Some(SlotOutcome::FewWaterNeighbors)
+        } else if !water_positions
+            .iter()
+            .any(|&(wx, wy)| water.is_large(wx, wy, self.min_body))
+        {
+            Some(SlotOutcome::SmallWaterBody)
+        } else {
+            None
+        }
+    }
+}
+
+/// [`CityPlacement::Scan`]: accept qualifying tiles in row-major order.
+fn scan_city_slots(
+    terrain: &[Vec<Terrain>],
+    water: &WaterBodies,
+    rules: &SlotRules,
+) -> Vec<(usize, usize)> {
+    let (map_size, spacing) = (rules.map_size, rules.spacing);
+    let mut taken = vec![vec![false; map_size]; map_size];
+    let mut slots = Vec::new();
+
+    for y in rules.span() {
+        for x in rules.span() {
+            if rules.qualifies(terrain, water, x, y)
+                && !is_area_taken(&taken, x, y, spacing, map_size)
+            {
+                slots.push((x, y));
+                mark_area_taken(&mut taken, x, y, spacing, map_size);
+            }
+        }
+    }
+    slots
+}
+
+/// [`CityPlacement::Scored`]: score every qualifying tile, then accept them
+/// in a weighted random order.
+///
+/// The order uses weighted sampling without replacement (Efraimidis-Spirakis):
+/// each candidate draws `u` in `(0, 1]` from a hash of its position and the
+/// seed and sorts by `ln(u) / weight`, so a candidate with twice the weight
+/// is twice as likely to come first. Accepting candidates in that order
+/// under the spacing grid is dart-throwing Poisson-disk sampling with the
+/// darts drawn from the candidate list.
+fn scored_city_slots(
+    terrain: &[Vec<Terrain>],
+    water: &WaterBodies,
+    elevation: &[Vec<f64>],
+    biomes: &[Vec<u8>],
+    config: &WorldConfig,
+    rules: &SlotRules,
+) -> Vec<(usize, usize)> {
+    let (map_size, spacing) = (rules.map_size, rules.spacing);
+    let candidates: Vec<(usize, usize)> = rules
+        .span()
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            rules
+                .span()
+                .filter(move |&x| rules.qualifies(terrain, water, x, y))
+                .map(move |x| (x, y))
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+
+    let water_threshold = f64::from(config.water_threshold);
+    let raw: Vec<[f64; 4]> = candidates
+        .par_iter()
+        .map(|&(x, y)| {
+            [
+                harbor_depth(terrain, elevation, water_threshold, x, y),
+                hinterland(terrain, x, y),
+                coast_straightness(terrain, x, y),
+                resource_potential(biomes, x, y),
+            ]
+        })
+        .collect();
+    let scores = combine_scores(&raw);
+
+    let mut order: Vec<(f64, usize, usize)> = candidates
+        .iter()
+        .zip(&scores)
+        .map(|(&(x, y), &score)| {
+            let weight = MIN_SAMPLING_WEIGHT + score;
+            (unit_hash(x, y, config.seed).ln() / weight, x, y)
+        })
+        .collect();
+    // Largest key first; ties by (y, x) for strict determinism.
+    order.sort_unstable_by(|a, b| {
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| (a.2, a.1).cmp(&(b.2, b.1)))
+    });
+
+    let mut taken = vec![vec![false; map_size]; map_size];
+    let mut slots = Vec::new();
+    for (_, x, y) in order {
+        if !is_area_taken(&taken, x, y, spacing, map_size) {
+            slots.push((x, y));
+            mark_area_taken(&mut taken, x, y, spacing, map_size);
+        }
+    }
+    slots
+}
+
+/// Min-max normalise each raw component across all candidates and combine
+/// them into one score in `[0, 1]` per candidate.
+fn combine_scores(raw: &[[f64; 4]]) -> Vec<f64> {
+    const WEIGHTS: [f64; 4] = [
+        HARBOR_WEIGHT,
+        HINTERLAND_WEIGHT,
+        STRAIGHTNESS_WEIGHT,
+        RESOURCE_WEIGHT,
+    ];
+    let mut lo = [f64::INFINITY; 4];
+    let mut hi = [f64::NEG_INFINITY; 4];
+    for r in raw {
+        for i in 0..4 {
+            lo[i] = lo[i].min(r[i]);
+            hi[i] = hi[i].max(r[i]);
+        }
+    }
+    raw.iter()
+        .map(|r| {
+            (0..4)
+                .map(|i| {
+                    let range = hi[i] - lo[i];
+                    let norm = if range > 0.0 {
+                        (r[i] - lo[i]) / range
+                    } else {
+                        0.5
+                    };
+                    norm * WEIGHTS[i]
+                })
+                .sum()
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Score components
+// ---------------------------------------------------------------------------
+
+/// Mean depth below the water threshold of the water tiles within
+/// [`HARBOR_RADIUS`]. Deep water close in makes a better harbor.
+fn harbor_depth(
+    terrain: &[Vec<Terrain>],
+    elevation: &[Vec<f64>],
+    water_threshold: f64,
+    x: usize,
+    y: usize,
+) -> f64 {
+    let (mut depth, mut n) = (0.0, 0u32);
+    for_each_in_disk(terrain.len(), x, y, HARBOR_RADIUS, |tx, ty| {
+        if terrain[ty][tx] == Terrain::Water {
+            depth += (water_threshold - elevation[ty][tx]).max(0.0);
+            n += 1;
+        }
+    });
+    if n == 0 {
+        0.0
+    } else {
+        depth / f64::from(n)
+    }
+}
+
+/// Fraction of the tiles within [`HINTERLAND_RADIUS`] that are Land.
+fn hinterland(terrain: &[Vec<Terrain>], x: usize, y: usize) -> f64 {
+    let (land, n) = land_in_disk(terrain, x, y);
+    f64::from(land) / f64::from(n.max(1))
+}
+
+/// Land tiles and all in-bounds tiles within [`HINTERLAND_RADIUS`].
+fn land_in_disk(terrain: &[Vec<Terrain>], x: usize, y: usize) -> (u32, u32) {
+    let (mut land, mut n) = (0u32, 0u32);
+    for_each_in_disk(terrain.len(), x, y, HINTERLAND_RADIUS, |tx, ty| {
+        if terrain[ty][tx] == Terrain::Land {
+            land += 1;
+        }
+        n += 1;
+    });
+    (land, n)
+}
+
+/// `1.0` for a straight coast, less for ragged ones.
+///
+/// Walks the square ring at [`COAST_RING_RADIUS`] and counts changes
+/// between water and non-water: a straight coastline crosses the ring
+/// twice, coves, spits and archipelagos more often.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn coast_straightness(terrain: &[Vec<Terrain>], x: usize, y: usize) -> f64 {
+    let map_size = terrain.len() as i32;
+    let r = COAST_RING_RADIUS;
+    let ring = (-r..r)
+        .map(|d| (d, -r))
+        .chain((-r..r).map(|d| (r, d)))
+        .chain((-r..r).map(|d| (-d, r)))
+        .chain((-r..r).map(|d| (-r, -d)));
+    let is_water: Vec<bool> = ring
+        .map(|(dx, dy)| {
+            let (tx, ty) = (x as i32 + dx, y as i32 + dy);
+            // Off-map counts as land: it never forms part of a harbor.
+            tx >= 0
+                && ty >= 0
+                && tx < map_size
+                && ty < map_size
+                && terrain[ty as usize][tx as usize] == Terrain::Water
+        })
+        .collect();
+    let transitions = (0..is_water.len())
+        .filter(|&i| is_water[i] != is_water[(i + 1) % is_water.len()])
+        .count();
+    2.0 / transitions.max(2) as f64
+}
+
+/// Mean summed production modifiers (wood, stone, food, metal, favor) of
+/// the biomes within [`CITY_SCAN_RADIUS`] -- the area
+/// [`CityResources`](crate::biome::CityResources) will later aggregate.
+fn resource_potential(biomes: &[Vec<u8>], x: usize, y: usize) -> f64 {
+    let (mut total, mut n) = (0i32, 0u32);
+    for_each_in_disk(biomes.len(), x, y, CITY_SCAN_RADIUS, |tx, ty| {
+        let m = Biome::from_u8(biomes[ty][tx]).resource_modifiers();
+        total += i32::from(m.wood)
+            + i32::from(m.stone)
+            + i32::from(m.food)
+            + i32::from(m.metal)
+            + i32::from(m.favor);
+        n += 1;
+    });
+    f64::from(total) / f64::from(n.max(1))
+}
+
+// ---------------------------------------------------------------------------
+// Site description helpers
+// ---------------------------------------------------------------------------
+
+/// Direction of the summed offsets to every water tile within
+/// [`HARBOR_RADIUS`], falling back to the first adjacent water tile when
+/// the water is spread evenly around the city.
+#[allow(clippy::cast_precision_loss)]
+fn water_facing(terrain: &[Vec<Terrain>], x: usize, y: usize) -> Facing {
+    let (mut sx, mut sy) = (0.0, 0.0);
+    for_each_in_disk(terrain.len(), x, y, HARBOR_RADIUS, |tx, ty| {
+        if terrain[ty][tx] == Terrain::Water {
+            sx += tx as f64 - x as f64;
+            sy += ty as f64 - y as f64;
+        }
+    });
+    Facing::from_vector(sx, sy)
+        .or_else(|| {
+            crate::terrain::neighbors_4(x, y, terrain.len())
+                .find(|&(nx, ny)| terrain[ny][nx] == Terrain::Water)
+                .and_then(|(nx, ny)| {
+                    Facing::from_vector(nx as f64 - x as f64, ny as f64 - y as f64)
+                })
+        })
+        .unwrap_or_default()
+}
+
+/// `1.0` with a [`DeepHarbor`](Biome::DeepHarbor) tile right next to the
+/// city, falling linearly to `0.0` beyond [`CITY_SCAN_RADIUS`].
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn deep_harbor_proximity(biomes: &[Vec<u8>], x: usize, y: usize) -> f64 {
+    let mut nearest_sq = i32::MAX;
+    for_each_in_disk(biomes.len(), x, y, CITY_SCAN_RADIUS, |tx, ty| {
+        if Biome::from_u8(biomes[ty][tx]) == Biome::DeepHarbor {
+            let (dx, dy) = (tx as i32 - x as i32, ty as i32 - y as i32);
+            nearest_sq = nearest_sq.min(dx * dx + dy * dy);
+        }
+    });
+    if nearest_sq == i32::MAX {
+        return 0.0;
+    }
+    let reach = f64::from(CITY_SCAN_RADIUS + 1);
+    (1.0 - (f64::from(nearest_sq).sqrt() - 1.0).max(0.0) / reach).max(0.0)
+}
+
+/// `true` if any tile within `radius` of `(x, y)` has biome `biome`.
+fn near_biome(biomes: &[Vec<u8>], x: usize, y: usize, radius: i32, biome: Biome) -> bool {
+    let mut found = false;
+    for_each_in_disk(biomes.len(), x, y, radius, |tx, ty| {
+        found |= Biome::from_u8(biomes[ty][tx]) == biome;
+    });
+    found
+}
+
+/// Call `f(tx, ty)` for every in-bounds tile within `radius` of `(x, y)`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn for_each_in_disk(
+    map_size: usize,
+    x: usize,
+    y: usize,
+    radius: i32,
+    mut f: impl FnMut(usize, usize),
+) {
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+            let (tx, ty) = (x as i32 + dx, y as i32 + dy);
+            if tx < 0 || ty < 0 || tx >= map_size as i32 || ty >= map_size as i32 {
+                continue;
+            }
+            f(tx as usize, ty as usize);
+        }
+    }
+}
+
+/// Deterministic value in `(0, 1]` from a position and the seed.
+#[allow(clippy::cast_precision_loss)]
+fn unit_hash(x: usize, y: usize, seed: u32) -> f64 {
+    let mut h = (x as u64) << 32 | y as u64;
+    h ^= u64::from(seed).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    // splitmix64 finaliser
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+    ((h >> 11) + 1) as f64 / (1u64 << 53) as f64
+}
+
+// ---------------------------------------------------------------------------
+// Island balancing
+// ---------------------------------------------------------------------------
+
+/// City count at quantile `q` (in `0..1`) of the target distribution.
+///
+/// Within a bucket the count rises linearly from `min` to `max`; an
+/// open-ended bucket imposes no limit.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn target_quantile(targets: &[IslandSizeTarget], q: f64) -> usize {
+    let total: f64 = targets.iter().map(|t| t.weight).sum();
+    let mut below = 0.0;
+    for (i, t) in targets.iter().enumerate() {
+        let share = t.weight / total;
+        if q < below + share || i + 1 == targets.len() {
+            let Some(max) = t.max else {
+                return usize::MAX;
+            };
+            let frac = ((q - below) / share).clamp(0.0, 1.0);
+            return (f64::from(t.min) + frac * f64::from(max - t.min)).round() as usize;
+        }
+        below += share;
+    }
+    usize::MAX
+}
+
+/// Pick `keep` of `slots`, spread as evenly as possible.
+///
+/// Farthest-point sampling: start from the slot nearest the centroid, then
+/// repeatedly take the slot farthest from every slot taken so far. Ties go
+/// to the earlier slot. The picked slots are returned in input order.
+#[allow(clippy::cast_precision_loss)]
+fn thin_evenly(slots: &[(usize, usize)], keep: usize) -> Vec<(usize, usize)> {
+    if keep == 0 || slots.is_empty() {
+        return Vec::new();
+    }
+    let n = slots.len() as f64;
+    let cx = slots.iter().map(|&(x, _)| x as f64).sum::<f64>() / n;
+    let cy = slots.iter().map(|&(_, y)| y as f64).sum::<f64>() / n;
+    let mut first = 0;
+    let mut best = f64::MAX;
+    for (i, &(x, y)) in slots.iter().enumerate() {
+        let (dx, dy) = (x as f64 - cx, y as f64 - cy);
+        let d = dx.mul_add(dx, dy * dy);
+        if d < best {
+            best = d;
+            first = i;
+        }
+    }
+
+    let dist2 = |a: (usize, usize), b: (usize, usize)| {
+        let dx = a.0.abs_diff(b.0) as u64;
+        let dy = a.1.abs_diff(b.1) as u64;
+        dx * dx + dy * dy
+    };
+    // Squared distance from each slot to the nearest picked slot.
+    let mut nearest: Vec<u64> = slots.iter().map(|&s| dist2(s, slots[first])).collect();
+    let mut picked = vec![first];
+    while picked.len() < keep.min(slots.len()) {
+        let mut next = 0;
+        for (i, &d) in nearest.iter().enumerate() {
+            if d > nearest[next] {
+                next = i;
+            }
+        }
+        picked.push(next);
+        for (d, &s) in nearest.iter_mut().zip(slots) {
+            *d = (*d).min(dist2(s, slots[next]));
+        }
+    }
+
+    picked.sort_unstable();
+    picked.into_iter().map(|i| slots[i]).collect()
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
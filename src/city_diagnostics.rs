@@ -0,0 +1,279 @@
+//! Why city slots did (or did not) end up where they are.
+//!
+//! City placement normally keeps no record of the tiles it turns down.
+//! With `CITY_DIAGNOSTICS=true` the generator additionally produces a
+//! [`CityDiagnostics`]: the [`SlotOutcome`] of every Land tile, and an
+//! [`IslandDrop`] for every island removed for having too few slots.
+//!
+//! The CLI saves it next to the world file (see
+//! [`save::save_city_diagnostics`](crate::save::save_city_diagnostics)) and
+//! writes [`report_json`](CityDiagnostics::report_json) alongside. The
+//! viewer loads the sidecar if present and draws the outcomes as a debug
+//! tile layer.
+//!
+//! Outcomes are decided in the order below; a tile reports the first rule
+//! it breaks.
+//!
+//! | Outcome | Meaning |
+//! |---------|---------|
+//! | [`MapEdge`](SlotOutcome::MapEdge) | Within `city_spacing` of the map border, never scanned |
+//! | [`OutsidePlayableArea`](SlotOutcome::OutsidePlayableArea) | Beyond `playable_radius` |
+//! | [`Inland`](SlotOutcome::Inland) | No water neighbour at all |
+//! | [`FewLandNeighbors`](SlotOutcome::FewLandNeighbors) | Fewer than `min_land_neighbors` |
+//! | [`FewWaterNeighbors`](SlotOutcome::FewWaterNeighbors) | Fewer than `min_water_neighbors` |
+//! | [`SmallWaterBody`](SlotOutcome::SmallWaterBody) | Only touches bodies under `min_water_body_size` |
+//! | [`Spacing`](SlotOutcome::Spacing) | Qualified, but too close to an accepted slot |
+//! | [`IslandTooSmall`](SlotOutcome::IslandTooSmall) | Accepted, then dropped with its island |
+//! | [`Thinned`](SlotOutcome::Thinned) | Accepted, then removed from an over-full island |
+//! | [`Placed`](SlotOutcome::Placed) | A city slot in the finished world |
+
+use crate::coords::WorldPos;
+
+// ---------------------------------------------------------------------------
+// Outcomes
+// ---------------------------------------------------------------------------
+
+/// What city placement decided for one Land tile.
+///
+/// Discriminants are part of the diagnostics sidecar format; 0 marks tiles
+/// that are not Land.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SlotOutcome {
+    MapEdge = 1,
+    OutsidePlayableArea = 2,
+    Inland = 3,
+    FewLandNeighbors = 4,
+    FewWaterNeighbors = 5,
+    SmallWaterBody = 6,
+    Spacing = 7,
+    IslandTooSmall = 8,
+    Placed = 9,
+    Thinned = 10,
+}
+
+impl SlotOutcome {
+    /// Number of outcomes.
+    pub const COUNT: usize = 10;
+
+    /// Every outcome, in discriminant order.
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::MapEdge,
+        Self::OutsidePlayableArea,
+        Self::Inland,
+        Self::FewLandNeighbors,
+        Self::FewWaterNeighbors,
+        Self::SmallWaterBody,
+        Self::Spacing,
+        Self::IslandTooSmall,
+        Self::Placed,
+        Self::Thinned,
+    ];
+
+    /// Snake-case name used in the JSON report.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::MapEdge => "map_edge",
+            Self::OutsidePlayableArea => "outside_playable_area",
+            Self::Inland => "inland",
+            Self::FewLandNeighbors => "few_land_neighbors",
+            Self::FewWaterNeighbors => "few_water_neighbors",
+            Self::SmallWaterBody => "small_water_body",
+            Self::Spacing => "spacing",
+            Self::IslandTooSmall => "island_too_small",
+            Self::Placed => "placed",
+            Self::Thinned => "thinned",
+        }
+    }
+
+    /// Overlay colour in the debug tile layer, or `None` for outcomes that
+    /// are not drawn (inland tiles would cover every island).
+    #[must_use]
+    pub const fn color(self) -> Option<[u8; 3]> {
+        match self {
+            Self::MapEdge => Some([90, 90, 90]),
+            Self::OutsidePlayableArea => Some([150, 150, 150]),
+            Self::Inland => None,
+            Self::FewLandNeighbors => Some([170, 60, 200]),
+            Self::FewWaterNeighbors => Some([230, 90, 170]),
+            Self::SmallWaterBody => Some([40, 200, 230]),
+            Self::Spacing => Some([240, 220, 40]),
+            Self::IslandTooSmall => Some([245, 130, 30]),
+            Self::Placed => Some([30, 200, 60]),
+            Self::Thinned => Some([60, 110, 230]),
+        }
+    }
+
+    #[must_use]
+    pub const fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Deserialize from the stored `u8`; `None` for 0 (not Land) and
+    /// unknown values.
+    #[must_use]
+    pub fn from_u8(v: u8) -> Option<Self> {
+        Self::ALL.get((v as usize).checked_sub(1)?).copied()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Islands
+// ---------------------------------------------------------------------------
+
+/// Why an island was removed by
+/// [`filter_city_slots_by_region`](crate::city::filter_city_slots_by_region).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IslandDropReason {
+    /// Not a single tile qualified; see the outcome histogram for why.
+    NoQualifyingCoast = 0,
+    /// Some slots were placed, but fewer than `min_city_slots_per_island`.
+    TooFewSlots = 1,
+}
+
+impl IslandDropReason {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::NoQualifyingCoast => "no_qualifying_coast",
+            Self::TooFewSlots => "too_few_slots",
+        }
+    }
+
+    #[must_use]
+    pub const fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::TooFewSlots,
+            _ => Self::NoQualifyingCoast,
+        }
+    }
+}
+
+/// An island (region) that ended up without cities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IslandDrop {
+    pub region_id: u32,
+    pub reason: IslandDropReason,
+    /// Slots placed on the island before it was dropped.
+    pub slots: u32,
+    /// `min_city_slots_per_island` at generation time.
+    pub required: u32,
+    /// Land tiles per outcome, indexed like [`SlotOutcome::ALL`].
+    pub outcomes: [u32; SlotOutcome::COUNT],
+}
+
+impl IslandDrop {
+    /// Land tiles of the island.
+    #[must_use]
+    pub fn area(&self) -> u32 {
+        self.outcomes.iter().sum()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CityDiagnostics
+// ---------------------------------------------------------------------------
+
+/// Per-tile placement outcomes and dropped islands for one world.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CityDiagnostics {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major [`SlotOutcome::to_u8`] per tile, 0 where not Land.
+    pub outcomes: Vec<u8>,
+    /// Dropped islands, sorted by region id.
+    pub dropped_islands: Vec<IslandDrop>,
+}
+
+impl CityDiagnostics {
+    /// Outcome of the tile at `pos`, or `None` if it is off the map or not
+    /// Land.
+    #[must_use]
+    pub fn outcome_at(&self, pos: WorldPos) -> Option<SlotOutcome> {
+        if pos.x >= self.width || pos.y >= self.height {
+            return None;
+        }
+        let idx = pos.y as usize * self.width as usize + pos.x as usize;
+        SlotOutcome::from_u8(self.outcomes[idx])
+    }
+
+    /// Land tiles per outcome over the whole map, indexed like
+    /// [`SlotOutcome::ALL`].
+    #[must_use]
+    pub fn totals(&self) -> [u64; SlotOutcome::COUNT] {
+        let mut totals = [0u64; SlotOutcome::COUNT];
+        for &o in &self.outcomes {
+            if let Some(i) = (o as usize).checked_sub(1).filter(|&i| i < totals.len()) {
+                totals[i] += 1;
+            }
+        }
+        totals
+    }
+
+    /// Summary report: map-wide totals per outcome and every dropped island
+    /// with its own outcome histogram.
+    ///
+    /// ```json
+    /// {"width":2000,"height":2000,
+    ///  "totals":{"placed":5149,"spacing":80211,...},
+    ///  "dropped_islands":[{"region_id":3,"reason":"too_few_slots","slots":2,
+    ///    "required":6,"area":410,"outcomes":{"island_too_small":2,...}}]}
+    /// ```
+    #[must_use]
+    pub fn report_json(&self) -> String {
+        let totals = self.totals();
+        let islands: Vec<String> = self
+            .dropped_islands
+            .iter()
+            .map(|d| {
+                format!(
+                    "{{\"region_id\":{},\"reason\":\"{}\",\"slots\":{},\"required\":{},\"area\":{},\"outcomes\":{}}}",
+                    d.region_id,
+                    d.reason.name(),
+                    d.slots,
+                    d.required,
+                    d.area(),
+                    outcome_counts_json(&d.outcomes.map(u64::from))
+                )
+            })
+            .collect();
+        format!(
+            "{{\"width\":{},\"height\":{},\"totals\":{},\"dropped_islands\":[{}]}}",
+            self.width,
+            self.height,
+            outcome_counts_json(&totals),
+            islands.join(",")
+        )
+    }
+
+    /// Outcome of every drawn tile (see [`SlotOutcome::color`]) inside the
+    /// inclusive box `min..=max`: `[[x, y, "outcome"], ...]`.
+    #[must_use]
+    pub fn tiles_json(&self, min: WorldPos, max: WorldPos) -> String {
+        let mut entries = Vec::new();
+        for y in min.y..=max.y.min(self.height.saturating_sub(1)) {
+            for x in min.x..=max.x.min(self.width.saturating_sub(1)) {
+                let Some(outcome) = self.outcome_at(WorldPos::new(x, y)) else {
+                    continue;
+                };
+                if outcome.color().is_some() {
+                    entries.push(format!("[{x},{y},\"{}\"]", outcome.name()));
+                }
+            }
+        }
+        format!("[{}]", entries.join(","))
+    }
+}
+
+/// `{"map_edge":n,...}`, skipping zero counts.
+fn outcome_counts_json(counts: &[u64; SlotOutcome::COUNT]) -> String {
+    let fields: Vec<String> = SlotOutcome::ALL
+        .iter()
+        .zip(counts)
+        .filter(|&(_, &n)| n > 0)
+        .map(|(o, n)| format!("\"{}\":{n}", o.name()))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
@@ -10,6 +10,11 @@
 
 use std::env;
 
+use crate::biome::ResourceArea;
+use crate::city::{CityPlacement, IslandSizeTarget};
+use crate::names::NameStyle;
+use crate::sector::DEFAULT_SECTOR_SIZE;
+
 /// All tunable parameters for world generation.
 ///
 /// Use [`Default::default()`] for the standard 10,000 x 10,000 world.
@@ -48,6 +53,13 @@ pub struct WorldConfig {
     pub city_spacing: u8,
     /// Islands with fewer candidate city slots than this are excluded.
     pub min_city_slots_per_island: u8,
+    /// Islands with more city slots than this are thinned down to it
+    /// (0 = no cap). Never below `min_city_slots_per_island`.
+    pub max_city_slots_per_island: u16,
+    /// Target distribution of cities per island; empty keeps every
+    /// island's natural slot count. See
+    /// [`balance_island_city_slots`](crate::city::balance_island_city_slots).
+    pub island_size_targets: Vec<IslandSizeTarget>,
     /// Minimum size (in tiles) of a neighbouring water body for a city to
     /// qualify as coastal.
     pub min_water_body_size: u16,
@@ -55,6 +67,29 @@ pub struct WorldConfig {
     pub min_land_neighbors: u8,
     /// Minimum number of water neighbours a tile must have to place a city.
     pub min_water_neighbors: u8,
+    /// How qualifying tiles are chosen among when spacing out city slots.
+    pub city_placement: CityPlacement,
+    /// Which tiles each city's resource profile is aggregated over.
+    pub city_resource_area: ResourceArea,
+    /// Record why every Land tile did or did not become a city slot (see
+    /// [`city_diagnostics`](crate::city_diagnostics)). Off by default.
+    pub city_diagnostics: bool,
+    /// Largest sea travel time between two cities recorded in the world
+    /// file's neighbour lists (see
+    /// [`compute_city_distances`](crate::navigation::compute_city_distances)).
+    /// `0` (the default) skips the stage.
+    pub city_distance_radius: f32,
+
+    // -- Ocean sectors ------------------------------------------------------
+    /// Side length in tiles of the numbered [`sector`](crate::sector) grid
+    /// (`0` = no grid).
+    pub sector_size: u16,
+
+    // -- Names --------------------------------------------------------------
+    /// Language the generated island, sea and village names imitate.
+    pub name_style: NameStyle,
+    /// Also name every village (islands and seas are always named).
+    pub village_names: bool,
 
     // -- Village placement --------------------------------------------------
     /// Alpha coefficient in the village count formula:
@@ -106,6 +141,50 @@ impl WorldConfig {
             }),
         };
 
+        let city_placement = match env::var("CITY_PLACEMENT").ok().as_deref() {
+            Some("") | None => CityPlacement::default(),
+            Some(v) => CityPlacement::parse(v).unwrap_or_else(|| {
+                eprintln!("CITY_PLACEMENT: invalid value \"{v}\", using scan");
+                CityPlacement::default()
+            }),
+        };
+
+        let city_resource_area = match env::var("CITY_RESOURCE_AREA").ok().as_deref() {
+            Some("") | None => ResourceArea::default(),
+            Some(v) => ResourceArea::parse(v).unwrap_or_else(|| {
+                eprintln!("CITY_RESOURCE_AREA: invalid value \"{v}\", using circle");
+                ResourceArea::default()
+            }),
+        };
+
+        let island_size_targets = match env::var("ISLAND_SIZE_TARGETS").ok().as_deref() {
+            Some("") | None => Vec::new(),
+            Some(v) => IslandSizeTarget::parse_list(v).unwrap_or_else(|| {
+                eprintln!(
+                    "ISLAND_SIZE_TARGETS: invalid value \"{v}\", keeping natural island sizes"
+                );
+                Vec::new()
+            }),
+        };
+
+        let name_style = match env::var("NAME_STYLE").ok().as_deref() {
+            Some("") | None => NameStyle::default(),
+            Some(v) => NameStyle::parse(v).unwrap_or_else(|| {
+                eprintln!("NAME_STYLE: invalid value \"{v}\", using greek");
+                NameStyle::default()
+            }),
+        };
+
+        let min_city_slots_per_island = env_u8("MIN_CITY_SLOTS_PER_ISLAND", 6);
+        let mut max_city_slots_per_island = env_u16("MAX_CITY_SLOTS_PER_ISLAND", 0);
+        if (1..u16::from(min_city_slots_per_island)).contains(&max_city_slots_per_island) {
+            eprintln!(
+                "MAX_CITY_SLOTS_PER_ISLAND: {max_city_slots_per_island} is below \
+                 MIN_CITY_SLOTS_PER_ISLAND, using {min_city_slots_per_island}"
+            );
+            max_city_slots_per_island = u16::from(min_city_slots_per_island);
+        }
+
         let city_spacing = env_u8("CITY_SPACING", 5);
         let radius_frac = env_f32("PLAYABLE_RADIUS_FRAC", 0.975);
         let farland_margin = env_u16("FARLAND_MARGIN", city_spacing as u16 * 2);
@@ -122,10 +201,19 @@ impl WorldConfig {
             playable_radius: ((map_size as f32 / 2.0) * radius_frac) as u16,
             farland_margin,
             city_spacing,
-            min_city_slots_per_island: env_u8("MIN_CITY_SLOTS_PER_ISLAND", 6),
+            min_city_slots_per_island,
+            max_city_slots_per_island,
+            island_size_targets,
             min_water_body_size: env_u16("MIN_WATER_BODY_SIZE", 500),
             min_land_neighbors: env_u8("MIN_LAND_NEIGHBORS", 2),
             min_water_neighbors: env_u8("MIN_WATER_NEIGHBORS", 2),
+            city_placement,
+            city_resource_area,
+            city_diagnostics: env_bool("CITY_DIAGNOSTICS", false),
+            city_distance_radius: env_f32("CITY_DISTANCE_RADIUS", 0.0),
+            sector_size: env_u16("SECTOR_SIZE", DEFAULT_SECTOR_SIZE),
+            name_style,
+            village_names: env_bool("VILLAGE_NAMES", true),
             // Village defaults
             village_alpha: env_f64("VILLAGE_ALPHA", 1.2),
             village_beta: env_f64("VILLAGE_BETA", 0.60),
@@ -134,6 +222,13 @@ impl WorldConfig {
         }
     }
 
+    /// `true` if over-full islands are thinned, by a cap or a target
+    /// distribution.
+    #[must_use]
+    pub const fn balances_islands(&self) -> bool {
+        self.max_city_slots_per_island > 0 || !self.island_size_targets.is_empty()
+    }
+
     /// Map size as `usize` -- avoids casts in hot loops.
     pub fn map_len(&self) -> usize {
         self.map_size as usize
@@ -234,3 +329,16 @@ fn env_f64(key: &str, default: f64) -> f64 {
         }),
     }
 }
+
+/// Accepts `true`/`false` and `1`/`0`.
+fn env_bool(key: &str, default: bool) -> bool {
+    match env::var(key).ok().as_deref() {
+        Some("") | None => default,
+        Some("true" | "1") => true,
+        Some("false" | "0") => false,
+        Some(v) => {
+            eprintln!("{key}: invalid value \"{v}\", using default {default}");
+            default
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! Typed coordinates.
+//!
+//! | Type | Addresses |
+//! |------|-----------|
+//! | [`WorldPos`] | A tile of the world map, `(0, 0)` at the top-left |
+//! | [`ChunkPos`] | A chunk of the chunk grid |
+//! | [`LocalPos`] | A tile inside one chunk |
+//! | [`TileXYZ`] | A 256 x 256 slippy-map image at zoom level `z` |
+//!
+//! The types only carry numbers; [`MapGeometry`] knows the map size and
+//! chunk size and converts between them. Every conversion is checked and
+//! returns `None` for positions outside the map, so edge chunks (which are
+//! smaller when the map size is not a multiple of the chunk size) and the
+//! last row and column of the map need no special casing by callers.
+
+// ---------------------------------------------------------------------------
+// Position types
+// ---------------------------------------------------------------------------
+
+/// A tile position in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct WorldPos {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl WorldPos {
+    #[must_use]
+    pub const fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    /// Squared straight-line distance to `other`, in tiles.
+    #[must_use]
+    pub fn distance_sq(self, other: Self) -> u64 {
+        let dx = u64::from(self.x.abs_diff(other.x));
+        let dy = u64::from(self.y.abs_diff(other.y));
+        dx * dx + dy * dy
+    }
+
+    /// Straight-line distance to `other`, in tiles.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn distance(self, other: Self) -> f64 {
+        (self.distance_sq(other) as f64).sqrt()
+    }
+
+    /// The position `(dx, dy)` tiles away, or `None` if it would be negative
+    /// or overflow. Use [`MapGeometry::contains`] to check the far edges.
+    #[must_use]
+    pub fn offset(self, dx: i32, dy: i32) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_add_signed(dx)?,
+            y: self.y.checked_add_signed(dy)?,
+        })
+    }
+}
+
+impl From<(u32, u32)> for WorldPos {
+    fn from((x, y): (u32, u32)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<WorldPos> for (u32, u32) {
+    fn from(pos: WorldPos) -> Self {
+        (pos.x, pos.y)
+    }
+}
+
+/// A chunk position in the chunk grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct ChunkPos {
+    pub cx: u32,
+    pub cy: u32,
+}
+
+impl ChunkPos {
+    #[must_use]
+    pub const fn new(cx: u32, cy: u32) -> Self {
+        Self { cx, cy }
+    }
+}
+
+/// A tile position relative to the top-left tile of its chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LocalPos {
+    pub lx: u32,
+    pub ly: u32,
+}
+
+impl LocalPos {
+    #[must_use]
+    pub const fn new(lx: u32, ly: u32) -> Self {
+        Self { lx, ly }
+    }
+
+    /// Index into the row-major per-tile arrays of a chunk `chunk_width`
+    /// tiles wide.
+    #[must_use]
+    pub const fn index(self, chunk_width: u32) -> usize {
+        self.ly as usize * chunk_width as usize + self.lx as usize
+    }
+}
+
+/// A slippy-map tile: column `x`, row `y` of the `2^z x 2^z` grid at zoom `z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TileXYZ {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileXYZ {
+    /// Deepest zoom level representable with `u32` tile coordinates.
+    pub const MAX_Z: u32 = 31;
+
+    #[must_use]
+    pub const fn new(z: u32, x: u32, y: u32) -> Self {
+        Self { z, x, y }
+    }
+
+    /// `Some` if `x` and `y` lie inside the grid at zoom `z` and `z` does
+    /// not exceed `max_zoom`.
+    #[must_use]
+    pub fn checked(z: u32, x: u32, y: u32, max_zoom: u32) -> Option<Self> {
+        if z > max_zoom.min(Self::MAX_Z) {
+            return None;
+        }
+        let n = Self::tiles_per_axis(z);
+        (x < n && y < n).then_some(Self { z, x, y })
+    }
+
+    /// Number of tiles along each axis at zoom `z`.
+    #[must_use]
+    pub fn tiles_per_axis(z: u32) -> u32 {
+        1u32 << z.min(Self::MAX_Z)
+    }
+
+    /// The tile one zoom level out that contains this one.
+    #[must_use]
+    pub fn parent(self) -> Option<Self> {
+        let z = self.z.checked_sub(1)?;
+        Some(Self {
+            z,
+            x: self.x / 2,
+            y: self.y / 2,
+        })
+    }
+
+    /// The four tiles one zoom level in, row-major.
+    #[must_use]
+    pub const fn children(self) -> Option<[Self; 4]> {
+        if self.z >= Self::MAX_Z {
+            return None;
+        }
+        let (z, x, y) = (self.z + 1, self.x * 2, self.y * 2);
+        Some([
+            Self::new(z, x, y),
+            Self::new(z, x + 1, y),
+            Self::new(z, x, y + 1),
+            Self::new(z, x + 1, y + 1),
+        ])
+    }
+}
+
+/// The world-coordinate area covered by one [`TileXYZ`].
+///
+/// Fractional because tile edges rarely fall on whole world tiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileSpan {
+    pub x_start: f64,
+    pub y_start: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl TileSpan {
+    /// World position under pixel `(px, py)` of a `size` x `size` image of
+    /// this span. Not clamped to the map; see [`MapGeometry::clamp`].
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn pixel_pos(&self, px: u32, py: u32, size: u32) -> WorldPos {
+        WorldPos::new(
+            (self.x_start + f64::from(px) * self.width / f64::from(size)) as u32,
+            (self.y_start + f64::from(py) * self.height / f64::from(size)) as u32,
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MapGeometry
+// ---------------------------------------------------------------------------
+
+/// Map and chunk dimensions; converts between the coordinate types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapGeometry {
+    pub width: u32,
+    pub height: u32,
+    pub chunk_size: u32,
+}
+
+impl MapGeometry {
+    #[must_use]
+    pub const fn new(width: u32, height: u32, chunk_size: u32) -> Self {
+        Self {
+            width,
+            height,
+            chunk_size,
+        }
+    }
+
+    #[must_use]
+    pub const fn chunks_x(&self) -> u32 {
+        self.width.div_ceil(self.chunk_size)
+    }
+
+    #[must_use]
+    pub const fn chunks_y(&self) -> u32 {
+        self.height.div_ceil(self.chunk_size)
+    }
+
+    /// `true` if `pos` lies on the map.
+    #[must_use]
+    pub const fn contains(&self, pos: WorldPos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    /// The on-map position closest to `pos`.
+    #[must_use]
+    pub fn clamp(&self, pos: WorldPos) -> WorldPos {
+        WorldPos::new(
+            pos.x.min(self.width.saturating_sub(1)),
+            pos.y.min(self.height.saturating_sub(1)),
+        )
+    }
+
+    /// `Some(pos)` if `(x, y)` lies on the map.
+    #[must_use]
+    pub fn checked_pos(&self, x: u32, y: u32) -> Option<WorldPos> {
+        let pos = WorldPos::new(x, y);
+        self.contains(pos).then_some(pos)
+    }
+
+    /// `true` if `chunk` lies inside the chunk grid.
+    #[must_use]
+    pub const fn contains_chunk(&self, chunk: ChunkPos) -> bool {
+        chunk.cx < self.chunks_x() && chunk.cy < self.chunks_y()
+    }
+
+    /// Width and height in tiles of `chunk`; edge chunks may be smaller.
+    #[must_use]
+    pub fn chunk_dims(&self, chunk: ChunkPos) -> Option<(u32, u32)> {
+        let origin = self.chunk_origin(chunk)?;
+        Some((
+            self.chunk_size.min(self.width - origin.x),
+            self.chunk_size.min(self.height - origin.y),
+        ))
+    }
+
+    /// World position of the top-left tile of `chunk`.
+    #[must_use]
+    pub const fn chunk_origin(&self, chunk: ChunkPos) -> Option<WorldPos> {
+        if !self.contains_chunk(chunk) {
+            return None;
+        }
+        Some(WorldPos::new(
+            chunk.cx * self.chunk_size,
+            chunk.cy * self.chunk_size,
+        ))
+    }
+
+    /// The chunk containing `pos`, or `None` if `pos` is off the map.
+    #[must_use]
+    pub fn chunk_of(&self, pos: WorldPos) -> Option<ChunkPos> {
+        self.split(pos).map(|(chunk, _)| chunk)
+    }
+
+    /// The chunk containing `pos` and the position of `pos` inside it.
+    #[must_use]
+    pub const fn split(&self, pos: WorldPos) -> Option<(ChunkPos, LocalPos)> {
+        if !self.contains(pos) {
+            return None;
+        }
+        let cs = self.chunk_size;
+        Some((
+            ChunkPos::new(pos.x / cs, pos.y / cs),
+            LocalPos::new(pos.x % cs, pos.y % cs),
+        ))
+    }
+
+    /// Inverse of [`split`](Self::split): `None` if `local` lies outside
+    /// `chunk`.
+    #[must_use]
+    pub fn join(&self, chunk: ChunkPos, local: LocalPos) -> Option<WorldPos> {
+        let (w, h) = self.chunk_dims(chunk)?;
+        if local.lx >= w || local.ly >= h {
+            return None;
+        }
+        let origin = self.chunk_origin(chunk)?;
+        Some(WorldPos::new(origin.x + local.lx, origin.y + local.ly))
+    }
+
+    /// The chunk containing `pos` and the index of `pos` in that chunk's
+    /// per-tile arrays.
+    #[must_use]
+    pub fn tile_index(&self, pos: WorldPos) -> Option<(ChunkPos, usize)> {
+        let (chunk, local) = self.split(pos)?;
+        let (w, _) = self.chunk_dims(chunk)?;
+        Some((chunk, local.index(w)))
+    }
+
+    /// The chunks overlapping the inclusive world rectangle `min..=max`,
+    /// row-major. Empty if the rectangle is not entirely on the map.
+    #[must_use]
+    pub fn chunks_in(&self, min: WorldPos, max: WorldPos) -> Vec<ChunkPos> {
+        let (Some(a), Some(b)) = (self.chunk_of(min), self.chunk_of(max)) else {
+            return Vec::new();
+        };
+        (a.cy..=b.cy)
+            .flat_map(|cy| (a.cx..=b.cx).map(move |cx| ChunkPos::new(cx, cy)))
+            .collect()
+    }
+
+    /// The world area covered by slippy-map tile `tile`.
+    #[must_use]
+    pub fn tile_span(&self, tile: TileXYZ) -> TileSpan {
+        let n = f64::from(TileXYZ::tiles_per_axis(tile.z));
+        let (map_w, map_h) = (f64::from(self.width), f64::from(self.height));
+        TileSpan {
+            x_start: f64::from(tile.x) * map_w / n,
+            y_start: f64::from(tile.y) * map_h / n,
+            width: map_w / n,
+            height: map_h / n,
+        }
+    }
+
+    /// The slippy-map tile at zoom `z` whose span contains `pos`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn tile_containing(&self, pos: WorldPos, z: u32) -> Option<TileXYZ> {
+        if !self.contains(pos) || z > TileXYZ::MAX_Z {
+            return None;
+        }
+        let n = u64::from(TileXYZ::tiles_per_axis(z));
+        let x = (u64::from(pos.x) * n / u64::from(self.width)).min(n - 1) as u32;
+        let y = (u64::from(pos.y) * n / u64::from(self.height)).min(n - 1) as u32;
+        Some(TileXYZ::new(z, x, y))
+    }
+}
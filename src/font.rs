@@ -182,6 +182,9 @@ fn glyph_for(ch: char) -> [u8; 7] {
         '.' => [
             0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100,
         ],
+        '%' => [
+            0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011,
+        ],
         ' ' => [0; 7],
         _ => [
             0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111,
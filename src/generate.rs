@@ -0,0 +1,235 @@
+//! The world generation pipeline.
+//!
+//! [`generate_world`] turns a [`WorldConfig`] into a complete [`WorldData`]
+//! in memory. The CLI saves the result to disk; the viewer's preview mode
+//! serves it directly through [`World::from_data`](crate::World::from_data).
+//!
+//! # Pipeline
+//! 1. **Elevation**: Fractal Brownian motion (fBm) Perlin noise for heightmap
+//! 2. **Terrain**: Classify tiles as Water, Land, or `FarLand` (decorative)
+//! 3. **Region labels**: Flood-fill to discover islands and assign IDs
+//! 4. **Water bodies**: Label connected water regions
+//! 5. **Ocean distances**: Pre-compute distance-to-ocean for village placement
+//! 6. **Biomes**: Classify terrain into 16 biome types
+//! 7. **City slots**: Score coastal positions and space out the best ones,
+//!    drop small islands and thin over-full ones toward the target sizes
+//!    (with `CITY_DIAGNOSTICS`, also record why every other tile was
+//!    rejected; see [`city_diagnostics`](crate::city_diagnostics))
+//! 8. **Territory**: Give every Land tile of an island to its nearest city
+//! 9. **Resources**: Compute production modifiers and gold vein locations
+//!    (over a circle or the city's territory)
+//! 10. **City sites**: Describe each city's facing, harbor and shore
+//! 11. **Villages**: Place inland villages with trade specialization
+//! 12. **Package**: Assemble [`WorldData`] (with the island registry and the
+//!     numbered [`sector`](crate::sector) grid) and capture generation metadata
+//! 13. **Names**: Give every island, sea and village a generated name in
+//!     the configured style (see [`names`](crate::names))
+//! 14. **Sea distances**: With `CITY_DISTANCE_RADIUS`, list every city's
+//!     sea neighbours and their travel times (see
+//!     [`compute_city_distances`](crate::navigation::compute_city_distances))
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::biome::ResourceArea;
+use crate::config::WorldConfig;
+use crate::metadata::{GenerationMetadata, PhaseTiming};
+use crate::navigation::{self, NavOptions};
+use crate::save::{self, WorldData};
+use crate::{biome, city, elevation, names, terrain, territory, village};
+
+/// Run every generation phase for `config`.
+///
+/// Nothing is printed: the returned data carries its [`GenerationMetadata`]
+/// (phase timings and counts), ready to be reported, saved or wrapped in a
+/// [`World`](crate::World).
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn generate_world(config: &WorldConfig) -> WorldData {
+    let mut timings = Vec::new();
+
+    // Phase 1: Generate heightmap using fractal Brownian motion
+    let elevation_grid = timed(&mut timings, "Elevation", || elevation::generate(config));
+
+    // Phase 2: Classify each tile's terrain type based on elevation and distance
+    let terrain_grid = timed(&mut timings, "Terrain", || {
+        terrain::classify_terrain(
+            &elevation_grid,
+            config.map_len(),
+            config.water_threshold,
+            config.playable_radius,
+            config.farland_margin,
+        )
+    });
+
+    // Phase 3: Label connected land tiles as numbered regions (islands)
+    let region_labels = timed(&mut timings, "Regions", || {
+        terrain::label_regions(&terrain_grid, config.map_len())
+    });
+
+    // Phase 4: Label connected water tiles as numbered water bodies
+    let water_bodies = timed(&mut timings, "Water bodies", || {
+        terrain::label_water_bodies(&terrain_grid, config.map_len())
+    });
+
+    // Phase 5: Compute distance from each tile to nearest ocean/farland
+    // (Used by village placement to find genuinely inland positions)
+    let ocean_distances = timed(&mut timings, "Ocean distances", || {
+        terrain::compute_ocean_distances(&terrain_grid, config.map_len())
+    });
+
+    // Phase 6: Classify terrain into 16 biome types using multiple noise layers
+    // (Scored city placement rates candidate sites by their surrounding biomes)
+    let biomes = timed(&mut timings, "Biomes", || {
+        biome::generate_biomes(config, &terrain_grid, &elevation_grid)
+    });
+
+    // Phase 7: Find valid coastal locations for city placement
+    let city_slots = timed(&mut timings, "City slots", || {
+        city::find_city_slots(
+            &terrain_grid,
+            &water_bodies,
+            &elevation_grid,
+            &biomes,
+            config,
+        )
+    });
+
+    // Phase 8: Filter city slots to keep only those on large enough islands
+    let filtered_cities = timed(&mut timings, "Filter islands", || {
+        city::filter_city_slots_by_region(
+            &city_slots,
+            &region_labels,
+            config.min_city_slots_per_island as usize,
+        )
+    });
+
+    // Phase 9: Thin over-full islands toward the configured size distribution
+    let filtered_cities = if config.balances_islands() {
+        timed(&mut timings, "Balance islands", || {
+            city::balance_island_city_slots(&filtered_cities, &region_labels, config)
+        })
+    } else {
+        filtered_cities
+    };
+
+    // Optional: record why every other coastal tile got no city
+    let city_diagnostics = config.city_diagnostics.then(|| {
+        timed(&mut timings, "City diagnostics", || {
+            city::diagnose_city_slots(
+                &terrain_grid,
+                &water_bodies,
+                &region_labels,
+                &city_slots,
+                &filtered_cities,
+                config,
+            )
+        })
+    });
+
+    // Phase 10: Count cities per region (needed for Favor scaling and village placement)
+    let region_city_counts: HashMap<usize, u32> = {
+        let mut counts = HashMap::new();
+        for &(x, y) in &filtered_cities {
+            let region_id = region_labels[y][x];
+            if region_id > 0 {
+                *counts.entry(region_id).or_insert(0u32) += 1;
+            }
+        }
+        counts
+    };
+
+    // Phase 11: Partition every island's land between its cities
+    let territory = timed(&mut timings, "Territory", || {
+        territory::compute_territory(&region_labels, &filtered_cities)
+    });
+
+    // Phase 12: Compute per-city resource profiles and gold vein locations
+    let city_resources = timed(&mut timings, "City resources", || {
+        biome::compute_city_resources(
+            &filtered_cities,
+            &biomes,
+            &region_labels,
+            (config.city_resource_area == ResourceArea::Territory).then_some(&territory[..]),
+            &region_city_counts,
+            u32::from(config.min_city_slots_per_island),
+            config.seed,
+        )
+    });
+
+    // Phase 13: Describe each city's surroundings (facing, harbor, shore)
+    let city_sites = timed(&mut timings, "City sites", || {
+        city::compute_city_sites(
+            &filtered_cities,
+            &terrain_grid,
+            &elevation_grid,
+            &biomes,
+            &water_bodies,
+            config.water_threshold,
+        )
+    });
+
+    // Phase 14: Place villages on each island with trade specialization
+    let villages = timed(&mut timings, "Villages", || {
+        village::place_villages(
+            &terrain_grid,
+            &biomes,
+            &region_labels,
+            &ocean_distances,
+            &region_city_counts,
+            &filtered_cities,
+            config,
+        )
+    });
+
+    // Phase 15: Package everything for binary file storage
+    let mut world_data = timed(&mut timings, "Build world data", || {
+        save::build_world_data(
+            elevation_grid,
+            terrain_grid,
+            region_labels,
+            &filtered_cities,
+            biomes,
+            territory,
+            city_resources,
+            city_sites,
+            villages,
+            config.clone(),
+        )
+    });
+
+    // Phase 16: Names for islands, seas and villages
+    timed(&mut timings, "Names", || {
+        names::assign_names(&mut world_data);
+    });
+
+    // Phase 17: Sea travel times between nearby cities (opt-in)
+    if config.city_distance_radius > 0.0 {
+        world_data.city_distances = timed(&mut timings, "Sea distances", || {
+            navigation::compute_city_distances(
+                &world_data,
+                config.city_distance_radius,
+                &NavOptions::default(),
+            )
+        });
+    }
+
+    world_data.metadata = GenerationMetadata::capture(&world_data, timings);
+    world_data.city_diagnostics = city_diagnostics;
+    world_data
+}
+
+/// Run a closure, measure its execution time, and return result.
+///
+/// Used to track performance of each generation phase; every measurement is
+/// appended to `timings` for the world file's generation metadata.
+fn timed<T>(timings: &mut Vec<PhaseTiming>, phase_name: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    timings.push(PhaseTiming {
+        name: phase_name.to_owned(),
+        duration: elapsed,
+    });
+    result
+}
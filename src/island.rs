@@ -2,11 +2,19 @@
 //!
 //! An island is a contiguous region of land tiles (see [`Land`](crate::terrain::Terrain::Land))
 //! that contains at least one city. This module provides the [`Island`] type and
-//! a discovery function to build the island registry from chunked world data.
+//! two ways to build the island registry:
+//!
+//! - [`compute_islands`] runs at generation time on the flat world grids; the
+//!   result is stored in the world file (format version 5+).
+//! - [`discover_islands`] rebuilds it from chunked world data. It is only
+//!   needed for older files that do not store the registry.
 
 use std::collections::HashMap;
 
-use crate::save::{ChunkData, ChunkedWorldReader};
+use crate::backend::WorldBackend;
+use crate::biome::CityResources;
+use crate::coords::{ChunkPos, LocalPos, MapGeometry, WorldPos};
+use crate::save::{ChunkData, WorldData};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -48,12 +56,20 @@ impl BoundingBox {
 pub struct Island {
     /// Unique region label from flood-fill (one per landmass).
     pub id: u32,
+    /// Generated [name](crate::names); empty for files older than version
+    /// 11.
+    pub name: String,
     /// Number of city slots on this island.
     pub city_count: u32,
     /// Average position (centroid) of all cities on the island.
     pub centroid: (u32, u32),
     /// Axis-aligned bounding box around the island's tiles.
     pub bounds: BoundingBox,
+    /// Number of land tiles.
+    pub area: u32,
+    /// Number of tile edges between the island and anything else (water,
+    /// other regions or the map border).
+    pub perimeter: u32,
     /// True if this island is the designated world spawn (largest island).
     pub is_world_spawn: bool,
     /// Population order: 0 = world spawn, 1 = nearest to spawn, 2 = next, ...
@@ -64,87 +80,252 @@ pub struct Island {
     pub spawn_order: u32,
 }
 
+/// Summed passive resource modifiers of a group of cities.
+///
+/// Wider than [`CityResources`](crate::biome::CityResources) so large
+/// islands cannot overflow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceTotals {
+    pub wood: i32,
+    pub stone: i32,
+    pub food: i32,
+    pub metal: i32,
+    pub favor: i32,
+    /// Sum of every city's [`gold_nodes`](crate::biome::CityResources::gold_nodes).
+    /// Nodes shared by neighbouring cities are counted once per city.
+    pub gold_nodes: u32,
+}
+
+impl ResourceTotals {
+    /// Add one city's resource profile.
+    pub fn add(&mut self, cr: &CityResources) {
+        self.wood += i32::from(cr.wood);
+        self.stone += i32::from(cr.stone);
+        self.food += i32::from(cr.food);
+        self.metal += i32::from(cr.metal);
+        self.favor += i32::from(cr.favor);
+        self.gold_nodes += u32::from(cr.gold_nodes);
+    }
+}
+
+/// Everything on one island, as returned by
+/// [`World::island`](crate::World::island).
+#[derive(Debug, Clone)]
+pub struct IslandDetails {
+    /// The island's registry entry.
+    pub island: Island,
+    /// Indices into [`World::city_slots`](crate::World::city_slots) (and
+    /// `city_resources`), ascending.
+    pub cities: Vec<usize>,
+    /// Indices into [`World::villages`](crate::World::villages), ascending.
+    pub villages: Vec<usize>,
+    /// Resource profiles of the island's cities, summed.
+    pub resources: ResourceTotals,
+    /// Number of gold vein tiles on the island.
+    pub gold_vein_tiles: u32,
+    /// `(biome, tile count)` over the island's tiles, sorted by biome id.
+    pub biome_counts: Vec<(u8, u32)>,
+}
+
+/// Tile statistics of one region, accumulated while scanning the map.
+#[derive(Default)]
+struct RegionShape {
+    bounds: Option<BoundingBox>,
+    area: u32,
+    perimeter: u32,
+}
+
+impl RegionShape {
+    fn add_tile(&mut self, x: u32, y: u32, exposed_edges: u32) {
+        match &mut self.bounds {
+            Some(bb) => bb.expand(x, y),
+            None => self.bounds = Some(BoundingBox::point(x, y)),
+        }
+        self.area += 1;
+        self.perimeter += exposed_edges;
+    }
+}
+
+/// Per-region city stats: `(sum_x, sum_y, count)`.
+type CityStats = HashMap<u32, (u64, u64, u32)>;
+
 // ---------------------------------------------------------------------------
-// Discovery
+// Generation
 // ---------------------------------------------------------------------------
 
-/// Scans city slots and chunk data to build the island registry.
+/// Build the island registry from freshly generated world data.
 ///
-/// After assembling basic island data, this function:
-/// 1. Tags the island with the most city slots as the world spawn.
-/// 2. Ranks every other island by centroid distance from the spawn, assigning `spawn_order` values starting at 1.
-pub fn discover_islands(
-    reader: &ChunkedWorldReader,
-    chunk_cache: &mut HashMap<(u32, u32), ChunkData>,
-) -> Vec<Island> {
-    let header = &reader.header;
-    let chunk_size = header.config.chunk_size as u32;
-
-    // Step 1: Gather per-region city stats (sum_x, sum_y, count).
-    let mut city_stats: HashMap<u32, (u64, u64, u32)> = HashMap::new();
-    for &(x, y) in &header.city_slots {
-        let region_id = region_label_at(reader, chunk_cache, chunk_size, x, y);
-        if region_id == 0 {
-            continue;
+/// Produces exactly what [`discover_islands`] would find in the saved file.
+#[must_use]
+pub fn compute_islands(data: &WorldData) -> Vec<Island> {
+    let (width, height) = (data.width, data.height);
+    let label_at = |x: u32, y: u32| data.region_labels[(y * width + x) as usize];
+
+    let mut city_stats = CityStats::new();
+    for (&(x, y), &region_id) in data.city_slots.iter().zip(&data.city_regions) {
+        add_city(&mut city_stats, region_id, x, y);
+    }
+
+    let mut shapes: HashMap<u32, RegionShape> = HashMap::new();
+    for y in 0..height {
+        for x in 0..width {
+            let region_id = label_at(x, y);
+            if region_id == 0 || !city_stats.contains_key(&region_id) {
+                continue;
+            }
+            let exposed = exposed_edges(x, y, region_id, width, height, label_at);
+            shapes.entry(region_id).or_default().add_tile(x, y, exposed);
         }
-        let entry = city_stats.entry(region_id).or_insert((0, 0, 0));
-        entry.0 += x as u64;
-        entry.1 += y as u64;
-        entry.2 += 1;
     }
 
-    // Step 2: Load every chunk and compute bounding boxes from region tiles.
-    let mut bounding_boxes: HashMap<u32, BoundingBox> = HashMap::new();
+    assemble_islands(city_stats, &shapes)
+}
+
+// ---------------------------------------------------------------------------
+// Discovery (fallback for files without a stored registry)
+// ---------------------------------------------------------------------------
+
+/// Scans city slots and chunk data from `reader` to build the island registry.
+///
+/// Chunks whose [summary](crate::save::ChunkSummary) shows no city-bearing
+/// region are skipped without being decompressed; every other chunk is held
+/// in memory until discovery finishes.
+pub fn discover_islands(reader: &dyn WorldBackend) -> Vec<Island> {
+    let chunk_cache = &mut HashMap::new();
+    let header = reader.header();
+    let geometry = header.geometry();
+    let (width, height) = (header.width, header.height);
+
+    // Step 1: Gather per-region city stats.
+    let mut city_stats = CityStats::new();
+    for &(x, y) in &header.city_slots {
+        let region_id = region_label_at(reader, chunk_cache, geometry, WorldPos::new(x, y));
+        add_city(&mut city_stats, region_id, x, y);
+    }
+
+    // Step 2: Load every chunk that intersects a city-bearing region.
+    // Chunk summaries (format version 4+) let us skip open water without
+    // decompressing it.
     for cy in 0..header.chunks_y {
         for cx in 0..header.chunks_x {
-            ensure_chunk(reader, chunk_cache, cx, cy);
-            let Some(chunk) = chunk_cache.get(&(cx, cy)) else {
-                continue;
-            };
-            let x0 = cx * chunk_size;
-            let y0 = cy * chunk_size;
-            for ly in 0..chunk.height {
-                for lx in 0..chunk.width {
-                    let region_id = chunk.region_labels[(ly * chunk.width + lx) as usize];
-                    if region_id == 0 || !city_stats.contains_key(&region_id) {
-                        continue;
-                    }
-                    bounding_boxes
-                        .entry(region_id)
-                        .and_modify(|bb| bb.expand(x0 + lx, y0 + ly))
-                        .or_insert_with(|| BoundingBox::point(x0 + lx, y0 + ly));
+            if let Some(summary) = reader.chunk_summary(cx, cy) {
+                if !summary
+                    .region_ids
+                    .iter()
+                    .any(|rid| city_stats.contains_key(rid))
+                {
+                    continue;
                 }
             }
+            ensure_chunk(reader, chunk_cache, ChunkPos::new(cx, cy));
         }
     }
 
-    // Step 3: Assemble Island structs (is_world_spawn and spawn_order filled below).
+    // Step 3: Measure regions from the cached tiles. Skipped chunks hold no
+    // city-bearing region, so treating them as region 0 is exact.
+    let cache = &*chunk_cache;
+    let label_at = |x: u32, y: u32| cached_region_label(cache, geometry, WorldPos::new(x, y));
+    let mut shapes: HashMap<u32, RegionShape> = HashMap::new();
+    for (&chunk_pos, chunk) in cache {
+        for ly in 0..chunk.height {
+            for lx in 0..chunk.width {
+                let local = LocalPos::new(lx, ly);
+                let region_id = chunk.region_labels[local.index(chunk.width)];
+                if region_id == 0 || !city_stats.contains_key(&region_id) {
+                    continue;
+                }
+                let Some(WorldPos { x, y }) = geometry.join(chunk_pos, local) else {
+                    continue;
+                };
+                let exposed = exposed_edges(x, y, region_id, width, height, label_at);
+                shapes.entry(region_id).or_default().add_tile(x, y, exposed);
+            }
+        }
+    }
+
+    assemble_islands(city_stats, &shapes)
+}
+
+// ---------------------------------------------------------------------------
+// Shared assembly
+// ---------------------------------------------------------------------------
+
+fn add_city(city_stats: &mut CityStats, region_id: u32, x: u32, y: u32) {
+    if region_id == 0 {
+        return;
+    }
+    let entry = city_stats.entry(region_id).or_insert((0, 0, 0));
+    entry.0 += u64::from(x);
+    entry.1 += u64::from(y);
+    entry.2 += 1;
+}
+
+/// Number of 4-neighbours of `(x, y)` outside region `region_id`, counting
+/// positions beyond the map border.
+fn exposed_edges(
+    x: u32,
+    y: u32,
+    region_id: u32,
+    width: u32,
+    height: u32,
+    label_at: impl Fn(u32, u32) -> u32,
+) -> u32 {
+    let mut exposed = 0;
+    let neighbors = [
+        (x.checked_sub(1), Some(y)),
+        (Some(x + 1).filter(|&nx| nx < width), Some(y)),
+        (Some(x), y.checked_sub(1)),
+        (Some(x), Some(y + 1).filter(|&ny| ny < height)),
+    ];
+    for (nx, ny) in neighbors {
+        match (nx, ny) {
+            (Some(nx), Some(ny)) if label_at(nx, ny) == region_id => {}
+            _ => exposed += 1,
+        }
+    }
+    exposed
+}
+
+/// Turn per-region stats into the island registry.
+///
+/// After assembling basic island data, this function:
+/// 1. Tags the island with the most city slots as the world spawn.
+/// 2. Ranks every other island by centroid distance from the spawn, assigning `spawn_order` values starting at 1.
+fn assemble_islands(city_stats: CityStats, shapes: &HashMap<u32, RegionShape>) -> Vec<Island> {
+    // Step 1: Assemble Island structs (is_world_spawn and spawn_order filled below).
     let mut islands: Vec<Island> = city_stats
         .into_iter()
-        .map(|(region_id, (sum_x, sum_y, count))| Island {
-            id: region_id,
-            city_count: count,
-            centroid: ((sum_x / count as u64) as u32, (sum_y / count as u64) as u32),
-            bounds: bounding_boxes.get(&region_id).copied().unwrap_or_default(),
-            is_world_spawn: false,
-            spawn_order: 0,
+        .map(|(region_id, (sum_x, sum_y, count))| {
+            let shape = shapes.get(&region_id);
+            // A mean of u32 coordinates always fits back into a u32.
+            let mean = |sum: u64| u32::try_from(sum / u64::from(count)).unwrap_or(u32::MAX);
+            Island {
+                id: region_id,
+                name: String::new(),
+                city_count: count,
+                centroid: (mean(sum_x), mean(sum_y)),
+                bounds: shape.and_then(|s| s.bounds).unwrap_or_default(),
+                area: shape.map_or(0, |s| s.area),
+                perimeter: shape.map_or(0, |s| s.perimeter),
+                is_world_spawn: false,
+                spawn_order: 0,
+            }
         })
         .collect();
 
-    // Step 4: Tag the largest island as world spawn.
+    // Sort first so ties in city count pick the same spawn every time.
+    islands.sort_by_key(|i| i.id);
+
+    // Step 2: Tag the largest island as world spawn.
     let spawn_centroid = if let Some(spawn) = islands.iter_mut().max_by_key(|i| i.city_count) {
         spawn.is_world_spawn = true;
-        eprintln!(
-            "Tagged island {} as world spawn ({} cities)",
-            spawn.id, spawn.city_count
-        );
         spawn.centroid
     } else {
         // No islands at all -- nothing more to do.
         return islands;
     };
 
-    // Step 5: Rank remaining islands by distance from the spawn centroid.
+    // Step 3: Rank remaining islands by distance from the spawn centroid.
     // Use squared distance to avoid sqrt; relative order is identical.
     let (sx, sy) = (spawn_centroid.0 as i64, spawn_centroid.1 as i64);
     let mut non_spawn: Vec<&mut Island> =
@@ -153,53 +334,59 @@ pub fn discover_islands(
     non_spawn.sort_by_key(|island| {
         let dx = island.centroid.0 as i64 - sx;
         let dy = island.centroid.1 as i64 - sy;
-        dx * dx + dy * dy
+        (dx * dx + dy * dy, island.id)
     });
 
     for (order, island) in non_spawn.iter_mut().enumerate() {
         island.spawn_order = (order + 1) as u32;
     }
 
-    islands.sort_by_key(|i| i.id);
-    eprintln!("Discovered {} islands", islands.len());
     islands
 }
 
 // ---------------------------------------------------------------------------
-// Chunk helpers (also used by world.rs)
+// Chunk helpers
 // ---------------------------------------------------------------------------
 
 /// Loads a chunk into the cache if it isn't already present.
-pub(crate) fn ensure_chunk(
-    reader: &ChunkedWorldReader,
-    cache: &mut HashMap<(u32, u32), ChunkData>,
-    cx: u32,
-    cy: u32,
+fn ensure_chunk(
+    reader: &dyn WorldBackend,
+    cache: &mut HashMap<ChunkPos, ChunkData>,
+    pos: ChunkPos,
 ) {
     use std::collections::hash_map::Entry;
-    if let Entry::Vacant(e) = cache.entry((cx, cy)) {
-        if let Ok(chunk) = reader.load_chunk(cx, cy) {
+    if let Entry::Vacant(e) = cache.entry(pos) {
+        if let Ok(chunk) = reader.load_chunk(pos.cx, pos.cy) {
             e.insert(chunk);
         }
     }
 }
 
-/// Returns the region label for a world coordinate, loading the containing chunk if necessary.
-fn region_label_at(
-    reader: &ChunkedWorldReader,
-    cache: &mut HashMap<(u32, u32), ChunkData>,
-    chunk_size: u32,
-    x: u32,
-    y: u32,
+/// Returns the region label for a world coordinate from cached chunks only
+/// (0 if not cached or off the map).
+fn cached_region_label(
+    cache: &HashMap<ChunkPos, ChunkData>,
+    geometry: MapGeometry,
+    pos: WorldPos,
 ) -> u32 {
-    let (chunk_x, chunk_y) = (x / chunk_size, y / chunk_size);
-    ensure_chunk(reader, cache, chunk_x, chunk_y);
-    cache
-        .get(&(chunk_x, chunk_y))
-        .map(|chunk| {
-            let local_x = (x - chunk_x * chunk_size) as usize;
-            let local_y = (y - chunk_y * chunk_size) as usize;
-            chunk.region_labels[local_y * chunk.width as usize + local_x]
+    geometry
+        .split(pos)
+        .and_then(|(chunk, local)| {
+            let chunk = cache.get(&chunk)?;
+            Some(chunk.region_labels[local.index(chunk.width)])
         })
         .unwrap_or(0)
 }
+
+/// Returns the region label for a world coordinate, loading the containing chunk if necessary.
+fn region_label_at(
+    reader: &dyn WorldBackend,
+    cache: &mut HashMap<ChunkPos, ChunkData>,
+    geometry: MapGeometry,
+    pos: WorldPos,
+) -> u32 {
+    if let Some(chunk) = geometry.chunk_of(pos) {
+        ensure_chunk(reader, cache, chunk);
+    }
+    cached_region_label(cache, geometry, pos)
+}
@@ -13,27 +13,57 @@
 //!
 //! | Module | Responsibility |
 //! |--------|---------------|
+//! | [`backend`] | Storage behind [`World`]: world file or in-memory data |
 //! | [`biome`] | Biome classification, resource modifiers, gold veins, city resources |
-//! | [`city`] | Coastal city-slot placement |
+//! | [`cache`] | Bounded LRU cache for decompressed chunks |
+//! | [`city`] | Coastal city-slot placement and site descriptions |
+//! | [`city_diagnostics`] | Optional per-tile record of why city slots were placed or rejected |
 //! | [`config`] | [`WorldConfig`] -- the single source of every tunable parameter |
+//! | [`coords`] | Typed world, chunk, local and slippy-map tile coordinates |
 //! | [`elevation`] | Perlin-noise heightmap generation |
 //! | [`font`] | Minimal 5x7 bitmap font for debug overlays |
-//! | [`island`] | Island discovery and representation |
+//! | [`generate`] | The generation pipeline: [`WorldConfig`] in, `WorldData` out |
+//! | [`island`] | Island registry: built at generation, discovery fallback |
+//! | [`lod`] | Downsampled raster pyramid for low-zoom tiles |
+//! | [`metadata`] | Generation provenance: version, timestamps, phase timings |
+//! | [`migrate`] | Upgrade old world files to the current format |
+//! | [`names`] | Seeded procedural names for islands, seas and villages |
+//! | [`navigation`] | Naval and land routes, precomputed sea travel times between cities |
+//! | [`rect`] | Row-major reads of world rectangles across chunk boundaries |
 //! | [`save`] | Chunked, compressed binary file format |
+//! | [`sector`] | Numbered ocean sector grid with per-sector islands, cities and land |
+//! | [`spatial`] | Grid index for nearest / radius / bbox queries on cities and villages |
 //! | [`terrain`] | Terrain classification, region labeling, ocean distance map |
+//! | [`territory`] | Per-tile partition of every island's land between its cities |
 //! | [`tile`] | Slippy-map tile renderer (256 x 256 PNGs) |
+//! | [`validate`] | Invariant checks over a finished world ([`validate::validate_world`]) |
 //! | [`village`] | Inland village placement and trade profile computation |
-//! | [`world`] | High-level [`World`] facade for game / viewer code |
+//! | [`world`] | High-level [`World`] facade for game / viewer code, per-tile queries |
 
+pub mod backend;
 pub mod biome;
+pub mod cache;
 pub mod city;
+pub mod city_diagnostics;
 pub mod config;
+pub mod coords;
 pub mod elevation;
 pub mod font;
+pub mod generate;
 pub mod island;
+pub mod lod;
+pub mod metadata;
+pub mod migrate;
+pub mod names;
+pub mod navigation;
+pub mod rect;
 pub mod save;
+pub mod sector;
+pub mod spatial;
 pub mod terrain;
+pub mod territory;
 pub mod tile;
+pub mod validate;
 pub mod village;
 pub mod world;
 
@@ -0,0 +1,118 @@
+//! Low-resolution level-of-detail (LOD) pyramid.
+//!
+//! Rendering a zoomed-out map tile only needs one world tile per output
+//! pixel, yet sampling the full-resolution chunks means decompressing every
+//! chunk the tile overlaps -- at zoom 0 that is the whole world. The LOD
+//! pyramid stores a handful of downsampled copies of the terrain, elevation
+//! and biome layers so [`tile`](crate::tile) can serve low zoom levels from a
+//! single small raster instead.
+//!
+//! Level `k` keeps every `factor`-th tile in each direction (nearest
+//! sampling, `factor = 2^k`), which matches what the tile renderer does with
+//! full-resolution data. Only levels no larger than [`LOD_MAX_SIDE`] are
+//! stored; the pyramid stops at the first level that fits in a single
+//! [`TILE_SIZE`](crate::tile::TILE_SIZE) tile.
+
+use crate::save::WorldData;
+use crate::tile::TILE_SIZE;
+
+/// Largest side length (in pixels) of a stored LOD level.
+///
+/// At 2048 the finest stored level of a 10,000-tile world is 1250 x 1250
+/// (factor 8), roughly 6 MB uncompressed.
+pub const LOD_MAX_SIDE: u32 = 2048;
+
+/// Shape of one pyramid level, as listed in the file header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LodInfo {
+    /// World tiles per LOD pixel along each axis (a power of two, >= 2).
+    pub factor: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A decoded pyramid level.
+#[derive(Clone)]
+pub struct LodLevel {
+    pub info: LodInfo,
+    /// Row-major terrain type (`Terrain::to_u8()`).
+    pub terrain: Vec<u8>,
+    /// Row-major elevation.
+    pub elevation: Vec<f32>,
+    /// Row-major biome (`Biome::to_u8()`).
+    pub biomes: Vec<u8>,
+}
+
+impl LodLevel {
+    /// Downsample `data` by `factor` using nearest sampling.
+    #[must_use]
+    pub fn from_data(data: &WorldData, factor: u32) -> Self {
+        let info = LodInfo {
+            factor,
+            width: data.width.div_ceil(factor),
+            height: data.height.div_ceil(factor),
+        };
+        let pixels = (info.width * info.height) as usize;
+        let mut level = Self {
+            info,
+            terrain: Vec::with_capacity(pixels),
+            elevation: Vec::with_capacity(pixels),
+            biomes: Vec::with_capacity(pixels),
+        };
+        for ly in 0..info.height {
+            let row = (ly * factor) as usize * data.width as usize;
+            for lx in 0..info.width {
+                let idx = row + (lx * factor) as usize;
+                level.terrain.push(data.terrain[idx]);
+                level.elevation.push(data.elevation[idx]);
+                level.biomes.push(data.biomes[idx]);
+            }
+        }
+        level
+    }
+
+    /// Index of the LOD pixel covering world tile `(x, y)`.
+    #[must_use]
+    pub fn index_of(&self, x: u32, y: u32) -> usize {
+        let lx = (x / self.info.factor).min(self.info.width - 1);
+        let ly = (y / self.info.factor).min(self.info.height - 1);
+        (ly * self.info.width + lx) as usize
+    }
+}
+
+/// Pyramid levels to store for a `width x height` world, finest first.
+#[must_use]
+pub fn lod_levels_for(width: u32, height: u32) -> Vec<LodInfo> {
+    let mut levels = Vec::new();
+    let mut factor = 2u32;
+    loop {
+        let info = LodInfo {
+            factor,
+            width: width.div_ceil(factor),
+            height: height.div_ceil(factor),
+        };
+        let side = info.width.max(info.height);
+        if side <= LOD_MAX_SIDE {
+            levels.push(info);
+        }
+        if side <= TILE_SIZE || info.width <= 1 && info.height <= 1 {
+            return levels;
+        }
+        factor *= 2;
+    }
+}
+
+/// Pick the coarsest level whose pixels are no larger than
+/// `world_tiles_per_pixel`, i.e. the cheapest level that still has at least
+/// one sample per rendered pixel.
+///
+/// Returns `None` if every stored level is too coarse.
+#[must_use]
+pub fn best_level(levels: &[LodInfo], world_tiles_per_pixel: f64) -> Option<usize> {
+    levels
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| f64::from(l.factor) <= world_tiles_per_pixel)
+        .max_by_key(|(_, l)| l.factor)
+        .map(|(i, _)| i)
+}
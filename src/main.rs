@@ -6,11 +6,20 @@
 //!
 //! # Usage
 //! ```text
-//! cargo run --release
+//! cargo run --release                  # generate world.world
+//! cargo run --release -- info [path]   # print header and generation metadata
+//! cargo run --release -- migrate <src> [dst] [--dry-run]
+//!                                      # upgrade a world file to the current format
+//! cargo run --release -- validate [path]
+//!                                      # check every generator invariant
+//! cargo run --release -- distances <path> [--radius <r>] [--out <file>]
+//!                                      # compute or export city sea distances
 //! ```
 //!
 //! Configuration is read from environment variables and `.env` file (if present).
 //! If a world file with the same seed already exists, generation is skipped.
+//! With `CITY_DIAGNOSTICS=true`, `world.citydiag` and `world.citydiag.json`
+//! are written alongside (see [`world_generator::city_diagnostics`]).
 //!
 //! # Environment variables
 //! - `SEED`: Random seed (default: random)
@@ -18,23 +27,23 @@
 //! - `CHUNK_SIZE`: Chunk size for file storage, "auto" picks optimal (default: auto)
 //! - See [`WorldConfig`] for all available parameters
 //!
-//! # Generation pipeline
-//! 1. **Elevation**: Fractal Brownian motion (fBm) Perlin noise for heightmap
-//! 2. **Terrain**: Classify tiles as Water, Land, or FarLand (decorative)
-//! 3. **Region labels**: Flood-fill to discover islands and assign IDs
-//! 4. **Water bodies**: Label connected water regions
-//! 5. **Ocean distances**: Pre-compute distance-to-ocean for village placement
-//! 6. **City slots**: Find valid coastal positions for cities
-//! 7. **Biomes**: Classify terrain into 16 biome types
-//! 8. **Resources**: Compute production modifiers and gold vein locations
-//! 9. **Villages**: Place inland villages with trade specialization
-//! 10. **Save**: Write everything to chunked, compressed binary format
-
-use std::collections::HashMap;
+//! The pipeline itself lives in [`world_generator::generate`].
+
 use std::time::Instant;
 
+use world_generator::city::IslandSizeTarget;
+use world_generator::city_diagnostics::{CityDiagnostics, SlotOutcome};
 use world_generator::config::WorldConfig;
-use world_generator::{biome, city, elevation, save, terrain, village};
+use world_generator::generate::generate_world;
+use world_generator::island::Island;
+use world_generator::metadata;
+use world_generator::migrate;
+use world_generator::navigation::{self, NavOptions};
+use world_generator::save;
+use world_generator::save::{ChunkedWorldReader, WorldData};
+use world_generator::territory;
+use world_generator::validate;
+use world_generator::World;
 
 const OUTPUT_PATH: &str = "world.world";
 
@@ -42,6 +51,35 @@ fn main() {
     // Load .env file if present (silently ignored if missing)
     let _ = dotenvy::dotenv();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => generate(),
+        Some("info") => info(args.get(1).map_or(OUTPUT_PATH, String::as_str)),
+        Some("migrate") => migrate(&args[1..]),
+        Some("validate") => validate(args.get(1).map_or(OUTPUT_PATH, String::as_str)),
+        Some("distances") => distances(&args[1..]),
+        Some(other) => {
+            eprintln!("Unknown command: {other}");
+            print_usage();
+            std::process::exit(2);
+        }
+    }
+}
+
+fn print_usage() {
+    eprintln!("Usage: world_generator [COMMAND]");
+    eprintln!();
+    eprintln!("  (none)                          generate {OUTPUT_PATH}");
+    eprintln!("  info [path]                     print header and generation metadata");
+    eprintln!("  migrate <src> [dst] [--dry-run] upgrade a world file to the current format");
+    eprintln!("  validate [path]                 check every generator invariant");
+    eprintln!("  distances <path> [--radius <r>] [--out <file>]");
+    eprintln!("                                  compute (with --radius) and export (to .csv");
+    eprintln!("                                  or .json) sea travel times between cities");
+}
+
+/// Run the full generation pipeline and write [`OUTPUT_PATH`].
+fn generate() {
     let config = WorldConfig::from_env();
 
     // Skip generation if a world file with the same seed already exists
@@ -65,150 +103,402 @@ fn main() {
         config.map_size, config.map_size, config.seed, config.chunk_size
     );
 
-    // Phase 1: Generate heightmap using fractal Brownian motion
-    let elevation_grid = timed("Elevation", || elevation::generate(&config));
-
-    // Phase 2: Classify each tile's terrain type based on elevation and distance
-    let terrain_grid = timed("Terrain", || {
-        terrain::classify_terrain(
-            &elevation_grid,
-            config.map_len(),
-            config.water_threshold,
-            config.playable_radius,
-            config.farland_margin,
-        )
-    });
+    let world_data = generate_world(&config);
+    print_generation(&world_data);
 
-    // Phase 3: Label connected land tiles as numbered regions (islands)
-    let region_labels = timed("Regions", || {
-        terrain::label_regions(&terrain_grid, config.map_len())
-    });
+    // Write everything to disk in chunked, compressed format.
+    // Not recorded in the metadata -- it happens after it is captured.
+    let save_start = Instant::now();
+    if let Err(e) = save::save_world_chunked(OUTPUT_PATH, &world_data) {
+        eprintln!("Error: Failed to save world to {OUTPUT_PATH}: {e}");
+        std::process::exit(1);
+    }
+    println!("  Save: {:.2?}", save_start.elapsed());
 
-    // Phase 4: Label connected water tiles as numbered water bodies
-    let water_bodies = timed("Water bodies", || {
-        terrain::label_water_bodies(&terrain_grid, config.map_len())
-    });
+    if let Some(diagnostics) = &world_data.city_diagnostics {
+        save_city_diagnostics(diagnostics);
+    }
 
-    // Phase 5: Compute distance from each tile to nearest ocean/farland
-    // (Used by village placement to find genuinely inland positions)
-    let ocean_distances = timed("Ocean distances", || {
-        terrain::compute_ocean_distances(&terrain_grid, config.map_len())
-    });
+    println!("\nGeneration complete. World saved to {}", OUTPUT_PATH);
+}
+
+/// Print the phase timings and a summary of what [`generate_world`] made.
+fn print_generation(data: &WorldData) {
+    let config = &data.config;
+    let meta = &data.metadata;
+    for phase in &meta.phase_timings {
+        println!("  {}: {:.2?}", phase.name, phase.duration);
+    }
+    println!();
 
-    // Phase 6: Find valid coastal locations for city placement
-    let city_slots = timed("City slots", || {
-        city::find_city_slots(&terrain_grid, &water_bodies, &config)
-    });
-    println!("  Found {} candidate city slots", city_slots.len());
-
-    // Phase 7: Filter city slots to keep only those on large enough islands
-    let filtered_cities = timed("Filter islands", || {
-        city::filter_city_slots_by_region(
-            &city_slots,
-            &region_labels,
-            config.min_city_slots_per_island as usize,
-        )
-    });
     println!(
-        "  Kept {} city slots after filtering small islands",
-        filtered_cities.len()
+        "  {} city slots on {} islands ({} placement)",
+        meta.counts.cities,
+        meta.counts.islands,
+        config.city_placement.name()
     );
+    if config.balances_islands() {
+        print_island_sizes(&data.islands, &config.island_size_targets);
+    }
+    if let Some(diagnostics) = &data.city_diagnostics {
+        let summary: Vec<String> = SlotOutcome::ALL
+            .iter()
+            .zip(diagnostics.totals())
+            .filter(|&(o, n)| n > 0 && o.color().is_some())
+            .map(|(o, n)| format!("{} {n}", o.name()))
+            .collect();
+        println!("  Slot outcomes: {}", summary.join(", "));
+        println!(
+            "  {} islands dropped without cities",
+            diagnostics.dropped_islands.len()
+        );
+    }
 
-    // Phase 8: Classify terrain into 16 biome types using multiple noise layers
-    let biomes = timed("Biomes", || {
-        biome::generate_biomes(&config, &terrain_grid, &elevation_grid)
+    let sizes =
+        territory::territory_sizes(std::slice::from_ref(&data.territory), data.city_slots.len());
+    let owned: u64 = sizes.iter().map(|&s| u64::from(s)).sum();
+    println!(
+        "  {} land tiles owned, {} per city on average (largest {})",
+        owned,
+        owned / sizes.len().max(1) as u64,
+        sizes.iter().copied().max().unwrap_or(0)
+    );
+    let cities_with_gold = data
+        .city_resources
+        .iter()
+        .filter(|r| r.gold_nodes > 0)
+        .count();
+    println!(
+        "  {}/{} cities have gold deposits ({} total nodes)",
+        cities_with_gold, meta.counts.cities, meta.counts.gold_nodes
+    );
+    println!("  Placed {} villages", meta.counts.villages);
+    println!(
+        "  Named {} islands, {} seas and {} villages ({})",
+        data.islands.len(),
+        data.sectors.sectors().len(),
+        data.village_names.len(),
+        config.name_style.name()
+    );
+    if config.city_distance_radius > 0.0 {
+        println!(
+            "  {} sea links within {}",
+            data.city_distances.link_count(),
+            config.city_distance_radius
+        );
+    }
+    if let Some(spawn) = data.islands.iter().find(|i| i.is_world_spawn) {
+        println!(
+            "  World spawn: island {} ({} cities)",
+            spawn.id, spawn.city_count
+        );
+    }
+}
+
+/// Print how many islands ended up in each target size bucket.
+#[allow(clippy::cast_precision_loss)]
+fn print_island_sizes(islands: &[Island], targets: &[IslandSizeTarget]) {
+    let counts: Vec<u32> = islands.iter().map(|i| i.city_count).collect();
+    let largest = counts.iter().copied().max().unwrap_or(0);
+    if targets.is_empty() {
+        println!("  {} islands, largest {} cities", counts.len(), largest);
+        return;
+    }
+
+    let total = counts.len().max(1) as f64;
+    let total_weight: f64 = targets.iter().map(|t| t.weight).sum();
+    let mut buckets: Vec<String> = targets
+        .iter()
+        .map(|t| {
+            let n = counts.iter().filter(|&&c| t.contains(c)).count();
+            format!(
+                "{} {} ({:.0}%, target {:.0}%)",
+                t.label(),
+                n,
+                n as f64 / total * 100.0,
+                t.weight / total_weight * 100.0
+            )
+        })
+        .collect();
+    let other = counts
+        .iter()
+        .filter(|&&c| !targets.iter().any(|t| t.contains(c)))
+        .count();
+    if other > 0 {
+        buckets.push(format!("other {other}"));
+    }
+    println!("  Island sizes: {}", buckets.join(", "));
+}
+
+/// Write the `CITY_DIAGNOSTICS` sidecar next to [`OUTPUT_PATH`], plus its
+/// JSON report. Failures are reported but do not fail the generation.
+fn save_city_diagnostics(diagnostics: &CityDiagnostics) {
+    let path = save::city_diagnostics_path(OUTPUT_PATH);
+    if let Err(e) = save::save_city_diagnostics(&path, diagnostics) {
+        eprintln!("Warning: Failed to save city diagnostics to {path}: {e}");
+    }
+    let report_path = format!("{path}.json");
+    match std::fs::write(&report_path, diagnostics.report_json()) {
+        Ok(()) => println!("  City diagnostics report: {report_path}"),
+        Err(e) => eprintln!("Warning: Failed to write {report_path}: {e}"),
+    }
+}
+
+/// Print the header and generation metadata of an existing world file.
+fn info(path: &str) {
+    let reader = ChunkedWorldReader::open(path).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to open {path}: {e}");
+        std::process::exit(1);
     });
+    let header = &reader.header;
+    let config = &header.config;
 
-    // Phase 9: Count cities per region (needed for Favor scaling and village placement)
-    let region_city_counts: HashMap<usize, u32> = {
-        let mut counts = HashMap::new();
-        for &(x, y) in &filtered_cities {
-            let region_id = region_labels[y][x];
-            if region_id > 0 {
-                *counts.entry(region_id).or_insert(0u32) += 1;
-            }
-        }
-        counts
+    println!("{path}");
+    println!("  Format version: {}", header.format_version);
+    println!("  Size:           {}x{}", header.width, header.height);
+    println!(
+        "  Chunks:         {}x{} ({} tiles each)",
+        header.chunks_x, header.chunks_y, config.chunk_size
+    );
+    println!("  Seed:           {}", config.seed);
+    println!("  Cities:         {}", header.city_slots.len());
+    println!("  Villages:       {}", header.villages.len());
+    if let Some(d) = header.city_distances.as_ref().filter(|d| d.radius > 0.0) {
+        println!("  Sea links:      {} within {}", d.link_count(), d.radius);
+    }
+    if let Some(g) = header.sectors.as_ref().filter(|g| !g.is_empty()) {
+        println!(
+            "  Sectors:        {}x{} ({} tiles each)",
+            g.cols, g.rows, g.size
+        );
+    }
+    if let Some(villages) = &header.village_names {
+        let kinds = if villages.is_empty() {
+            "islands and seas"
+        } else {
+            "islands, seas and villages"
+        };
+        println!("  Names:          {} ({kinds})", config.name_style.name());
+    }
+    if !header.lod_levels.is_empty() {
+        let levels: Vec<String> = header
+            .lod_levels
+            .iter()
+            .map(|l| format!("1/{} ({}x{})", l.factor, l.width, l.height))
+            .collect();
+        println!("  LOD levels:     {}", levels.join(", "));
+    }
+    let chunk_count = header.chunks_x * header.chunks_y;
+    let land_chunks = (0..header.chunks_y)
+        .flat_map(|cy| (0..header.chunks_x).map(move |cx| (cx, cy)))
+        .filter_map(|(cx, cy)| reader.chunk_summary(cx, cy))
+        .filter(|s| s.has_land())
+        .count();
+    if header.format_version >= 4 {
+        println!("  Land chunks:    {land_chunks} of {chunk_count}");
+    }
+
+    let Some(meta) = &header.metadata else {
+        println!("\nNo generation metadata (format version 1 file).");
+        return;
     };
 
-    // Phase 10: Compute per-city resource profiles and gold vein locations
-    let city_resources = timed("City resources", || {
-        biome::compute_city_resources(
-            &filtered_cities,
-            &biomes,
-            &region_labels,
-            &region_city_counts,
-            config.min_city_slots_per_island as u32,
-            config.seed,
-        )
-    });
-    {
-        let total_gold_nodes: u32 = city_resources.iter().map(|r| r.gold_nodes as u32).sum();
-        let cities_with_gold = city_resources.iter().filter(|r| r.gold_nodes > 0).count();
+    println!("\nGeneration");
+    println!(
+        "  Generator:      {}",
+        Some(meta.generator_version.as_str())
+            .filter(|v| !v.is_empty())
+            .unwrap_or("unknown")
+    );
+    println!(
+        "  Git revision:   {}",
+        meta.git_revision.as_deref().unwrap_or("unknown")
+    );
+    if meta.generated_at > 0 {
         println!(
-            "  {}/{} cities have gold deposits ({} total nodes)",
-            cities_with_gold,
-            filtered_cities.len(),
-            total_gold_nodes
+            "  Generated at:   {}",
+            metadata::format_unix_utc(meta.generated_at)
         );
     }
+    println!("  Threads:        {}", meta.threads);
+    println!(
+        "  Islands: {}, cities: {}, villages: {}, gold nodes: {}",
+        meta.counts.islands, meta.counts.cities, meta.counts.villages, meta.counts.gold_nodes
+    );
 
-    // Phase 11: Place villages on each island with trade specialization
-    let villages = timed("Villages", || {
-        village::place_villages(
-            &terrain_grid,
-            &biomes,
-            &region_labels,
-            &ocean_distances,
-            &region_city_counts,
-            &filtered_cities,
-            &config,
-        )
-    });
-    {
-        // Summary of trade specializations
-        let mut trade_counts = [0u32; 5];
-        for village_instance in &villages {
-            trade_counts[village_instance.trade.offers.to_u8() as usize] += 1;
+    if !meta.phase_timings.is_empty() {
+        println!("\nPhase timings");
+        for phase in &meta.phase_timings {
+            println!("  {:<18} {:>10.2?}", phase.name, phase.duration);
         }
-        println!("  Placed {} villages", villages.len());
-    }
-
-    // Phase 12: Package everything for binary file storage
-    let world_data = timed("Build world data", || {
-        save::build_world_data(
-            elevation_grid,
-            terrain_grid,
-            region_labels,
-            &filtered_cities,
-            biomes,
-            city_resources,
-            villages,
-            config.clone(),
-        )
-    });
+        println!("  {:<18} {:>10.2?}", "Total", meta.total_duration());
+    }
+}
 
-    // Phase 13: Write everything to disk in chunked, compressed format
-    timed("Save", || {
-        match save::save_world_chunked(OUTPUT_PATH, &world_data) {
-            Ok(()) => {}
-            Err(e) => {
-                eprintln!("Error: Failed to save world to {}: {}", OUTPUT_PATH, e);
-                std::process::exit(1);
-            }
-        }
+/// Upgrade a world file to the current format, in place unless `dst` is given.
+///
+/// With `--dry-run`, only prints what would be done.
+fn migrate(args: &[String]) {
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let paths: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--dry-run")
+        .collect();
+    let Some(&src) = paths.first() else {
+        print_usage();
+        std::process::exit(2);
+    };
+    let dst = paths.get(1).copied().unwrap_or(src);
+
+    let result = if dry_run {
+        migrate::plan_migration(src)
+    } else {
+        migrate::migrate_world(src, dst)
+    };
+    let summary = result.unwrap_or_else(|e| {
+        eprintln!("Error: Failed to migrate {src}: {e}");
+        std::process::exit(1);
     });
 
-    println!("\nGeneration complete. World saved to {}", OUTPUT_PATH);
+    println!(
+        "{src}: format version {} -> {}",
+        summary.source_version, summary.target_version
+    );
+    println!(
+        "  {}x{} tiles, {} chunks, {} cities, {} villages (kept as-is)",
+        summary.width, summary.height, summary.chunks, summary.cities, summary.villages
+    );
+    if summary.is_current() {
+        println!("  Already current; the file is rewritten without changes.");
+    }
+    for section in &summary.derived {
+        println!("  + {section}");
+    }
+    if dry_run {
+        println!("Dry run: nothing written.");
+    } else {
+        println!("Wrote {dst}");
+    }
 }
 
-/// Run a closure, measure its execution time, print elapsed time, and return result.
+/// Check every generator invariant of a world file and list the violations.
 ///
-/// Used to track performance of each generation phase.
-fn timed<T>(phase_name: &str, f: impl FnOnce() -> T) -> T {
+/// Exits with status 1 if any rule is broken.
+fn validate(path: &str) {
+    let world = World::open(path).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to open {path}: {e}");
+        std::process::exit(1);
+    });
     let start = Instant::now();
-    let result = f();
-    let elapsed = start.elapsed();
-    println!("  {}: {:.2?}", phase_name, elapsed);
-    result
+    let report = validate::validate_world(&world);
+
+    println!(
+        "{path}: checked {} cities, {} villages, {} chunks in {:.2?}",
+        report.cities_checked,
+        report.villages_checked,
+        report.chunks_checked,
+        start.elapsed()
+    );
+    if report.is_ok() {
+        println!("  No violations.");
+        return;
+    }
+    for violation in &report.violations {
+        println!("  {violation}");
+    }
+    println!("{} violations.", report.violations.len());
+    std::process::exit(1);
+}
+
+/// Compute, store and export the sea neighbours of every city.
+///
+/// `--radius` recomputes the lists and rewrites the file; `--out` writes
+/// them as JSON if the name ends in `.json`, as CSV otherwise. Without
+/// either, prints what the file stores.
+fn distances(args: &[String]) {
+    let mut path = None;
+    let mut radius = None;
+    let mut out = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--radius" => {
+                let value = iter.next().and_then(|v| v.parse::<f32>().ok());
+                if let Some(r) = value.filter(|r| r.is_finite()) {
+                    radius = Some(r)
+                } else {
+                    eprintln!("--radius needs a number");
+                    std::process::exit(2);
+                }
+            }
+            "--out" => {
+                if let Some(file) = iter.next().filter(|v| !v.starts_with("--")) {
+                    out = Some(file)
+                } else {
+                    eprintln!("--out needs a file name");
+                    std::process::exit(2);
+                }
+            }
+            flag if flag.starts_with("--") => {
+                eprintln!("Unknown option {flag}");
+                print_usage();
+                std::process::exit(2);
+            }
+            _ if path.is_some() => {
+                eprintln!("Unexpected argument {arg}");
+                print_usage();
+                std::process::exit(2);
+            }
+            _ => path = Some(arg.as_str()),
+        }
+    }
+    let Some(path) = path else {
+        print_usage();
+        std::process::exit(2);
+    };
+
+    if let Some(radius) = radius {
+        let start = Instant::now();
+        let stored = navigation::store_city_distances(path, radius, &NavOptions::default())
+            .unwrap_or_else(|e| {
+                eprintln!("Error: Failed to compute sea distances for {path}: {e}");
+                std::process::exit(1);
+            });
+        println!(
+            "{path}: {} sea links between {} cities within {radius} in {:.2?}",
+            stored.link_count(),
+            stored.city_count(),
+            start.elapsed()
+        );
+    }
+
+    let world = World::open(path).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to open {path}: {e}");
+        std::process::exit(1);
+    });
+    let Some(distances) = world.city_distances() else {
+        eprintln!("{path} stores no sea distances; compute them with --radius <r>");
+        std::process::exit(1);
+    };
+    let Some(out) = out else {
+        println!(
+            "{path}: {} sea links between {} cities within {} ({:.1} per city)",
+            distances.link_count(),
+            distances.city_count(),
+            distances.radius,
+            distances.link_count() as f64 / distances.city_count().max(1) as f64
+        );
+        return;
+    };
+    let contents = if out.ends_with(".json") {
+        distances.to_json()
+    } else {
+        distances.to_csv()
+    };
+    if let Err(e) = std::fs::write(out, contents) {
+        eprintln!("Error: Failed to write {out}: {e}");
+        std::process::exit(1);
+    }
+    println!("Wrote {} sea links to {out}", distances.link_count());
 }
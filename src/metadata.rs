@@ -0,0 +1,125 @@
+//! Generation metadata -- how, when and by what a world file was produced.
+//!
+//! [`GenerationMetadata`] is captured by the generator once the pipeline has
+//! finished and stored in the world file header (format version 2+). It is
+//! purely informational: nothing in the reader or the game depends on it,
+//! but it answers "which build made this file, and how long did it take?"
+//! without regenerating the world.
+
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::save::WorldData;
+
+/// Wall-clock duration of one named generation phase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseTiming {
+    /// Human-readable phase name, e.g. `"Elevation"`.
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// Summary counts of the generated content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorldCounts {
+    /// Islands that received at least one city slot.
+    pub islands: u32,
+    pub cities: u32,
+    pub villages: u32,
+    /// Sum of [`CityResources::gold_nodes`](crate::biome::CityResources::gold_nodes)
+    /// over all cities.
+    pub gold_nodes: u32,
+}
+
+impl WorldCounts {
+    /// Count the islands, cities, villages and gold nodes in `data`.
+    #[must_use]
+    pub fn of(data: &WorldData) -> Self {
+        let width = data.width as usize;
+        let islands: HashSet<u32> = data
+            .city_slots
+            .iter()
+            .map(|&(x, y)| data.region_labels[y as usize * width + x as usize])
+            .filter(|&rid| rid != 0)
+            .collect();
+
+        Self {
+            islands: saturating_u32(islands.len()),
+            cities: saturating_u32(data.city_slots.len()),
+            villages: saturating_u32(data.villages.len()),
+            gold_nodes: data
+                .city_resources
+                .iter()
+                .map(|r| u32::from(r.gold_nodes))
+                .sum(),
+        }
+    }
+}
+
+/// Provenance of a generated world.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenerationMetadata {
+    /// Crate version of the generator (`CARGO_PKG_VERSION`).
+    pub generator_version: String,
+    /// Git revision the generator was built from, if known at build time.
+    pub git_revision: Option<String>,
+    /// Generation time as seconds since the Unix epoch (0 = unknown).
+    pub generated_at: u64,
+    /// Size of the rayon thread pool used for generation.
+    pub threads: u32,
+    /// Per-phase durations, in pipeline order.
+    pub phase_timings: Vec<PhaseTiming>,
+    pub counts: WorldCounts,
+}
+
+impl GenerationMetadata {
+    /// Describe `data` as produced right now by this build of the generator.
+    pub fn capture(data: &WorldData, phase_timings: Vec<PhaseTiming>) -> Self {
+        Self {
+            generator_version: env!("CARGO_PKG_VERSION").to_owned(),
+            git_revision: option_env!("WORLDGEN_GIT_REVISION").map(str::to_owned),
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            threads: saturating_u32(rayon::current_num_threads()),
+            phase_timings,
+            counts: WorldCounts::of(data),
+        }
+    }
+
+    /// Sum of all recorded phase durations.
+    #[must_use]
+    pub fn total_duration(&self) -> Duration {
+        self.phase_timings.iter().map(|p| p.duration).sum()
+    }
+}
+
+fn saturating_u32(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+/// Format Unix seconds as `YYYY-MM-DD HH:MM:SS UTC`.
+#[must_use]
+pub fn format_unix_utc(secs: u64) -> String {
+    // u64::MAX / 86_400 is well below i64::MAX, so this never saturates.
+    let days = i64::try_from(secs / 86_400).unwrap_or(i64::MAX);
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant), valid for the whole u64 range we care about.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
@@ -0,0 +1,242 @@
+//! Format migration -- upgrade existing world files to the current layout.
+//!
+//! Live worlds cannot be regenerated when the file format changes: even with
+//! the same seed, a newer generator may place things differently and the map
+//! would shift under players. Migration instead reads a file in any
+//! supported [`FORMAT_VERSION`], keeps every tile, city and village exactly
+//! as stored, derives whatever the newer layout adds from that data, and
+//! writes the result in the current format.
+//!
+//! # Derived data per source version
+//!
+//! | Source | Filled in |
+//! |--------|-----------|
+//! | 1 | Generation metadata (summary counts only; provenance is unknown) |
+//! | 1-2 | Low-resolution LOD pyramid (always recomputed from the tiles on save) |
+//! | 1-3 | Per-chunk summary statistics (computed from each chunk on save) |
+//! | 1-4 | Island registry (id, cities, centroid, bounds, area, perimeter, spawn order) |
+//! | 1-5 | Region id of every city slot |
+//! | 1-6 | City site descriptions (facing, harbor quality, coast type, hinterland) |
+//! | 1-7 | Territory of every city (owning city of each Land tile) |
+//! | 1-9 | Ocean sector grid, with the default sector size |
+//! | 1-10 | Island, sea and village names, in the default style |
+//! | 1-11 | Village placement rules (inland distance, spacing), as the defaults |
+//! | 1-12 | City placement mode, as the default |
+//!
+//! Sea travel times between cities are optional and slow to compute, so
+//! files older than version 9 get an empty section; fill it in afterwards
+//! with [`store_city_distances`](crate::navigation::store_city_distances).
+
+use std::io;
+
+use crate::city::{self, CitySite};
+use crate::island;
+use crate::metadata::{GenerationMetadata, WorldCounts};
+use crate::names;
+use crate::save::{self, ChunkedWorldHeader, ChunkedWorldReader, WorldData, FORMAT_VERSION};
+use crate::sector;
+use crate::terrain::{self, Terrain};
+use crate::territory;
+
+/// What a migration did, or would do in a dry run.
+#[derive(Debug, Clone)]
+pub struct MigrationSummary {
+    /// Format version of the source file.
+    pub source_version: u8,
+    /// Format version that is (or would be) written.
+    pub target_version: u8,
+    pub width: u32,
+    pub height: u32,
+    pub chunks: u32,
+    pub cities: usize,
+    pub villages: usize,
+    /// Human-readable description of each derived section.
+    pub derived: Vec<&'static str>,
+}
+
+impl MigrationSummary {
+    /// `true` if the source already uses the current format.
+    #[must_use]
+    pub const fn is_current(&self) -> bool {
+        self.source_version == self.target_version
+    }
+}
+
+/// Inspect `path` and describe the migration without writing anything.
+///
+/// # Errors
+///
+/// Any error opening or reading the header of `path`.
+pub fn plan_migration(path: &str) -> io::Result<MigrationSummary> {
+    let reader = ChunkedWorldReader::open(path)?;
+    Ok(summarize(&reader.header))
+}
+
+/// Migrate the world at `src` to the current format and write it to `dst`.
+///
+/// `src` and `dst` may be the same path (see
+/// [`save_world_replacing`](save::save_world_replacing)).
+///
+/// # Errors
+///
+/// Any error reading `src` or writing `dst`; `dst` is left untouched on
+/// failure.
+pub fn migrate_world(src: &str, dst: &str) -> io::Result<MigrationSummary> {
+    let reader = ChunkedWorldReader::open(src)?;
+    let summary = summarize(&reader.header);
+
+    let mut data = reader.load_world_data()?;
+    fill_derived(&mut data, &reader.header);
+
+    save::save_world_replacing(dst, &data)?;
+
+    Ok(summary)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+fn summarize(header: &ChunkedWorldHeader) -> MigrationSummary {
+    let version = header.format_version;
+    let mut derived = Vec::new();
+    if version < 2 {
+        derived.push("generation metadata (summary counts)");
+    }
+    if version < 3 {
+        derived.push("low-resolution LOD pyramid");
+    }
+    if version < 4 {
+        derived.push("per-chunk summary statistics");
+    }
+    if version < 5 {
+        derived.push("island registry");
+    }
+    if version < 6 {
+        derived.push("city region ids");
+    }
+    if version < 7 {
+        derived.push("city site descriptions");
+    }
+    if version < 8 {
+        derived.push("city territories");
+    }
+    if version < 10 {
+        derived.push("ocean sector grid");
+    }
+    if version < 11 {
+        derived.push("island, sea and village names");
+    }
+    if version < 12 {
+        derived.push("village placement rules (defaults)");
+    }
+    if version < 13 {
+        derived.push("city placement mode (default)");
+    }
+
+    MigrationSummary {
+        source_version: version,
+        target_version: FORMAT_VERSION,
+        width: header.width,
+        height: header.height,
+        chunks: header.chunks_x * header.chunks_y,
+        cities: header.city_slots.len(),
+        villages: header.villages.len(),
+        derived,
+    }
+}
+
+/// Fill every section the source version did not store.
+///
+/// Must stay in sync with the `derived` list built by [`summarize`].
+fn fill_derived(data: &mut WorldData, header: &ChunkedWorldHeader) {
+    if header.format_version < 2 {
+        // Provenance is unknown; only the counts can be recovered.
+        data.metadata = GenerationMetadata {
+            counts: WorldCounts::of(data),
+            ..GenerationMetadata::default()
+        };
+    }
+    if header.format_version < 6 {
+        data.city_regions = data.city_region_labels();
+    }
+    if header.format_version < 5 {
+        data.islands = island::compute_islands(data);
+    }
+    if header.format_version < 7 {
+        data.city_sites = derive_city_sites(data);
+    }
+    if header.format_version < 8 {
+        // City resources stay as stored (aggregated over circles).
+        data.territory = derive_territory(data);
+    }
+    if header.format_version < 10 {
+        // Needs the island registry, filled in above for versions 1-4.
+        data.sectors = sector::compute_sectors(data);
+    }
+    if header.format_version < 11 {
+        // Needs the island registry and the sectors, filled in above.
+        names::assign_names(data);
+    }
+    // Versions 1-11: the village rules, and versions 1-12: the city
+    // placement mode, keep the defaults the header reader filled in.
+}
+
+/// Partition every island's land between its cities from the stored region
+/// labels.
+fn derive_territory(data: &WorldData) -> Vec<u32> {
+    let width = data.width as usize;
+    let labels: Vec<Vec<usize>> = data
+        .region_labels
+        .chunks(width.max(1))
+        .map(|row| row.iter().map(|&r| r as usize).collect())
+        .collect();
+    let slots: Vec<(usize, usize)> = data
+        .city_slots
+        .iter()
+        .map(|&(x, y)| (x as usize, y as usize))
+        .collect();
+
+    territory::compute_territory(&labels, &slots).concat()
+}
+
+/// Describe every city slot from the stored tiles.
+///
+/// Rebuilds the row-based grids and water bodies the generator works on,
+/// so it briefly needs several times the memory of the flat grids.
+fn derive_city_sites(data: &WorldData) -> Vec<CitySite> {
+    let (width, height) = (data.width as usize, data.height as usize);
+    let rows = |i: usize| i * width..(i + 1) * width;
+    let terrain: Vec<Vec<Terrain>> = (0..height)
+        .map(|y| {
+            data.terrain[rows(y)]
+                .iter()
+                .map(|&t| Terrain::from_u8(t))
+                .collect()
+        })
+        .collect();
+    let elevation: Vec<Vec<f64>> = (0..height)
+        .map(|y| {
+            data.elevation[rows(y)]
+                .iter()
+                .map(|&e| f64::from(e))
+                .collect()
+        })
+        .collect();
+    let biomes: Vec<Vec<u8>> = (0..height).map(|y| data.biomes[rows(y)].to_vec()).collect();
+    let water = terrain::label_water_bodies(&terrain, width);
+    let slots: Vec<(usize, usize)> = data
+        .city_slots
+        .iter()
+        .map(|&(x, y)| (x as usize, y as usize))
+        .collect();
+
+    city::compute_city_sites(
+        &slots,
+        &terrain,
+        &elevation,
+        &biomes,
+        &water,
+        data.config.water_threshold,
+    )
+}
@@ -0,0 +1,309 @@
+//! Procedural place names for islands, seas and villages.
+//!
+//! Names come from a character Markov chain (order 2) trained on real
+//! ancient place names of one [`NameStyle`], so a Greek world gets names
+//! like *Kerinthos* and a Latin one names like *Aquenum*. Sea names wrap a
+//! generated name in the style's word for sea (*Pelagos*, *Mare*, ...).
+//!
+//! [`NameGenerator`] is seeded from the world seed, so the same world always
+//! gets the same names, and never hands out a name twice: when the chain
+//! keeps repeating itself, a Roman numeral is appended (*Naxa II*).
+//! [`assign_names`] names every island (by id), every ocean
+//! [`sector`](crate::sector) (row-major) and, with `VILLAGE_NAMES`, every
+//! village, in that order.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use crate::save::WorldData;
+
+/// Language the generated names imitate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameStyle {
+    #[default]
+    Greek,
+    Latin,
+    Egyptian,
+    Phoenician,
+}
+
+impl NameStyle {
+    /// Parse the `NAME_STYLE` value (`greek`, `latin`, `egyptian` or
+    /// `phoenician`).
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "greek" => Some(Self::Greek),
+            "latin" => Some(Self::Latin),
+            "egyptian" => Some(Self::Egyptian),
+            "phoenician" => Some(Self::Phoenician),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Greek => "greek",
+            Self::Latin => "latin",
+            Self::Egyptian => "egyptian",
+            Self::Phoenician => "phoenician",
+        }
+    }
+
+    #[must_use]
+    pub const fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Unknown values fall back to the default style.
+    #[must_use]
+    pub const fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Latin,
+            2 => Self::Egyptian,
+            3 => Self::Phoenician,
+            _ => Self::Greek,
+        }
+    }
+
+    /// Real place names the chain is trained on (lowercase ASCII).
+    fn corpus(self) -> impl Iterator<Item = &'static str> {
+        match self {
+            Self::Greek => GREEK,
+            Self::Latin => LATIN,
+            Self::Egyptian => EGYPTIAN,
+            Self::Phoenician => PHOENICIAN,
+        }
+        .split_whitespace()
+    }
+
+    /// Sea name built around a generated `name`.
+    fn sea_name(self, name: &str) -> String {
+        match self {
+            Self::Greek => format!("{name} Pelagos"),
+            Self::Latin => format!("Mare {name}"),
+            Self::Egyptian => format!("{name} Wadjwer"),
+            Self::Phoenician => format!("Yam {name}"),
+        }
+    }
+}
+
+// Corpora, one name per word.
+const GREEK: &str = "\
+    athenai sparta korinthos thebai argos delphoi olympia mykenai knossos \
+    rhodos naxos paros delos samos chios lesbos lemnos ithaka kephallenia \
+    zakynthos kythera melos thera syros tenos andros euboia aigina salamis \
+    megara eretria chalkis miletos ephesos smyrna phokaia knidos \
+    halikarnassos patmos amorgos sikinos pholegandros seriphos siphnos \
+    kythnos keos skyros skiathos thasos samothrake imbros tenedos leukas \
+    kerkyra pylos tiryns epidauros nemea sikyon troizen kalymnos \
+    astypalaia anaphe ikaria";
+const LATIN: &str = "\
+    roma ostia capua neapolis pompeii herculaneum tarentum brundisium \
+    ravenna aquileia mediolanum verona genua florentia pisae arretium \
+    perusia ancona ariminum placentia cremona mutina bononia sardinia \
+    corsica sicilia ilva capreae aenaria melita gaulos lipara caralis \
+    panormus syracusae agrigentum messana rhegium croto sybaris tusculum \
+    praeneste tibur antium tarracina formiae puteoli misenum sora venusia \
+    beneventum luceria aesernia narnia spoletium tuder clusium volsinii \
+    cosa luna vercellae comum";
+const EGYPTIAN: &str = "\
+    waset mennefer abdju iunu sauty nekhen nubt khmun djedu perbast zau \
+    tjeni behdet shedet khentmin iunet swenet abu hutwaret perwadjet \
+    djanet khasut taremu gebtu nekheb iunyt mednit hebenu ipu tepihu saka \
+    hutsekhem wenu kebet pemdje sepermeru hardai tabennu medamu hutnesut \
+    perhathor tawer neni djerty hefat sumenu imet zawty menat khufu \
+    akhetaten sekhem";
+const PHOENICIAN: &str = "\
+    tsor tsidon gubla arwad berut qarthadasht utica gadir motya kition \
+    lapethos tharros sulcis nora bitia karali malaka sexi abdera lixus \
+    hadrumetum leptis oea sabratha ibosim tingis rusaddir rusucuru hippo \
+    tipasa iol kerkouane byrsa amrit sarepta akko yapu dor ushu sumur \
+    batrun tabarka thapsus zilis mogador rashqart solunto panormos eryx \
+    lilybaion kossura gaulos melite";
+
+/// Shortest and longest generated name, in letters.
+const MIN_LEN: usize = 4;
+const MAX_LEN: usize = 10;
+
+/// Draws from the chain before falling back to a numbered name.
+const ATTEMPTS: u32 = 64;
+
+/// Chance that a letter follows from the previous letter alone rather than
+/// the previous two, which widens the chain well past the corpus.
+const BACKOFF: f64 = 0.2;
+
+/// Marks the start and end of a name in the chain.
+const EDGE: u8 = b'^';
+
+/// Seeded source of unique names in one [`NameStyle`].
+pub struct NameGenerator {
+    style: NameStyle,
+    /// Letters seen after each pair of letters in the corpus ([`EDGE`]
+    /// before the first and after the last).
+    chain: HashMap<[u8; 2], Vec<u8>>,
+    /// Letters seen after each single letter.
+    backoff: HashMap<u8, Vec<u8>>,
+    /// Corpus names are never handed out as they are.
+    corpus: HashSet<&'static str>,
+    /// Every name handed out so far.
+    used: HashSet<String>,
+    /// Last number given to each base name that had to be numbered.
+    numbered: HashMap<String, u32>,
+    rng: StdRng,
+}
+
+impl NameGenerator {
+    #[must_use]
+    pub fn new(style: NameStyle, seed: u32) -> Self {
+        let mut chain: HashMap<[u8; 2], Vec<u8>> = HashMap::new();
+        let mut backoff: HashMap<u8, Vec<u8>> = HashMap::new();
+        for name in style.corpus() {
+            let padded: Vec<u8> = [EDGE, EDGE]
+                .into_iter()
+                .chain(name.bytes())
+                .chain([EDGE])
+                .collect();
+            for w in padded.windows(3) {
+                chain.entry([w[0], w[1]]).or_default().push(w[2]);
+                backoff.entry(w[1]).or_default().push(w[2]);
+            }
+        }
+        Self {
+            style,
+            chain,
+            backoff,
+            corpus: style.corpus().collect(),
+            used: HashSet::new(),
+            numbered: HashMap::new(),
+            rng: StdRng::seed_from_u64(u64::from(seed) ^ 0x4E41_4D45),
+        }
+    }
+
+    #[must_use]
+    pub const fn style(&self) -> NameStyle {
+        self.style
+    }
+
+    /// A name not handed out before, e.g. `Kerinthos`.
+    pub fn next_name(&mut self) -> String {
+        let mut last = None;
+        for _ in 0..ATTEMPTS {
+            let Some(name) = self.draw() else {
+                continue;
+            };
+            if !self.used.contains(&name) {
+                self.used.insert(name.clone());
+                return name;
+            }
+            last = Some(name);
+        }
+        // The chain keeps repeating itself: number the last draw instead.
+        let base = last.unwrap_or_else(|| capitalize(self.style.corpus().next().unwrap_or("")));
+        let n = self.numbered.entry(base.clone()).or_insert(1);
+        let name = loop {
+            *n += 1;
+            let name = format!("{base} {}", roman(*n));
+            if !self.used.contains(&name) {
+                break name;
+            }
+        };
+        self.used.insert(name.clone());
+        name
+    }
+
+    /// A sea name not handed out before, e.g. `Kerinthos Pelagos`.
+    pub fn next_sea_name(&mut self) -> String {
+        let name = self.next_name();
+        self.style.sea_name(&name)
+    }
+
+    /// One walk of the chain, or `None` if it is too short, too long or a
+    /// corpus name.
+    fn draw(&mut self) -> Option<String> {
+        let mut letters = vec![EDGE, EDGE];
+        loop {
+            let key = [letters[letters.len() - 2], letters[letters.len() - 1]];
+            // The first two letters always come from the pair chain.
+            let next = if key[0] != EDGE && self.rng.random_bool(BACKOFF) {
+                self.backoff.get(&key[1])?
+            } else {
+                self.chain.get(&key)?
+            };
+            let c = next[self.rng.random_range(0..next.len())];
+            if c == EDGE {
+                break;
+            }
+            letters.push(c);
+            if letters.len() - 2 > MAX_LEN {
+                return None;
+            }
+        }
+        let name = std::str::from_utf8(&letters[2..]).ok()?;
+        if name.len() < MIN_LEN || self.corpus.contains(name) {
+            return None;
+        }
+        Some(capitalize(name))
+    }
+}
+
+/// Name every island, sea and (with `config.village_names`) village of
+/// `data` with `config.name_style`.
+///
+/// Needs the island registry and the sector grid.
+pub fn assign_names(data: &mut WorldData) {
+    let mut names = NameGenerator::new(data.config.name_style, data.config.seed);
+    for island in &mut data.islands {
+        island.name = names.next_name();
+    }
+    for sector in data.sectors.sectors_mut() {
+        sector.name = names.next_sea_name();
+    }
+    data.village_names = if data.config.village_names {
+        data.villages.iter().map(|_| names.next_name()).collect()
+    } else {
+        Vec::new()
+    };
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// `n` in Roman numerals (`n >= 1`), or in digits from 4000 on, where
+/// they would need a run of `M`s.
+fn roman(mut n: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    if n >= 4000 {
+        return n.to_string();
+    }
+    let mut s = String::new();
+    for &(value, numeral) in &NUMERALS {
+        while n >= value {
+            s.push_str(numeral);
+            n -= value;
+        }
+    }
+    s
+}
@@ -0,0 +1,332 @@
+//! Sea travel between nearby cities, precomputed for the whole map.
+//!
+//! For every city slot a Dijkstra search floods the sea around it until the
+//! travel time exceeds a radius, noting every other city it reaches. The
+//! result is a sparse neighbour list rather than a full matrix: a 10,000-tile
+//! map has far too many cities for every pair to be worth storing, and a
+//! game server only needs travel times between cities close enough to
+//! attack or trade with each other.
+//!
+//! Steps follow the same rules as [`World::naval_path`](crate::World::naval_path),
+//! but the search is exact: travel times here are never longer than the
+//! naval search's, and usually equal. They are not quite symmetric, as a
+//! step is charged the cost of the water tile it enters.
+
+use std::collections::HashMap;
+use std::io;
+
+use rayon::prelude::*;
+
+use super::grid::{tile_class, BLOCKED, COAST};
+use super::{NavOptions, STEPS};
+use crate::save::{self, ChunkedWorldReader, WorldData, FORMAT_VERSION};
+
+/// One city reachable by sea from another within the radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeaLink {
+    /// Index into `city_slots` of the city reached.
+    pub city: u32,
+    /// Length of the route in tiles (diagonal steps count √2).
+    pub distance: f32,
+    /// Travel time of the route (see [`NavOptions`]).
+    pub travel_time: f32,
+}
+
+/// Sea neighbours of every city slot, as one list per city sorted by
+/// travel time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CityDistances {
+    /// Largest travel time searched. `0.0` if nothing was computed.
+    pub radius: f32,
+    /// `links[offsets[i]..offsets[i + 1]]` are the links of city `i`.
+    offsets: Vec<u32>,
+    links: Vec<SeaLink>,
+}
+
+impl CityDistances {
+    /// Pack one list of links per city.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(radius: f32, lists: Vec<Vec<SeaLink>>) -> Self {
+        let mut offsets = Vec::with_capacity(lists.len() + 1);
+        offsets.push(0);
+        let mut links = Vec::with_capacity(lists.iter().map(Vec::len).sum());
+        for list in lists {
+            links.extend(list);
+            offsets.push(links.len() as u32);
+        }
+        Self {
+            radius,
+            offsets,
+            links,
+        }
+    }
+
+    /// Number of cities with a list (zero if nothing was computed).
+    #[must_use]
+    pub const fn city_count(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    /// Total number of links over all cities.
+    #[must_use]
+    pub const fn link_count(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Cities reachable by sea from `city` within the radius, nearest
+    /// first. Empty if `city` is out of range.
+    #[must_use]
+    pub fn neighbors(&self, city: usize) -> &[SeaLink] {
+        match (self.offsets.get(city), self.offsets.get(city + 1)) {
+            (Some(&start), Some(&end)) => &self.links[start as usize..end as usize],
+            _ => &[],
+        }
+    }
+
+    /// Every link as `(from, link)`, by city then travel time.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &SeaLink)> + '_ {
+        (0..self.city_count()).flat_map(move |i| self.neighbors(i).iter().map(move |l| (i, l)))
+    }
+
+    /// One line per link, with a header row:
+    ///
+    /// ```text
+    /// from,to,distance,travel_time
+    /// 0,17,42.38,48.12
+    /// ```
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("from,to,distance,travel_time\n");
+        csv.extend(self.iter().map(|(from, link)| {
+            format!(
+                "{from},{},{:.2},{:.2}\n",
+                link.city, link.distance, link.travel_time
+            )
+        }));
+        csv
+    }
+
+    /// The lists as JSON, one array of `[to, distance, travel_time]` per
+    /// city:
+    ///
+    /// ```json
+    /// {"radius":150.0,"cities":[[[17,42.38,48.12],...],...]}
+    /// ```
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let cities: Vec<String> = (0..self.city_count())
+            .map(|i| {
+                let links: Vec<String> = self
+                    .neighbors(i)
+                    .iter()
+                    .map(|l| format!("[{},{:.2},{:.2}]", l.city, l.distance, l.travel_time))
+                    .collect();
+                format!("[{}]", links.join(","))
+            })
+            .collect();
+        format!(
+            "{{\"radius\":{:.1},\"cities\":[{}]}}",
+            self.radius,
+            cities.join(",")
+        )
+    }
+}
+
+/// Tile class of a city slot: land, but a route may end there.
+const CITY: u8 = u8::MAX;
+
+/// Sea neighbours of every city slot of `data` within `radius` travel
+/// time.
+///
+/// A radius that is not positive and finite, or `options` that fail
+/// [`NavOptions::validate`], compute nothing and return an empty
+/// [`CityDistances`].
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn compute_city_distances(
+    data: &WorldData,
+    radius: f32,
+    options: &NavOptions,
+) -> CityDistances {
+    if !(radius > 0.0 && radius.is_finite()) || options.validate().is_err() {
+        return CityDistances::default();
+    }
+    let width = data.width as usize;
+    let mut classes: Vec<u8> = data
+        .terrain
+        .iter()
+        .zip(&data.biomes)
+        .map(|(&t, &b)| tile_class(t, b))
+        .collect();
+    let mut cities = HashMap::with_capacity(data.city_slots.len());
+    for (i, &(x, y)) in data.city_slots.iter().enumerate() {
+        let idx = y as usize * width + x as usize;
+        classes[idx] = CITY;
+        cities.insert(idx, i as u32);
+    }
+    let map = SeaMap {
+        classes,
+        width,
+        height: data.height as usize,
+        cities,
+    };
+    let lists = data
+        .city_slots
+        .par_iter()
+        .map(|&(x, y)| map.neighbors((x as usize, y as usize), f64::from(radius), options))
+        .collect();
+    CityDistances::new(radius, lists)
+}
+
+/// Compute the sea neighbours of every city of the world file at `path`
+/// and store them in it, replacing any stored before.
+///
+/// The file must already be in the current [`FORMAT_VERSION`]
+/// ([`migrate`](crate::migrate) it first); it is rewritten through a
+/// temporary file next to it.
+///
+/// # Errors
+///
+/// [`InvalidInput`](io::ErrorKind::InvalidInput) if `options` fail
+/// [`NavOptions::validate`] or the file is in an older format, or any error
+/// reading or rewriting it.
+pub fn store_city_distances(
+    path: &str,
+    radius: f32,
+    options: &NavOptions,
+) -> io::Result<CityDistances> {
+    options.validate()?;
+    let reader = ChunkedWorldReader::open(path)?;
+    let version = reader.header.format_version;
+    if version != FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("format version {version} is not current ({FORMAT_VERSION}); migrate the file first"),
+        ));
+    }
+    let mut data = reader.load_world_data()?;
+    data.city_distances = compute_city_distances(&data, radius, options);
+    save::save_world_replacing(path, &data)?;
+    Ok(data.city_distances)
+}
+
+/// Tile classes of the whole map, with cities marked.
+struct SeaMap {
+    /// Row-major tile classes; [`CITY`] on city slots.
+    classes: Vec<u8>,
+    width: usize,
+    height: usize,
+    /// City index by row-major tile index.
+    cities: HashMap<usize, u32>,
+}
+
+impl SeaMap {
+    /// Dijkstra from the city at `(sx, sy)` until the travel time passes
+    /// `radius`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn neighbors(
+        &self,
+        (sx, sy): (usize, usize),
+        radius: f64,
+        options: &NavOptions,
+    ) -> Vec<SeaLink> {
+        let width = self.width;
+        // No route within the radius leaves this window.
+        let reach = (radius / options.min_cost()).ceil() as usize + 1;
+        let (x0, y0) = (sx.saturating_sub(reach), sy.saturating_sub(reach));
+        let (x1, y1) = (
+            (sx + reach).min(width - 1),
+            (sy + reach).min(self.height - 1),
+        );
+        let w = x1 - x0 + 1;
+        let len = w * (y1 - y0 + 1);
+        let id_of = |x: usize, y: usize| ((y - y0) * w + (x - x0)) as u32;
+        let class_at = |x: usize, y: usize| self.classes[y * width + x];
+        let water = |class: u8| class != BLOCKED && class != CITY;
+        let tile_cost = |class: u8| {
+            if class == COAST {
+                options.coast_cost
+            } else {
+                options.ocean_cost
+            }
+        };
+        let steps = if options.diagonal {
+            &STEPS[..]
+        } else {
+            &STEPS[..4]
+        };
+
+        // A bucket queue as wide as the cheapest step is exact: a tile's
+        // travel time cannot improve through a tile of its own bucket.
+        let bucket_width = options.min_cost();
+        let mut buckets = vec![Vec::new(); (radius / bucket_width) as usize + 1];
+        let mut g = vec![f64::INFINITY; len];
+        let mut length = vec![0.0f64; len];
+        let mut closed = vec![false; len];
+        let start = id_of(sx, sy);
+        g[start as usize] = 0.0;
+        buckets[0].push(start);
+
+        let mut links = Vec::new();
+        for b in 0..buckets.len() {
+            for node in std::mem::take(&mut buckets[b]) {
+                if closed[node as usize] {
+                    continue;
+                }
+                closed[node as usize] = true;
+                let (x, y) = (x0 + node as usize % w, y0 + node as usize / w);
+                let class = class_at(x, y);
+                if node != start && !water(class) {
+                    // Only cities are entered on land, and a route ends there.
+                    if let Some(&city) = self.cities.get(&(y * width + x)) {
+                        links.push(SeaLink {
+                            city,
+                            distance: length[node as usize] as f32,
+                            travel_time: g[node as usize] as f32,
+                        });
+                    }
+                    continue;
+                }
+                for &(dx, dy) in steps {
+                    let (Some(nx), Some(ny)) = (
+                        x.checked_add_signed(dx as isize),
+                        y.checked_add_signed(dy as isize),
+                    ) else {
+                        continue;
+                    };
+                    if nx < x0 || ny < y0 || nx > x1 || ny > y1 {
+                        continue;
+                    }
+                    let next_class = class_at(nx, ny);
+                    if !water(next_class) && (next_class != CITY || !water(class)) {
+                        continue;
+                    }
+                    let diagonal = dx != 0 && dy != 0;
+                    if diagonal && (!water(class_at(nx, y)) || !water(class_at(x, ny))) {
+                        // No cutting corners of land.
+                        continue;
+                    }
+                    let step = if diagonal {
+                        std::f64::consts::SQRT_2
+                    } else {
+                        1.0
+                    };
+                    let cost = step * tile_cost(if water(next_class) { next_class } else { class });
+                    let next = id_of(nx, ny);
+                    let ng = g[node as usize] + cost;
+                    if ng <= radius && ng < g[next as usize] {
+                        g[next as usize] = ng;
+                        length[next as usize] = length[node as usize] + step;
+                        buckets[((ng / bucket_width) as usize).max(b + 1)].push(next);
+                    }
+                }
+            }
+        }
+        links.sort_by(|a, b| {
+            a.travel_time
+                .total_cmp(&b.travel_time)
+                .then(a.city.cmp(&b.city))
+        });
+        links
+    }
+}
@@ -0,0 +1,380 @@
+//! The coarse navigation grid.
+//!
+//! The map is cut into square cells of [`NAV_CELL_SIZE`] tiles. The water
+//! of each cell is split into its 4-connected pieces; each piece is one
+//! node of the grid, and two nodes are linked when a water tile of one
+//! borders a water tile of the other across their cells' shared edge. Two
+//! water tiles are connected by sea exactly when their nodes are connected
+//! in the grid, and every node also carries the id of the sea it belongs
+//! to, so unreachable queries are rejected without searching.
+
+use std::collections::VecDeque;
+use std::io;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use super::NavOptions;
+use crate::biome::Biome;
+use crate::coords::WorldPos;
+use crate::rect::Layers;
+use crate::terrain::Terrain;
+use crate::world::World;
+
+/// Side length of one grid cell in tiles.
+pub const NAV_CELL_SIZE: u32 = 8;
+
+/// Tiles of one cell, for the fixed-size label arrays.
+pub(super) const CELL_AREA: usize = (NAV_CELL_SIZE * NAV_CELL_SIZE) as usize;
+
+// Tile classes.
+pub(super) const BLOCKED: u8 = 0;
+pub(super) const OCEAN: u8 = 1;
+pub(super) const COAST: u8 = 2;
+
+/// Naval class of a tile: only Water is navigable, Coast biome water is
+/// shallow.
+pub(super) fn tile_class(terrain: u8, biome: u8) -> u8 {
+    if Terrain::from_u8(terrain) != Terrain::Water {
+        BLOCKED
+    } else if Biome::from_u8(biome) == Biome::Coast {
+        COAST
+    } else {
+        OCEAN
+    }
+}
+
+// ---------------------------------------------------------------------------
+// NavGrid
+// ---------------------------------------------------------------------------
+
+/// One connected piece of water inside a cell.
+#[derive(Debug, Clone, Copy)]
+struct NavNode {
+    /// Row-major cell index.
+    cell: u32,
+    /// Water tiles in the piece.
+    water: u16,
+    /// Of which Coast biome.
+    coast: u16,
+}
+
+/// Coarse connectivity of the world's water, built once per world by
+/// [`World::nav_grid`](crate::World::nav_grid).
+#[derive(Debug, Clone)]
+pub struct NavGrid {
+    cols: u32,
+    rows: u32,
+    /// `cell_start[c]..cell_start[c + 1]` are the nodes of cell `c`, in the
+    /// order [`label_cell`] numbers its pieces.
+    cell_start: Vec<u32>,
+    nodes: Vec<NavNode>,
+    /// `edge_start[n]..edge_start[n + 1]` is the range of `edges` holding
+    /// the neighbours of node `n`.
+    edge_start: Vec<u32>,
+    edges: Vec<u32>,
+    /// Sea id of every node; nodes are connected iff their ids are equal.
+    sea: Vec<u32>,
+    num_seas: u32,
+}
+
+impl NavGrid {
+    /// Scan every tile of `world` and build its grid.
+    ///
+    /// Reads the whole map once, one band of cells at a time in parallel.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn build(world: &World) -> io::Result<Self> {
+        let width = world.width();
+        let cols = width.div_ceil(NAV_CELL_SIZE);
+        let rows = world.height().div_ceil(NAV_CELL_SIZE);
+
+        let bands = (0..rows)
+            .into_par_iter()
+            .map(|row| scan_band(world, row, cols))
+            .collect::<io::Result<Vec<Band>>>()?;
+
+        // Stitch the bands together: offset their node ids (so nodes are
+        // numbered cell by cell, row-major) and link each band's bottom row
+        // to the next band's top row.
+        let mut cell_start = Vec::with_capacity((cols * rows) as usize + 1);
+        let mut nodes = Vec::new();
+        let mut links = Vec::new();
+        let mut above: Option<(u32, &[u32])> = None;
+        for band in &bands {
+            let base = nodes.len() as u32;
+            let mut next = base;
+            for &count in &band.cell_counts {
+                cell_start.push(next);
+                next += u32::from(count);
+            }
+            nodes.extend_from_slice(&band.nodes);
+            links.extend(band.links.iter().map(|&(a, b)| (base + a, base + b)));
+            if let Some((above_base, bottom)) = above {
+                for (&a, &b) in bottom.iter().zip(&band.top) {
+                    if a != 0 && b != 0 {
+                        links.push((above_base + a - 1, base + b - 1));
+                    }
+                }
+            }
+            above = Some((base, &band.bottom));
+        }
+        cell_start.push(nodes.len() as u32);
+
+        let (edge_start, edges) = adjacency(nodes.len(), links);
+        let (sea, num_seas) = label_seas(&edge_start, &edges);
+
+        Ok(Self {
+            cols,
+            rows,
+            cell_start,
+            nodes,
+            edge_start,
+            edges,
+            sea,
+            num_seas,
+        })
+    }
+
+    /// Width and height of the grid in cells.
+    #[must_use]
+    pub const fn dims(&self) -> (u32, u32) {
+        (self.cols, self.rows)
+    }
+
+    /// Number of nodes (connected pieces of water in a cell).
+    #[must_use]
+    pub const fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of separate bodies of water, lakes included.
+    #[must_use]
+    pub const fn sea_count(&self) -> u32 {
+        self.num_seas
+    }
+
+    /// Approximate heap size of the grid in bytes.
+    #[must_use]
+    pub const fn memory_bytes(&self) -> usize {
+        (self.cell_start.len() + self.edge_start.len() + self.edges.len() + self.sea.len()) * 4
+            + self.nodes.len() * std::mem::size_of::<NavNode>()
+    }
+
+    /// Row-major index of the cell containing `pos`.
+    pub(super) const fn cell_of(&self, pos: WorldPos) -> u32 {
+        (pos.y / NAV_CELL_SIZE) * self.cols + pos.x / NAV_CELL_SIZE
+    }
+
+    /// Column and row of `cell`.
+    pub(super) const fn cell_xy(&self, cell: u32) -> (u32, u32) {
+        (cell % self.cols, cell / self.cols)
+    }
+
+    /// Node of piece `piece` (as numbered by [`label_cell`]) of `cell`.
+    pub(super) fn node(&self, cell: u32, piece: u8) -> Option<u32> {
+        let start = *self.cell_start.get(cell as usize)?;
+        let end = *self.cell_start.get(cell as usize + 1)?;
+        let node = start + u32::from(piece);
+        (node < end).then_some(node)
+    }
+
+    pub(super) fn node_cell(&self, node: u32) -> u32 {
+        self.nodes[node as usize].cell
+    }
+
+    pub(super) fn neighbors(&self, node: u32) -> &[u32] {
+        let n = node as usize;
+        &self.edges[self.edge_start[n] as usize..self.edge_start[n + 1] as usize]
+    }
+
+    pub(super) fn sea(&self, node: u32) -> u32 {
+        self.sea[node as usize]
+    }
+
+    /// Mean travel time per tile of `node`'s water.
+    pub(super) fn node_cost(&self, node: u32, options: &NavOptions) -> f64 {
+        let n = self.nodes[node as usize];
+        let coast = f64::from(n.coast);
+        let ocean = f64::from(n.water - n.coast);
+        coast.mul_add(options.coast_cost, ocean * options.ocean_cost) / f64::from(n.water.max(1))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Building
+// ---------------------------------------------------------------------------
+
+/// The nodes of one row of cells.
+struct Band {
+    nodes: Vec<NavNode>,
+    /// Pieces per cell, left to right.
+    cell_counts: Vec<u8>,
+    /// Links between pieces of horizontally adjacent cells, band-local ids.
+    links: Vec<(u32, u32)>,
+    /// Band-local node id + 1 of every tile of the top and bottom rows, 0
+    /// where not water.
+    top: Vec<u32>,
+    bottom: Vec<u32>,
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn scan_band(world: &World, row: u32, cols: u32) -> io::Result<Band> {
+    let width = world.width();
+    let y0 = row * NAV_CELL_SIZE;
+    let h = NAV_CELL_SIZE.min(world.height() - y0);
+    let rect = world.read_rect(
+        WorldPos::new(0, y0),
+        width,
+        h,
+        Layers::TERRAIN | Layers::BIOMES,
+    )?;
+    let classes: Vec<u8> = rect
+        .terrain
+        .iter()
+        .zip(&rect.biomes)
+        .map(|(&t, &b)| tile_class(t, b))
+        .collect();
+
+    let mut nodes = Vec::new();
+    let mut cell_counts = Vec::with_capacity(cols as usize);
+    // Band-local node id + 1 per tile.
+    let mut ids = vec![0u32; classes.len()];
+    for col in 0..cols {
+        let x0 = col * NAV_CELL_SIZE;
+        let w = NAV_CELL_SIZE.min(width - x0);
+        let class_at = |lx: u32, ly: u32| classes[(ly * width + x0 + lx) as usize];
+        let (labels, count) = label_cell(class_at, w, h);
+
+        let base = nodes.len() as u32;
+        nodes.extend((0..count).map(|_| NavNode {
+            cell: row * cols + col,
+            water: 0,
+            coast: 0,
+        }));
+        for ly in 0..h {
+            for lx in 0..w {
+                let label = labels[(ly * NAV_CELL_SIZE + lx) as usize];
+                if label == 0 {
+                    continue;
+                }
+                let id = base + u32::from(label) - 1;
+                ids[(ly * width + x0 + lx) as usize] = id + 1;
+                let node = &mut nodes[id as usize];
+                node.water += 1;
+                if class_at(lx, ly) == COAST {
+                    node.coast += 1;
+                }
+            }
+        }
+        cell_counts.push(count);
+    }
+
+    let mut links = Vec::new();
+    for ly in 0..h {
+        let row_ids = &ids[(ly * width) as usize..((ly + 1) * width) as usize];
+        for x in (NAV_CELL_SIZE..width).step_by(NAV_CELL_SIZE as usize) {
+            let (a, b) = (row_ids[x as usize - 1], row_ids[x as usize]);
+            if a != 0 && b != 0 {
+                links.push((a - 1, b - 1));
+            }
+        }
+    }
+
+    Ok(Band {
+        nodes,
+        cell_counts,
+        links,
+        top: ids[..width as usize].to_vec(),
+        bottom: ids[((h - 1) * width) as usize..].to_vec(),
+    })
+}
+
+/// Number the 4-connected pieces of water of a `w` x `h` cell.
+///
+/// Returns each tile's piece + 1 (0 where not water), indexed
+/// `ly * NAV_CELL_SIZE + lx`, and the number of pieces. Pieces are
+/// numbered in row-major order of their first tile, so the numbering only
+/// depends on the tiles.
+pub(super) fn label_cell(
+    class_at: impl Fn(u32, u32) -> u8,
+    w: u32,
+    h: u32,
+) -> ([u8; CELL_AREA], u8) {
+    let mut labels = [0u8; CELL_AREA];
+    let mut count = 0u8;
+    let mut stack = Vec::new();
+    for y in 0..h {
+        for x in 0..w {
+            let idx = (y * NAV_CELL_SIZE + x) as usize;
+            if labels[idx] != 0 || class_at(x, y) == BLOCKED {
+                continue;
+            }
+            count += 1;
+            labels[idx] = count;
+            stack.push((x, y));
+            while let Some((x, y)) = stack.pop() {
+                let neighbors = [
+                    (x.wrapping_sub(1), y),
+                    (x + 1, y),
+                    (x, y.wrapping_sub(1)),
+                    (x, y + 1),
+                ];
+                for (nx, ny) in neighbors {
+                    if nx >= w || ny >= h {
+                        continue;
+                    }
+                    let n = (ny * NAV_CELL_SIZE + nx) as usize;
+                    if labels[n] == 0 && class_at(nx, ny) != BLOCKED {
+                        labels[n] = count;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+    }
+    (labels, count)
+}
+
+/// Symmetric adjacency lists (CSR) from undirected `links`.
+fn adjacency(num_nodes: usize, links: Vec<(u32, u32)>) -> (Vec<u32>, Vec<u32>) {
+    let mut pairs: Vec<(u32, u32)> = links
+        .into_iter()
+        .flat_map(|(a, b)| [(a, b), (b, a)])
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+
+    let mut edge_start = vec![0u32; num_nodes + 1];
+    for &(a, _) in &pairs {
+        edge_start[a as usize + 1] += 1;
+    }
+    for i in 0..num_nodes {
+        edge_start[i + 1] += edge_start[i];
+    }
+    let edges = pairs.into_iter().map(|(_, b)| b).collect();
+    (edge_start, edges)
+}
+
+/// Connected components of the grid: a sea id per node and the count.
+fn label_seas(edge_start: &[u32], edges: &[u32]) -> (Vec<u32>, u32) {
+    let num_nodes = edge_start.len() - 1;
+    let mut sea = vec![u32::MAX; num_nodes];
+    let mut count = 0;
+    let mut queue = VecDeque::new();
+    for start in 0..num_nodes {
+        if sea[start] != u32::MAX {
+            continue;
+        }
+        sea[start] = count;
+        queue.push_back(start);
+        while let Some(n) = queue.pop_front() {
+            for &m in &edges[edge_start[n] as usize..edge_start[n + 1] as usize] {
+                if sea[m as usize] == u32::MAX {
+                    sea[m as usize] = count;
+                    queue.push_back(m as usize);
+                }
+            }
+        }
+        count += 1;
+    }
+    (sea, count)
+}
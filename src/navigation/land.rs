@@ -0,0 +1,215 @@
+//! Land route search: A* over the tiles of one island, read in a single
+//! rectangle around it.
+
+use std::collections::BinaryHeap;
+use std::io;
+
+use super::{octile, LandOptions, NavPath, Open, NONE, STEPS};
+use crate::biome::Biome;
+use crate::coords::{ChunkPos, WorldPos};
+use crate::island::BoundingBox;
+use crate::rect::Layers;
+use crate::terrain::Terrain;
+use crate::world::{TileInfo, World};
+
+/// Search the land route from `from` to `to`, both on the map.
+///
+/// Both ends must be land tiles of the same region; anything else has no
+/// route.
+#[allow(clippy::too_many_lines)]
+pub fn find_land_path(
+    world: &World,
+    from: WorldPos,
+    to: WorldPos,
+    options: &LandOptions,
+) -> io::Result<Option<NavPath>> {
+    let ends = world.tiles_at(&[from, to])?;
+    let region = ends[0].region_id;
+    let passable = |t: &TileInfo| t.terrain == Terrain::Land && t.biome.movement_cost().is_some();
+    if region == 0 || ends[1].region_id != region || !ends.iter().all(passable) {
+        return Ok(None);
+    }
+    if from == to {
+        return Ok(Some(NavPath {
+            tiles: vec![from],
+            distance: 0.0,
+            travel_time: 0.0,
+        }));
+    }
+
+    let bb = region_bounds(world, region);
+    let (w, h) = (bb.max_x - bb.min_x + 1, bb.max_y - bb.min_y + 1);
+    let rect = world.read_rect(
+        WorldPos::new(bb.min_x, bb.min_y),
+        w,
+        h,
+        Layers::TERRAIN | Layers::ELEVATION | Layers::REGION_LABELS | Layers::BIOMES,
+    )?;
+    // Movement cost of each tile of the region; `None` outside it.
+    let costs: Vec<Option<f64>> = (0..rect.terrain.len())
+        .map(|i| {
+            let on_region = rect.region_labels[i] == region
+                && Terrain::from_u8(rect.terrain[i]) == Terrain::Land;
+            on_region
+                .then(|| Biome::from_u8(rect.biomes[i]).movement_cost())
+                .flatten()
+                .map(f64::from)
+        })
+        .collect();
+
+    let id_of = |pos: WorldPos| {
+        let inside =
+            (bb.min_x..=bb.max_x).contains(&pos.x) && (bb.min_y..=bb.max_y).contains(&pos.y);
+        inside.then(|| (pos.y - bb.min_y) * w + (pos.x - bb.min_x))
+    };
+    let pos_of = |id: u32| WorldPos::new(bb.min_x + id % w, bb.min_y + id / w);
+    let cost_of = |id: Option<u32>| id.and_then(|id| costs[id as usize]);
+    let (Some(start), Some(goal)) = (id_of(from), id_of(to)) else {
+        return Ok(None);
+    };
+    let min_cost = min_movement_cost();
+    let heuristic = |pos: WorldPos| {
+        let dx = f64::from(pos.x.abs_diff(to.x));
+        let dy = f64::from(pos.y.abs_diff(to.y));
+        let tiles = if options.diagonal {
+            octile(dx, dy)
+        } else {
+            dx + dy
+        };
+        tiles * min_cost
+    };
+    let steps = if options.diagonal {
+        &STEPS[..]
+    } else {
+        &STEPS[..4]
+    };
+
+    let mut g = vec![f64::INFINITY; costs.len()];
+    let mut parent = vec![NONE; costs.len()];
+    let mut closed = vec![false; costs.len()];
+    let mut open = BinaryHeap::new();
+    g[start as usize] = 0.0;
+    open.push(Open {
+        estimate: heuristic(from),
+        node: start,
+    });
+
+    while let Some(Open { node, .. }) = open.pop() {
+        if closed[node as usize] {
+            continue;
+        }
+        closed[node as usize] = true;
+        if node == goal {
+            break;
+        }
+        let pos = pos_of(node);
+        let Some(cost) = costs[node as usize] else {
+            continue;
+        };
+        for &(dx, dy) in steps {
+            let Some(next_pos) = pos.offset(dx, dy) else {
+                continue;
+            };
+            let next = id_of(next_pos);
+            let (Some(next), Some(next_cost)) = (next, cost_of(next)) else {
+                continue;
+            };
+            let diagonal = dx != 0 && dy != 0;
+            if diagonal {
+                // No cutting corners of water or of other islands.
+                let side = |p: Option<WorldPos>| cost_of(p.and_then(id_of)).is_some();
+                if !side(pos.offset(dx, 0)) || !side(pos.offset(0, dy)) {
+                    continue;
+                }
+            }
+            let length = if diagonal {
+                std::f64::consts::SQRT_2
+            } else {
+                1.0
+            };
+            let grade =
+                f64::from((rect.elevation[node as usize] - rect.elevation[next as usize]).abs())
+                    / length;
+            let step =
+                length * (cost + next_cost) / 2.0 * options.slope_penalty.mul_add(grade, 1.0);
+            let ng = g[node as usize] + step;
+            if ng < g[next as usize] {
+                g[next as usize] = ng;
+                parent[next as usize] = node;
+                open.push(Open {
+                    estimate: ng + heuristic(next_pos),
+                    node: next,
+                });
+            }
+        }
+    }
+    if !closed[goal as usize] {
+        return Ok(None);
+    }
+
+    let mut tiles = vec![to];
+    let mut distance = 0.0;
+    let (mut node, mut here) = (goal, to);
+    while parent[node as usize] != NONE {
+        node = parent[node as usize];
+        let prev = pos_of(node);
+        distance += prev.distance(here);
+        tiles.push(prev);
+        here = prev;
+    }
+    tiles.reverse();
+    Ok(Some(NavPath {
+        tiles,
+        distance,
+        travel_time: g[goal as usize],
+    }))
+}
+
+/// Bounding box of region `region`.
+///
+/// Exact for islands in the [registry](World::islands). Other regions get
+/// the union of the chunks whose summary lists them, or the whole map for
+/// files older than version 4, which have no summaries.
+fn region_bounds(world: &World, region: u32) -> BoundingBox {
+    let islands = world.islands();
+    if let Ok(i) = islands.binary_search_by_key(&region, |i| i.id) {
+        return islands[i].bounds;
+    }
+    let geometry = world.geometry();
+    let whole = BoundingBox {
+        min_x: 0,
+        min_y: 0,
+        max_x: world.width() - 1,
+        max_y: world.height() - 1,
+    };
+    let mut bounds: Option<BoundingBox> = None;
+    for cy in 0..world.chunks_y() {
+        for cx in 0..world.chunks_x() {
+            let pos = ChunkPos::new(cx, cy);
+            let Some(summary) = world.chunk_summary(pos) else {
+                return whole;
+            };
+            if summary.region_ids.binary_search(&region).is_err() {
+                continue;
+            }
+            let (Some(origin), Some((w, h))) =
+                (geometry.chunk_origin(pos), geometry.chunk_dims(pos))
+            else {
+                continue;
+            };
+            let bb = bounds.get_or_insert_with(|| BoundingBox::point(origin.x, origin.y));
+            bb.expand(origin.x, origin.y);
+            bb.expand(origin.x + w - 1, origin.y + h - 1);
+        }
+    }
+    bounds.unwrap_or(whole)
+}
+
+/// Cheapest movement cost of any biome, for an admissible heuristic.
+fn min_movement_cost() -> f64 {
+    (0..=u8::MAX)
+        .map(Biome::from_u8)
+        .filter_map(Biome::movement_cost)
+        .map(f64::from)
+        .fold(f64::INFINITY, f64::min)
+}
@@ -0,0 +1,210 @@
+//! Pathfinding over the world map.
+//!
+//! # Naval routes
+//!
+//! Naval travel is searched in two passes so that a query across a
+//! 10,000-tile map stays fast:
+//!
+//! 1. A [`NavGrid`] splits the map into square cells of [`NAV_CELL_SIZE`]
+//!    tiles and each cell's water into its connected pieces. It is built
+//!    once per world (see [`World::nav_grid`](crate::World::nav_grid)) and
+//!    answers "are these two points connected by sea, and roughly along
+//!    which cells?".
+//! 2. The exact route is then searched tile by tile, but only inside a
+//!    narrow corridor around the cells the coarse route passed through.
+//!
+//! Because each cell's water is split into connected pieces, the coarse
+//! pass never finds a route the tile search cannot follow, and a `None`
+//! result means the two points really are not connected by water. The
+//! corridor makes routes near-optimal rather than optimal: the route may
+//! be slightly longer than the best one when the best one leaves the
+//! corridor.
+//!
+//! # Land routes
+//!
+//! Armies only march within one island, so a land search reads the
+//! island's bounding box once and runs A* over its tiles directly. Each
+//! tile costs its biome's [movement cost](crate::biome::Biome::movement_cost)
+//! and every step is made dearer by the elevation it climbs or descends
+//! (see [`LandOptions`]). Routes are optimal.
+//!
+//! # City sea distances
+//!
+//! [`compute_city_distances`] floods the sea around every city at once and
+//! keeps, for each, the other cities within a travel-time radius: the
+//! [`CityDistances`] the world file can store.
+//!
+//! | Type | Role |
+//! |------|------|
+//! | [`NavOptions`] | Travel time per tile of open ocean and of coastal water, diagonal moves |
+//! | [`LandOptions`] | Slope penalty, diagonal moves |
+//! | [`NavPath`] | The tiles of a route, its length and its travel time |
+//! | [`NavGrid`] | The cached coarse grid |
+//! | [`CityDistances`], [`SeaLink`] | Sea neighbours of every city with their travel times |
+
+mod distances;
+mod grid;
+mod land;
+mod naval;
+
+use std::cmp::Ordering;
+use std::io;
+
+use crate::coords::WorldPos;
+
+pub use distances::{compute_city_distances, store_city_distances, CityDistances, SeaLink};
+pub use grid::{NavGrid, NAV_CELL_SIZE};
+pub(crate) use land::find_land_path;
+pub(crate) use naval::find_naval_path;
+
+// ---------------------------------------------------------------------------
+// Options and results
+// ---------------------------------------------------------------------------
+
+/// Costs and moves of a naval search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavOptions {
+    /// Travel time to cross one tile of open water (Ocean and Deep Harbor
+    /// biomes).
+    pub ocean_cost: f64,
+    /// Travel time to cross one tile of shallow coastal water (Coast biome).
+    pub coast_cost: f64,
+    /// Allow diagonal moves (costing √2 times as much). Ships never cut a
+    /// corner of land: a diagonal move needs both tiles beside it to be
+    /// water too.
+    pub diagonal: bool,
+}
+
+impl Default for NavOptions {
+    fn default() -> Self {
+        Self {
+            ocean_cost: 1.0,
+            coast_cost: 1.5,
+            diagonal: true,
+        }
+    }
+}
+
+impl NavOptions {
+    /// Check that both costs are positive and finite, which the searches
+    /// and their heuristics rely on.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) naming the first bad
+    /// cost.
+    pub fn validate(&self) -> io::Result<()> {
+        for (name, cost) in [
+            ("ocean_cost", self.ocean_cost),
+            ("coast_cost", self.coast_cost),
+        ] {
+            if !(cost.is_finite() && cost > 0.0) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{name} must be positive and finite, got {cost}"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Cheapest cost of any tile, for admissible heuristics.
+    const fn min_cost(&self) -> f64 {
+        self.ocean_cost.min(self.coast_cost)
+    }
+}
+
+/// Costs and moves of a land search.
+///
+/// A step between two tiles costs its length times the mean
+/// [movement cost](crate::biome::Biome::movement_cost) of the two biomes,
+/// times `1 + slope_penalty * grade`, where the grade is the elevation
+/// difference of the two tiles divided by the step's length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LandOptions {
+    /// Weight of the grade. Neighbouring land tiles typically differ by
+    /// 0.01 to 0.05 in elevation, so the default of 20 makes a typical
+    /// step 20% to 100% dearer than the same step on flat ground.
+    pub slope_penalty: f64,
+    /// Allow diagonal moves. Armies never cut a corner of water or of
+    /// another island: a diagonal move needs both tiles beside it to be
+    /// passable too.
+    pub diagonal: bool,
+}
+
+impl Default for LandOptions {
+    fn default() -> Self {
+        Self {
+            slope_penalty: 20.0,
+            diagonal: true,
+        }
+    }
+}
+
+/// A route found by a naval or land search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavPath {
+    /// Every tile of the route, from the start to the goal inclusive.
+    pub tiles: Vec<WorldPos>,
+    /// Length of the route in tiles (diagonal steps count √2).
+    pub distance: f64,
+    /// Sum of the step costs along the route (see [`NavOptions`] and
+    /// [`LandOptions`]).
+    pub travel_time: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Search helpers
+// ---------------------------------------------------------------------------
+
+/// No parent node, or a cell outside the corridor.
+const NONE: u32 = u32::MAX;
+
+/// Tile steps: straight first, then diagonal.
+const STEPS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Length of the shortest 8-connected path across `dx` by `dy` tiles or
+/// cells.
+fn octile(dx: f64, dy: f64) -> f64 {
+    (std::f64::consts::SQRT_2 - 1.0).mul_add(dx.min(dy), dx.max(dy))
+}
+
+/// An entry of an A* open list, ordered so that `BinaryHeap` pops the
+/// lowest estimate first (ties by node, for deterministic results).
+#[derive(Debug, Clone, Copy)]
+struct Open {
+    estimate: f64,
+    node: u32,
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
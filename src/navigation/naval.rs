@@ -0,0 +1,431 @@
+//! Naval route search: the coarse pass over the [`NavGrid`], then the tile
+//! pass inside the corridor around the coarse route.
+
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::io;
+use std::sync::Arc;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use super::grid::{label_cell, tile_class, NavGrid, BLOCKED, CELL_AREA, COAST, NAV_CELL_SIZE};
+use super::{octile, NavOptions, NavPath, Open, NONE, STEPS};
+use crate::coords::{ChunkPos, WorldPos};
+use crate::save::ChunkData;
+use crate::world::World;
+
+/// Cells on each side of the coarse route the tile search may use.
+const CORRIDOR_RADIUS: u32 = 1;
+
+/// Search the sea route from `from` to `to`, both on the map.
+///
+/// Each end is either a water tile or a land tile with a water
+/// 4-neighbour (a coastal city, say); anything else has no route.
+pub fn find_naval_path(
+    world: &World,
+    grid: &NavGrid,
+    from: WorldPos,
+    to: WorldPos,
+    options: &NavOptions,
+) -> io::Result<Option<NavPath>> {
+    let mut cells = CellCache::new(world, grid);
+    let starts = endpoint_nodes(&mut cells, from)?;
+    let goals = endpoint_nodes(&mut cells, to)?;
+    if starts.is_empty() || goals.is_empty() {
+        return Ok(None);
+    }
+    if from == to {
+        return Ok(Some(NavPath {
+            tiles: vec![from],
+            distance: 0.0,
+            travel_time: 0.0,
+        }));
+    }
+    let Some(route) = coarse_route(grid, &starts, &goals, options) else {
+        return Ok(None);
+    };
+    let corridor = corridor(grid, &route);
+    cells.prefetch(&corridor)?;
+    tile_route(&mut cells, &corridor, from, to, options)
+}
+
+// ---------------------------------------------------------------------------
+// Cells
+// ---------------------------------------------------------------------------
+
+/// Tile classes of the cells a query touches, each read once.
+struct CellCache<'a> {
+    world: &'a World,
+    grid: &'a NavGrid,
+    cells: HashMap<u32, [u8; CELL_AREA]>,
+    /// Chunks under the cells, held for the whole query.
+    chunks: HashMap<ChunkPos, Arc<ChunkData>>,
+}
+
+impl<'a> CellCache<'a> {
+    fn new(world: &'a World, grid: &'a NavGrid) -> Self {
+        Self {
+            world,
+            grid,
+            cells: HashMap::new(),
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// Load every chunk under `cells` at once, in parallel.
+    fn prefetch(&mut self, cells: &[u32]) -> io::Result<()> {
+        let geometry = self.world.geometry();
+        let mut wanted = BTreeSet::new();
+        for &cell in cells {
+            let (origin, w, h) = self.bounds(cell);
+            let far = WorldPos::new(origin.x + w - 1, origin.y + h - 1);
+            wanted.extend(
+                geometry
+                    .chunks_in(origin, far)
+                    .into_iter()
+                    .filter(|pos| !self.chunks.contains_key(pos)),
+            );
+        }
+        let loaded = wanted
+            .into_par_iter()
+            .map(|pos| Ok((pos, self.world.chunk(pos)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        self.chunks.extend(loaded);
+        Ok(())
+    }
+
+    fn chunk(&mut self, pos: ChunkPos) -> io::Result<Arc<ChunkData>> {
+        if let Some(chunk) = self.chunks.get(&pos) {
+            return Ok(Arc::clone(chunk));
+        }
+        let chunk = self.world.chunk(pos)?;
+        self.chunks.insert(pos, Arc::clone(&chunk));
+        Ok(chunk)
+    }
+
+    /// Top-left tile and size of `cell` (edge cells may be smaller).
+    fn bounds(&self, cell: u32) -> (WorldPos, u32, u32) {
+        let (cx, cy) = self.grid.cell_xy(cell);
+        let origin = WorldPos::new(cx * NAV_CELL_SIZE, cy * NAV_CELL_SIZE);
+        let w = NAV_CELL_SIZE.min(self.world.width() - origin.x);
+        let h = NAV_CELL_SIZE.min(self.world.height() - origin.y);
+        (origin, w, h)
+    }
+
+    /// Tile classes of `cell`, indexed `ly * NAV_CELL_SIZE + lx`.
+    fn load(&mut self, cell: u32) -> io::Result<&[u8; CELL_AREA]> {
+        if !self.cells.contains_key(&cell) {
+            let (origin, w, h) = self.bounds(cell);
+            let geometry = self.world.geometry();
+            let mut classes = [BLOCKED; CELL_AREA];
+            let mut chunk: Option<(ChunkPos, Arc<ChunkData>)> = None;
+            for ly in 0..h {
+                for lx in 0..w {
+                    let pos = WorldPos::new(origin.x + lx, origin.y + ly);
+                    let Some((chunk_pos, idx)) = geometry.tile_index(pos) else {
+                        continue;
+                    };
+                    let data = match &chunk {
+                        Some((p, data)) if *p == chunk_pos => data,
+                        _ => &chunk.insert((chunk_pos, self.chunk(chunk_pos)?)).1,
+                    };
+                    classes[(ly * NAV_CELL_SIZE + lx) as usize] =
+                        tile_class(data.terrain[idx], data.biomes[idx]);
+                }
+            }
+            self.cells.insert(cell, classes);
+        }
+        Ok(&self.cells[&cell])
+    }
+
+    fn class_at(&mut self, pos: WorldPos) -> io::Result<u8> {
+        let classes = self.load(self.grid.cell_of(pos))?;
+        Ok(classes[local_index(pos) as usize])
+    }
+
+    /// Grid node of the water tile `pos`.
+    fn node_at(&mut self, pos: WorldPos) -> io::Result<Option<u32>> {
+        let cell = self.grid.cell_of(pos);
+        let (_, w, h) = self.bounds(cell);
+        let classes = self.load(cell)?;
+        let (labels, _) = label_cell(|lx, ly| classes[(ly * NAV_CELL_SIZE + lx) as usize], w, h);
+        let label = labels[local_index(pos) as usize];
+        Ok(label
+            .checked_sub(1)
+            .and_then(|piece| self.grid.node(cell, piece)))
+    }
+}
+
+/// Index of `pos` in its cell's class array.
+const fn local_index(pos: WorldPos) -> u32 {
+    (pos.y % NAV_CELL_SIZE) * NAV_CELL_SIZE + pos.x % NAV_CELL_SIZE
+}
+
+/// Grid nodes a route may start or end in at `pos`: its own if it is
+/// water, otherwise those of its water 4-neighbours.
+fn endpoint_nodes(cells: &mut CellCache, pos: WorldPos) -> io::Result<Vec<u32>> {
+    let geometry = cells.world.geometry();
+    let tiles: Vec<WorldPos> = if cells.class_at(pos)? == BLOCKED {
+        STEPS[..4]
+            .iter()
+            .filter_map(|&(dx, dy)| pos.offset(dx, dy))
+            .filter(|&n| geometry.contains(n))
+            .collect()
+    } else {
+        vec![pos]
+    };
+    let mut nodes = Vec::new();
+    for tile in tiles {
+        if cells.class_at(tile)? == BLOCKED {
+            continue;
+        }
+        if let Some(node) = cells.node_at(tile)? {
+            nodes.push(node);
+        }
+    }
+    nodes.sort_unstable();
+    nodes.dedup();
+    Ok(nodes)
+}
+
+// ---------------------------------------------------------------------------
+// Coarse pass
+// ---------------------------------------------------------------------------
+
+/// Cheapest chain of grid nodes from any of `starts` to any of `goals`.
+fn coarse_route(
+    grid: &NavGrid,
+    starts: &[u32],
+    goals: &[u32],
+    options: &NavOptions,
+) -> Option<Vec<u32>> {
+    let seas: HashSet<u32> = goals.iter().map(|&g| grid.sea(g)).collect();
+    let goal_cells: Vec<(u32, u32)> = goals
+        .iter()
+        .map(|&g| grid.cell_xy(grid.node_cell(g)))
+        .collect();
+    let step = f64::from(NAV_CELL_SIZE);
+    let heuristic = |node: u32| {
+        let (x, y) = grid.cell_xy(grid.node_cell(node));
+        let cells = goal_cells
+            .iter()
+            .map(|&(gx, gy)| octile(f64::from(x.abs_diff(gx)), f64::from(y.abs_diff(gy))))
+            .fold(f64::INFINITY, f64::min);
+        cells * step * options.min_cost()
+    };
+
+    let num_nodes = grid.node_count();
+    let mut g = vec![f64::INFINITY; num_nodes];
+    let mut parent = vec![NONE; num_nodes];
+    let mut closed = vec![false; num_nodes];
+    let mut open = BinaryHeap::new();
+    for &s in starts.iter().filter(|&&s| seas.contains(&grid.sea(s))) {
+        g[s as usize] = 0.0;
+        open.push(Open {
+            estimate: heuristic(s),
+            node: s,
+        });
+    }
+
+    while let Some(Open { node, .. }) = open.pop() {
+        if closed[node as usize] {
+            continue;
+        }
+        closed[node as usize] = true;
+        if goals.contains(&node) {
+            let mut route = vec![node];
+            let mut n = node;
+            while parent[n as usize] != NONE {
+                n = parent[n as usize];
+                route.push(n);
+            }
+            route.reverse();
+            return Some(route);
+        }
+        let here = g[node as usize];
+        let cost = grid.node_cost(node, options);
+        let (x, y) = grid.cell_xy(grid.node_cell(node));
+        // Straight moves to linked pieces, and diagonal moves to pieces
+        // linked to one of those (so that the staircase of straight moves
+        // along a diagonal does not look longer than it is).
+        let straight = grid.neighbors(node).iter().map(|&n| (n, 1.0));
+        let diagonal = grid
+            .neighbors(node)
+            .iter()
+            .flat_map(|&side| grid.neighbors(side))
+            .filter(|&&n| {
+                let (nx, ny) = grid.cell_xy(grid.node_cell(n));
+                nx.abs_diff(x) == 1 && ny.abs_diff(y) == 1
+            })
+            .map(|&n| (n, std::f64::consts::SQRT_2));
+        for (next, length) in straight.chain(diagonal) {
+            let ng = here + length * step * (cost + grid.node_cost(next, options)) / 2.0;
+            if ng < g[next as usize] {
+                g[next as usize] = ng;
+                parent[next as usize] = node;
+                open.push(Open {
+                    estimate: ng + heuristic(next),
+                    node: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Cells within [`CORRIDOR_RADIUS`] of any cell of `route`, ascending.
+fn corridor(grid: &NavGrid, route: &[u32]) -> Vec<u32> {
+    let (cols, rows) = grid.dims();
+    let mut cells = BTreeSet::new();
+    for &node in route {
+        let (x, y) = grid.cell_xy(grid.node_cell(node));
+        for cy in y.saturating_sub(CORRIDOR_RADIUS)..=(y + CORRIDOR_RADIUS).min(rows - 1) {
+            for cx in x.saturating_sub(CORRIDOR_RADIUS)..=(x + CORRIDOR_RADIUS).min(cols - 1) {
+                cells.insert(cy * cols + cx);
+            }
+        }
+    }
+    cells.into_iter().collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tile pass
+// ---------------------------------------------------------------------------
+
+/// A* over the tiles of `corridor`.
+#[allow(clippy::too_many_lines)]
+fn tile_route(
+    cells: &mut CellCache,
+    corridor: &[u32],
+    from: WorldPos,
+    to: WorldPos,
+    options: &NavOptions,
+) -> io::Result<Option<NavPath>> {
+    let grid = cells.grid;
+    let geometry = cells.world.geometry();
+
+    // Tile ids: slot of the cell in `corridor` * CELL_AREA + local index.
+    let area = NAV_CELL_SIZE * NAV_CELL_SIZE;
+    let (cols, rows) = grid.dims();
+    let mut slots = vec![NONE; (cols * rows) as usize];
+    for (&cell, slot) in corridor.iter().zip(0..) {
+        slots[cell as usize] = slot;
+    }
+    let mut classes = Vec::with_capacity(corridor.len() * CELL_AREA);
+    for &cell in corridor {
+        classes.extend_from_slice(cells.load(cell)?);
+    }
+    let id_of = |pos: WorldPos| {
+        let slot = slots[grid.cell_of(pos) as usize];
+        (slot != NONE).then(|| slot * area + local_index(pos))
+    };
+    let pos_of = |id: u32| {
+        let (cx, cy) = grid.cell_xy(corridor[(id / area) as usize]);
+        let local = id % area;
+        WorldPos::new(
+            cx * NAV_CELL_SIZE + local % NAV_CELL_SIZE,
+            cy * NAV_CELL_SIZE + local / NAV_CELL_SIZE,
+        )
+    };
+    let (Some(start), Some(goal)) = (id_of(from), id_of(to)) else {
+        return Ok(None);
+    };
+    let water = |id: u32| classes[id as usize] != BLOCKED;
+    let tile_cost = |id: u32| {
+        if classes[id as usize] == COAST {
+            options.coast_cost
+        } else {
+            options.ocean_cost
+        }
+    };
+    let heuristic = |pos: WorldPos| {
+        let dx = f64::from(pos.x.abs_diff(to.x));
+        let dy = f64::from(pos.y.abs_diff(to.y));
+        let tiles = if options.diagonal {
+            octile(dx, dy)
+        } else {
+            dx + dy
+        };
+        tiles * options.min_cost()
+    };
+    let steps = if options.diagonal {
+        &STEPS[..]
+    } else {
+        &STEPS[..4]
+    };
+
+    let mut g = vec![f64::INFINITY; classes.len()];
+    let mut parent = vec![NONE; classes.len()];
+    let mut closed = vec![false; classes.len()];
+    let mut open = BinaryHeap::new();
+    g[start as usize] = 0.0;
+    open.push(Open {
+        estimate: heuristic(from),
+        node: start,
+    });
+
+    while let Some(Open { node, .. }) = open.pop() {
+        if closed[node as usize] {
+            continue;
+        }
+        closed[node as usize] = true;
+        if node == goal {
+            break;
+        }
+        let pos = pos_of(node);
+        for &(dx, dy) in steps {
+            let Some(next_pos) = pos.offset(dx, dy).filter(|&p| geometry.contains(p)) else {
+                continue;
+            };
+            let Some(next) = id_of(next_pos) else {
+                continue;
+            };
+            // Land is only entered to arrive, and only from the water.
+            if !water(next) && (next != goal || !water(node)) {
+                continue;
+            }
+            let diagonal = dx != 0 && dy != 0;
+            if diagonal {
+                // No cutting corners of land.
+                let side = |p: Option<WorldPos>| p.and_then(id_of).is_some_and(water);
+                if !side(pos.offset(dx, 0)) || !side(pos.offset(0, dy)) {
+                    continue;
+                }
+            }
+            let length = if diagonal {
+                std::f64::consts::SQRT_2
+            } else {
+                1.0
+            };
+            let cost = length * tile_cost(if water(next) { next } else { node });
+            let ng = g[node as usize] + cost;
+            if ng < g[next as usize] {
+                g[next as usize] = ng;
+                parent[next as usize] = node;
+                open.push(Open {
+                    estimate: ng + heuristic(next_pos),
+                    node: next,
+                });
+            }
+        }
+    }
+    if !closed[goal as usize] {
+        return Ok(None);
+    }
+
+    let mut tiles = vec![to];
+    let mut distance = 0.0;
+    let (mut node, mut here) = (goal, to);
+    while parent[node as usize] != NONE {
+        node = parent[node as usize];
+        let prev = pos_of(node);
+        distance += prev.distance(here);
+        tiles.push(prev);
+        here = prev;
+    }
+    tiles.reverse();
+    Ok(Some(NavPath {
+        tiles,
+        distance,
+        travel_time: g[goal as usize],
+    }))
+}
@@ -0,0 +1,193 @@
+//! Rectangular reads across chunk boundaries.
+//!
+//! World data is stored in square chunks, but most consumers think in world
+//! rectangles: the tiles under a map tile, an island's bounding box, a city's
+//! hinterland. [`World::read_rect`](crate::World::read_rect) copies the
+//! requested [`Layers`] of such a rectangle into one contiguous row-major
+//! [`RectData`], loading every chunk it overlaps, so callers index with
+//! world coordinates and never see a chunk seam.
+
+use std::ops::BitOr;
+
+use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::biome::Biome;
+use crate::coords::WorldPos;
+use crate::save::ChunkData;
+use crate::terrain::Terrain;
+use crate::territory::{self, UNOWNED};
+
+// ---------------------------------------------------------------------------
+// Layer selection
+// ---------------------------------------------------------------------------
+
+/// A set of per-tile layers to read. Combine with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layers(u8);
+
+impl Layers {
+    pub const TERRAIN: Self = Self(1);
+    pub const ELEVATION: Self = Self(1 << 1);
+    pub const REGION_LABELS: Self = Self(1 << 2);
+    pub const BIOMES: Self = Self(1 << 3);
+    pub const TERRITORY: Self = Self(1 << 4);
+    pub const ALL: Self = Self(0b11111);
+
+    /// `true` if every layer in `other` is also in `self`.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Layers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RectData
+// ---------------------------------------------------------------------------
+
+/// The selected layers of a world rectangle, row-major.
+///
+/// Layers that were not requested are left empty.
+#[derive(Debug, Clone)]
+pub struct RectData {
+    /// World x of the first column.
+    pub x0: u32,
+    /// World y of the first row.
+    pub y0: u32,
+    pub width: u32,
+    pub height: u32,
+    pub layers: Layers,
+    /// Terrain type (`Terrain::to_u8()`).
+    pub terrain: Vec<u8>,
+    pub elevation: Vec<f32>,
+    pub region_labels: Vec<u32>,
+    /// Biome classification (`Biome::to_u8()`).
+    pub biomes: Vec<u8>,
+    /// Owning city of each tile (see [`territory`](crate::territory)).
+    pub territory: Vec<u32>,
+}
+
+/// One tile yielded by [`RectData::par_tiles`].
+///
+/// Fields of layers that were not read hold their zero value (water,
+/// ocean, elevation `0.0`, region `0`, no owner).
+#[derive(Debug, Clone, Copy)]
+pub struct RectTile {
+    pub pos: WorldPos,
+    pub terrain: Terrain,
+    pub biome: Biome,
+    pub elevation: f32,
+    pub region_id: u32,
+    /// Index into `city_slots` of the city owning this tile.
+    pub owner: Option<usize>,
+}
+
+impl RectData {
+    /// Allocate an empty rectangle with room for `layers`.
+    pub(crate) fn new(origin: WorldPos, width: u32, height: u32, layers: Layers) -> Self {
+        let len = width as usize * height as usize;
+        let alloc = |layer: Layers| if layers.contains(layer) { len } else { 0 };
+        Self {
+            x0: origin.x,
+            y0: origin.y,
+            width,
+            height,
+            layers,
+            terrain: vec![0; alloc(Layers::TERRAIN)],
+            elevation: vec![0.0; alloc(Layers::ELEVATION)],
+            region_labels: vec![0; alloc(Layers::REGION_LABELS)],
+            biomes: vec![0; alloc(Layers::BIOMES)],
+            territory: vec![UNOWNED; alloc(Layers::TERRITORY)],
+        }
+    }
+
+    /// Copy the part of `chunk` (whose top-left tile is `chunk_origin`) that
+    /// overlaps this rectangle.
+    pub(crate) fn copy_from_chunk(&mut self, chunk: &ChunkData, chunk_origin: WorldPos) {
+        let WorldPos { x: ox, y: oy } = chunk_origin;
+        let x_start = self.x0.max(ox);
+        let x_end = (self.x0 + self.width).min(ox + chunk.width);
+        let y_start = self.y0.max(oy);
+        let y_end = (self.y0 + self.height).min(oy + chunk.height);
+        if x_start >= x_end || y_start >= y_end {
+            return;
+        }
+        let span = (x_end - x_start) as usize;
+
+        for y in y_start..y_end {
+            let src = ((y - oy) * chunk.width + (x_start - ox)) as usize;
+            let dst = self.index_of(WorldPos::new(x_start, y));
+            if self.layers.contains(Layers::TERRAIN) {
+                self.terrain[dst..dst + span].copy_from_slice(&chunk.terrain[src..src + span]);
+            }
+            if self.layers.contains(Layers::ELEVATION) {
+                self.elevation[dst..dst + span].copy_from_slice(&chunk.elevation[src..src + span]);
+            }
+            if self.layers.contains(Layers::REGION_LABELS) {
+                self.region_labels[dst..dst + span]
+                    .copy_from_slice(&chunk.region_labels[src..src + span]);
+            }
+            if self.layers.contains(Layers::BIOMES) {
+                self.biomes[dst..dst + span].copy_from_slice(&chunk.biomes[src..src + span]);
+            }
+            if self.layers.contains(Layers::TERRITORY) {
+                self.territory[dst..dst + span].copy_from_slice(&chunk.territory[src..src + span]);
+            }
+        }
+    }
+
+    /// World position of the top-left tile.
+    #[must_use]
+    pub const fn origin(&self) -> WorldPos {
+        WorldPos::new(self.x0, self.y0)
+    }
+
+    /// `true` if world tile `pos` lies inside the rectangle.
+    #[must_use]
+    pub const fn contains(&self, pos: WorldPos) -> bool {
+        pos.x >= self.x0
+            && pos.y >= self.y0
+            && pos.x - self.x0 < self.width
+            && pos.y - self.y0 < self.height
+    }
+
+    /// Index of world tile `pos` in the layer buffers.
+    ///
+    /// `pos` must lie inside the rectangle (see [`contains`](Self::contains)).
+    #[must_use]
+    pub const fn index_of(&self, pos: WorldPos) -> usize {
+        (pos.y - self.y0) as usize * self.width as usize + (pos.x - self.x0) as usize
+    }
+
+    /// The tile at buffer index `idx`.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn tile(&self, idx: usize) -> RectTile {
+        let w = self.width as usize;
+        RectTile {
+            pos: WorldPos::new(self.x0 + (idx % w) as u32, self.y0 + (idx / w) as u32),
+            terrain: Terrain::from_u8(self.terrain.get(idx).copied().unwrap_or(0)),
+            biome: Biome::from_u8(self.biomes.get(idx).copied().unwrap_or(0)),
+            elevation: self.elevation.get(idx).copied().unwrap_or(0.0),
+            region_id: self.region_labels.get(idx).copied().unwrap_or(0),
+            owner: self
+                .territory
+                .get(idx)
+                .copied()
+                .and_then(territory::owner_index),
+        }
+    }
+
+    /// Every tile of the rectangle in row-major order, in parallel.
+    #[must_use]
+    pub fn par_tiles(&self) -> impl IndexedParallelIterator<Item = RectTile> + '_ {
+        let len = self.width as usize * self.height as usize;
+        (0..len).into_par_iter().map(|idx| self.tile(idx))
+    }
+}
@@ -0,0 +1,140 @@
+//! City diagnostics sidecar file.
+//!
+//! [`CityDiagnostics`] are large (one byte per tile) and only useful while
+//! tuning placement, so they are kept out of the world file and saved next
+//! to it instead, as `<name>.citydiag`.
+//!
+//! Layout:
+//!
+//! ```text
+//! magic "WGCD"(4) · version(1) · width(4) · height(4)
+//! · outcomes_uncompressed_len(4) · outcomes_compressed_len(4)
+//! · outcomes (deflate, row-major SlotOutcome::to_u8)
+//! · num_islands(4) · islands
+//! ```
+//!
+//! Each dropped island is:
+//!
+//! ```text
+//! region_id(4) · reason(1) · slots(4) · required(4)
+//! · one count per outcome(4 each, SlotOutcome::COUNT of them)
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use super::{
+    compress, decompress, read_u32, read_u8, write_len, write_u32, write_u8, EncodedChunk,
+};
+use crate::city_diagnostics::{CityDiagnostics, IslandDrop, IslandDropReason, SlotOutcome};
+
+const MAGIC: &[u8; 4] = b"WGCD";
+const VERSION: u8 = 2;
+
+/// Sidecar path for the world file at `world_path`: the `.world` extension
+/// (if any) replaced by `.citydiag`.
+#[must_use]
+pub fn city_diagnostics_path(world_path: &str) -> String {
+    let stem = world_path.strip_suffix(".world").unwrap_or(world_path);
+    format!("{stem}.citydiag")
+}
+
+/// Write `diagnostics` to `path`.
+///
+/// # Errors
+///
+/// Any error creating or writing the file.
+pub fn save_city_diagnostics(path: &str, diagnostics: &CityDiagnostics) -> io::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    f.write_all(MAGIC)?;
+    write_u8(&mut f, VERSION)?;
+    write_u32(&mut f, diagnostics.width)?;
+    write_u32(&mut f, diagnostics.height)?;
+
+    let encoded = compress(&diagnostics.outcomes)?;
+    write_u32(&mut f, encoded.uncompressed_len)?;
+    write_len(&mut f, encoded.data.len())?;
+    f.write_all(&encoded.data)?;
+
+    write_len(&mut f, diagnostics.dropped_islands.len())?;
+    for d in &diagnostics.dropped_islands {
+        write_u32(&mut f, d.region_id)?;
+        write_u8(&mut f, d.reason as u8)?;
+        write_u32(&mut f, d.slots)?;
+        write_u32(&mut f, d.required)?;
+        for &count in &d.outcomes {
+            write_u32(&mut f, count)?;
+        }
+    }
+    f.flush()?;
+    eprintln!("Saved city diagnostics to {path}");
+    Ok(())
+}
+
+/// Read a sidecar written by [`save_city_diagnostics`].
+///
+/// # Errors
+///
+/// Any error reading the file, or [`InvalidData`](io::ErrorKind::InvalidData)
+/// if it is not a valid sidecar.
+pub fn load_city_diagnostics(path: &str) -> io::Result<CityDiagnostics> {
+    let mut f = BufReader::new(File::open(path)?);
+    let mut magic = [0u8; 4];
+    f.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a WGCD file",
+        ));
+    }
+    let version = read_u8(&mut f)?;
+    if version != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported city diagnostics version {version}"),
+        ));
+    }
+    let width = read_u32(&mut f)?;
+    let height = read_u32(&mut f)?;
+
+    let uncompressed_len = read_u32(&mut f)?;
+    if u64::from(uncompressed_len) != u64::from(width) * u64::from(height) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("outcome raster has {uncompressed_len} tiles, expected {width}x{height}"),
+        ));
+    }
+    let mut data = vec![0u8; read_u32(&mut f)? as usize];
+    f.read_exact(&mut data)?;
+    let outcomes = decompress(&EncodedChunk {
+        data,
+        uncompressed_len,
+    })?;
+
+    let num_islands = read_u32(&mut f)?;
+    let mut dropped_islands = Vec::with_capacity(num_islands as usize);
+    for _ in 0..num_islands {
+        let region_id = read_u32(&mut f)?;
+        let reason = IslandDropReason::from_u8(read_u8(&mut f)?);
+        let slots = read_u32(&mut f)?;
+        let required = read_u32(&mut f)?;
+        let mut outcomes = [0u32; SlotOutcome::COUNT];
+        for count in &mut outcomes {
+            *count = read_u32(&mut f)?;
+        }
+        dropped_islands.push(IslandDrop {
+            region_id,
+            reason,
+            slots,
+            required,
+            outcomes,
+        });
+    }
+
+    Ok(CityDiagnostics {
+        width,
+        height,
+        outcomes,
+        dropped_islands,
+    })
+}
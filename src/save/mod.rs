@@ -0,0 +1,1600 @@
+//! World file I/O -- chunked binary format.
+//!
+//! ## Format version 13
+//!
+//! Header layout (in order):
+//!
+//! ```text
+//! magic(4) · version(1) · config · width(2) · height(2) · chunk_size(2)
+//! · chunks_x(2) · chunks_y(2) · num_cities(4) · city_slots · city_resources
+//! · num_villages(4) · villages · metadata · lod_levels · islands
+//! · city_regions · city_sites · city_distances · sectors · names
+//! · village_rules · city_placement
+//! · lod_index · chunk_index · lod_data · chunk_data · chunk_summaries
+//! ```
+//!
+//! Each chunk index entry is followed by the file offset of that chunk's
+//! [`ChunkSummary`].
+//!
+//! Older versions lack trailing header sections:
+//! - version 1 has no `metadata`;
+//! - versions 1-2 have no `lod_levels`, `lod_index` or `lod_data`;
+//! - versions 1-3 have no summary offsets in the chunk index and no
+//!   `chunk_summaries`;
+//! - versions 1-4 have no `islands` registry;
+//! - versions 1-5 have no `city_regions`;
+//! - versions 1-6 have no `city_sites`;
+//! - versions 1-7 store 8 bytes per tile, without `territory`;
+//! - versions 1-8 have no `city_distances`;
+//! - versions 1-9 have no `sectors`;
+//! - versions 1-10 have no `names`;
+//! - versions 1-11 have no `village_rules`;
+//! - versions 1-12 have no `city_placement`.
+//!
+//! Each chunk stores 12 bytes per tile (terrain, elevation, region label,
+//! biome, [`territory`](crate::territory)). Per-city [`CityResources`](crate::biome::CityResources),
+//! [`CitySite`] descriptions, sea neighbours ([`CityDistances`]), the
+//! ocean [`SectorGrid`] and the [`names`](crate::names) of islands, seas and
+//! villages are stored in the header. The [`lod`](crate::lod) pyramid levels use the
+//! same index entry layout and store 4 bytes per pixel (terrain,
+//! elevation, biome).
+//!
+//! Existing files can be patched chunk by chunk with
+//! [`ChunkedWorldWriter`] and rewritten without dead space by
+//! [`compact_world`].
+
+mod diagnostics;
+mod summary;
+mod writer;
+
+pub use diagnostics::{city_diagnostics_path, load_city_diagnostics, save_city_diagnostics};
+pub use summary::ChunkSummary;
+pub use writer::{compact_world, ChunkedWorldWriter};
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::biome::{CityResources, ResourceArea};
+use crate::city::{CityPlacement, CitySite, CoastType, Facing};
+use crate::city_diagnostics::CityDiagnostics;
+use crate::config::WorldConfig;
+use crate::coords::MapGeometry;
+use crate::island::{self, BoundingBox, Island};
+use crate::lod::{self, LodInfo, LodLevel};
+use crate::metadata::{GenerationMetadata, PhaseTiming, WorldCounts};
+use crate::names::NameStyle;
+use crate::navigation::{CityDistances, SeaLink};
+use crate::sector::{self, SectorGrid, DEFAULT_SECTOR_SIZE};
+use crate::terrain::Terrain;
+use crate::territory::UNOWNED;
+use crate::village::{TradeResource, Village, VillageTrade};
+
+// ---------------------------------------------------------------------------
+// Magic & version
+// ---------------------------------------------------------------------------
+
+const MAGIC: &[u8; 4] = b"WGCH";
+/// Current format version. Readers accept every version from 1 up to this.
+pub const FORMAT_VERSION: u8 = 13;
+
+/// Bytes per tile in an uncompressed chunk.
+const TILE_BYTES: usize = 12;
+
+/// Bytes per tile in an uncompressed chunk of format versions 1-7.
+const LEGACY_TILE_BYTES: usize = 8;
+
+/// Bytes per pixel in an uncompressed LOD level.
+const LOD_PIXEL_BYTES: usize = 4;
+
+/// Bytes per index entry: `offset(8) · compressed_len(4) · uncompressed_len(4)`.
+const INDEX_ENTRY_LEN: u64 = 16;
+
+/// Bytes per chunk index entry (version 4+): an index entry followed by
+/// `summary_offset(8)`.
+const CHUNK_INDEX_ENTRY_LEN: u64 = INDEX_ENTRY_LEN + 8;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Everything produced by the generation pipeline, ready to be serialized.
+pub struct WorldData {
+    pub config: WorldConfig,
+    pub width: u32,
+    pub height: u32,
+    /// Row-major elevation grid (f32, sufficient for rendering).
+    pub elevation: Vec<f32>,
+    /// Row-major terrain type (`Terrain::to_u8()`).
+    pub terrain: Vec<u8>,
+    /// Row-major region labels (flood-fill IDs).
+    pub region_labels: Vec<u32>,
+    /// `(x, y)` world coordinates of every city slot.
+    pub city_slots: Vec<(u32, u32)>,
+    /// Region label (island id) of every city slot, parallel to `city_slots`.
+    pub city_regions: Vec<u32>,
+    /// Row-major biome classification (`Biome::to_u8()`).
+    pub biomes: Vec<u8>,
+    /// Row-major owning city (see [`territory`](crate::territory)).
+    pub territory: Vec<u32>,
+    /// Per-city aggregated resource profile, parallel to `city_slots`.
+    pub city_resources: Vec<CityResources>,
+    /// Per-city surroundings (facing, harbor, shore), parallel to `city_slots`.
+    pub city_sites: Vec<CitySite>,
+    /// Sea neighbours of every city; empty unless `CITY_DISTANCE_RADIUS`
+    /// is set.
+    pub city_distances: CityDistances,
+    /// Numbered ocean sectors of `config.sector_size` tiles.
+    pub sectors: SectorGrid,
+    /// All villages, sorted by (region_id, y, x).
+    pub villages: Vec<Village>,
+    /// Generated name of every village, parallel to `villages`; empty
+    /// unless `VILLAGE_NAMES` is set.
+    pub village_names: Vec<String>,
+    /// Provenance of this world (generator version, timings, counts).
+    pub metadata: GenerationMetadata,
+    /// Island registry, sorted by id.
+    pub islands: Vec<Island>,
+    /// Placement outcome of every tile; only produced with
+    /// `CITY_DIAGNOSTICS` and never stored in the world file (see
+    /// [`save_city_diagnostics`]).
+    pub city_diagnostics: Option<CityDiagnostics>,
+}
+
+/// A single decompressed chunk.
+#[derive(Clone)]
+pub struct ChunkData {
+    pub width: u32,
+    pub height: u32,
+    pub terrain: Vec<u8>,
+    pub elevation: Vec<f32>,
+    pub region_labels: Vec<u32>,
+    /// Biome classification per tile (`Biome::to_u8()`).
+    pub biomes: Vec<u8>,
+    /// Owning city per tile (see [`territory`](crate::territory)); all
+    /// [`UNOWNED`](crate::territory::UNOWNED) before format version 8.
+    pub territory: Vec<u32>,
+}
+
+/// Metadata stored at the beginning of the chunked file.
+pub struct ChunkedWorldHeader {
+    pub config: WorldConfig,
+    pub width: u32,
+    pub height: u32,
+    pub chunks_x: u32,
+    pub chunks_y: u32,
+    pub city_slots: Vec<(u32, u32)>,
+    /// Region label of every city slot, parallel to `city_slots`. `None`
+    /// before version 6; look the labels up in the chunks instead.
+    pub city_regions: Option<Vec<u32>>,
+    /// File format version.
+    pub format_version: u8,
+    /// Per-city aggregated resource profiles, parallel to `city_slots`.
+    pub city_resources: Vec<CityResources>,
+    /// Per-city surroundings, parallel to `city_slots`. `None` before
+    /// version 7.
+    pub city_sites: Option<Vec<CitySite>>,
+    /// Sea neighbours of every city. `None` before version 9; radius `0`
+    /// if they were not computed.
+    pub city_distances: Option<CityDistances>,
+    /// Ocean sector grid. `None` before version 10.
+    pub sectors: Option<SectorGrid>,
+    /// All villages stored in the world file. Always populated for version-1 files.
+    pub villages: Vec<Village>,
+    /// Name of every village, parallel to `villages`. `None` before version
+    /// 11; empty if villages were not named.
+    pub village_names: Option<Vec<String>>,
+    /// Generation metadata. `None` for version-1 files.
+    pub metadata: Option<GenerationMetadata>,
+    /// Stored LOD pyramid levels, finest first. Empty before version 3.
+    pub lod_levels: Vec<LodInfo>,
+    /// Island registry, sorted by id. `None` before version 5; use
+    /// [`island::discover_islands`] to rebuild it.
+    pub islands: Option<Vec<Island>>,
+}
+
+/// Random-access reader for the chunked world file.
+pub struct ChunkedWorldReader {
+    pub header: ChunkedWorldHeader,
+    index: Vec<ChunkIndexEntry>,
+    /// File offset of the first chunk index entry.
+    index_offset: u64,
+    /// One entry per [`ChunkedWorldHeader::lod_levels`] level.
+    lod_index: Vec<ChunkIndexEntry>,
+    /// Row-major per-chunk summaries. Empty before version 4.
+    summaries: Vec<ChunkSummary>,
+    path: String,
+}
+
+#[derive(Clone, Copy)]
+struct ChunkIndexEntry {
+    offset: u64,
+    compressed_len: u32,
+    uncompressed_len: u32,
+}
+
+// ---------------------------------------------------------------------------
+// Builder
+// ---------------------------------------------------------------------------
+
+/// Convert raw generation output into a flat [`WorldData`] for serialization.
+///
+/// Also looks up each city's region and builds the island registry and the
+/// sector grid from the flattened grids.
+#[allow(clippy::too_many_arguments)]
+pub fn build_world_data(
+    elevation: Vec<Vec<f64>>,
+    terrain: Vec<Vec<Terrain>>,
+    region_labels: Vec<Vec<usize>>,
+    city_slots: &[(usize, usize)],
+    biomes: Vec<Vec<u8>>,
+    territory: Vec<Vec<u32>>,
+    city_resources: Vec<CityResources>,
+    city_sites: Vec<CitySite>,
+    villages: Vec<Village>,
+    config: WorldConfig,
+) -> WorldData {
+    let height = elevation.len() as u32;
+    let width = elevation[0].len() as u32;
+
+    let flat_elevation: Vec<f32> = elevation.iter().flatten().map(|&e| e as f32).collect();
+    let flat_terrain: Vec<u8> = terrain.iter().flatten().map(|t| t.to_u8()).collect();
+    let flat_regions: Vec<u32> = region_labels.iter().flatten().map(|&r| r as u32).collect();
+    let flat_biomes: Vec<u8> = biomes.into_iter().flatten().collect();
+    let flat_territory: Vec<u32> = territory.into_iter().flatten().collect();
+    let city_slots = city_slots
+        .iter()
+        .map(|&(x, y)| (x as u32, y as u32))
+        .collect();
+
+    let mut data = WorldData {
+        config,
+        width,
+        height,
+        elevation: flat_elevation,
+        terrain: flat_terrain,
+        region_labels: flat_regions,
+        city_slots,
+        city_regions: Vec::new(),
+        biomes: flat_biomes,
+        territory: flat_territory,
+        city_resources,
+        city_sites,
+        city_distances: CityDistances::default(),
+        sectors: SectorGrid::default(),
+        villages,
+        village_names: Vec::new(),
+        metadata: GenerationMetadata::default(),
+        islands: Vec::new(),
+        city_diagnostics: None,
+    };
+    data.city_regions = data.city_region_labels();
+    data.islands = island::compute_islands(&data);
+    data.sectors = sector::compute_sectors(&data);
+    data
+}
+
+// ---------------------------------------------------------------------------
+// Writing
+// ---------------------------------------------------------------------------
+
+/// A compressed chunk payload, ready to be written to disk.
+pub(crate) struct EncodedChunk {
+    pub(crate) data: Vec<u8>,
+    pub(crate) uncompressed_len: u32,
+}
+
+/// Serialize a [`WorldData`] into the chunked binary format.
+pub fn save_world_chunked(path: &str, data: &WorldData) -> io::Result<()> {
+    let header = ChunkedWorldHeader::from_data(data);
+    write_world_file(path, &header, data)?;
+    eprintln!("Saved chunked world to {path}");
+    Ok(())
+}
+
+/// [`save_world_chunked`] through a temporary file next to `path`, synced
+/// and renamed over it once complete, so that `path` may be the file `data`
+/// was read from and a crash never leaves it half-written.
+///
+/// # Errors
+///
+/// Any error writing, syncing or renaming the temporary file (which is
+/// then removed).
+pub fn save_world_replacing(path: &str, data: &WorldData) -> io::Result<()> {
+    let tmp_path = format!("{path}.tmp");
+    let written = save_world_chunked(&tmp_path, data).and_then(|()| rename_synced(&tmp_path, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Source of the compressed payloads written by [`write_world_file`].
+///
+/// Implemented by [`WorldData`] (encodes on the fly) and by
+/// [`ChunkedWorldReader`] (copies stored payloads verbatim, for compaction).
+pub(crate) trait Payloads {
+    /// Compressed payload of LOD level `level` (index into `lod_levels`).
+    fn lod(&self, level: usize) -> io::Result<EncodedChunk>;
+    /// Compressed payload and summary of chunk `(cx, cy)`.
+    fn chunk(&self, cx: u32, cy: u32) -> io::Result<(EncodedChunk, ChunkSummary)>;
+}
+
+impl Payloads for WorldData {
+    fn lod(&self, level: usize) -> io::Result<EncodedChunk> {
+        let factor = lod::lod_levels_for(self.width, self.height)[level].factor;
+        encode_lod(&LodLevel::from_data(self, factor))
+    }
+
+    fn chunk(&self, cx: u32, cy: u32) -> io::Result<(EncodedChunk, ChunkSummary)> {
+        let chunk = self.extract_chunk(cx, cy);
+        Ok((encode_chunk(&chunk)?, ChunkSummary::from_chunk(&chunk)))
+    }
+}
+
+impl Payloads for ChunkedWorldReader {
+    fn lod(&self, level: usize) -> io::Result<EncodedChunk> {
+        self.load_raw(&self.lod_index[level])
+    }
+
+    fn chunk(&self, cx: u32, cy: u32) -> io::Result<(EncodedChunk, ChunkSummary)> {
+        let summary = match self.chunk_summary(cx, cy) {
+            Some(summary) => summary.clone(),
+            None => ChunkSummary::from_chunk(&self.load_chunk(cx, cy)?),
+        };
+        Ok((self.load_raw_chunk(cx, cy)?, summary))
+    }
+}
+
+/// Write a complete world file: header, indices and payloads.
+///
+/// Shared by [`save_world_chunked`] and [`compact_world`].
+pub(crate) fn write_world_file(
+    path: &str,
+    header: &ChunkedWorldHeader,
+    payloads: &impl Payloads,
+) -> io::Result<()> {
+    let num_lods = header.lod_levels.len();
+    let num_chunks = (header.chunks_x * header.chunks_y) as usize;
+
+    let mut f = BufWriter::new(File::create(path)?);
+    write_header(&mut f, header)?;
+
+    // -- LOD + chunk index (placeholder, back-patched later) ----------------
+    let index_offset = f.stream_position()?;
+    let index_len = num_lods as u64 * INDEX_ENTRY_LEN + num_chunks as u64 * CHUNK_INDEX_ENTRY_LEN;
+    let index_len = usize::try_from(index_len).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("chunk index of {index_len} bytes does not fit in memory"),
+        )
+    })?;
+    f.write_all(&vec![0u8; index_len])?;
+
+    let mut entries: Vec<ChunkIndexEntry> = Vec::with_capacity(num_lods + num_chunks);
+    let mut append = |f: &mut BufWriter<File>, payload: EncodedChunk| -> io::Result<()> {
+        let compressed_len = u32::try_from(payload.data.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "payload compresses to more than 4 GiB",
+            )
+        })?;
+        let offset = f.stream_position()?;
+        f.write_all(&payload.data)?;
+        entries.push(ChunkIndexEntry {
+            offset,
+            compressed_len,
+            uncompressed_len: payload.uncompressed_len,
+        });
+        Ok(())
+    };
+
+    // -- LOD data -----------------------------------------------------------
+    for level in 0..num_lods {
+        append(&mut f, payloads.lod(level)?)?;
+    }
+
+    // -- Chunk data ---------------------------------------------------------
+    let mut summaries = Vec::with_capacity(num_chunks);
+    for cy in 0..header.chunks_y {
+        for cx in 0..header.chunks_x {
+            let (payload, summary) = payloads.chunk(cx, cy)?;
+            append(&mut f, payload)?;
+            summaries.push(summary);
+        }
+    }
+
+    // -- Chunk summaries ----------------------------------------------------
+    let mut summary_offsets = Vec::with_capacity(num_chunks);
+    for summary in &summaries {
+        summary_offsets.push(f.stream_position()?);
+        summary::write_summary(&mut f, summary)?;
+    }
+
+    // -- Back-patch index ---------------------------------------------------
+    f.seek(SeekFrom::Start(index_offset))?;
+    let (lod_entries, chunk_entries) = entries.split_at(num_lods);
+    for entry in lod_entries {
+        write_index_entry(&mut f, entry)?;
+    }
+    for (entry, &summary_offset) in chunk_entries.iter().zip(&summary_offsets) {
+        write_index_entry(&mut f, entry)?;
+        write_u64(&mut f, summary_offset)?;
+    }
+
+    f.flush()
+}
+
+/// Move the finished file `tmp_path` over `path`.
+///
+/// The file is synced before the rename and its directory after, so a
+/// crash leaves either the old or the new file at `path`.
+pub(crate) fn rename_synced(tmp_path: &str, path: &str) -> io::Result<()> {
+    File::open(tmp_path)?.sync_all()?;
+    fs::rename(tmp_path, path)?;
+    sync_parent_dir(path)
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &str) -> io::Result<()> {
+    let dir = match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be opened as files here; the rename is as durable as
+/// the platform makes it.
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn sync_parent_dir(_path: &str) -> io::Result<()> {
+    Ok(())
+}
+
+/// Write everything that precedes the chunk index.
+fn write_header(f: &mut impl Write, h: &ChunkedWorldHeader) -> io::Result<()> {
+    f.write_all(MAGIC)?;
+    write_u8(f, FORMAT_VERSION)?;
+    write_config(f, &h.config)?;
+    write_u32_as_u16(f, h.width)?;
+    write_u32_as_u16(f, h.height)?;
+    write_u16(f, h.config.chunk_size)?;
+    write_u32_as_u16(f, h.chunks_x)?;
+    write_u32_as_u16(f, h.chunks_y)?;
+
+    // Cities
+    write_len(f, h.city_slots.len())?;
+    for &(x, y) in &h.city_slots {
+        write_u32_as_u16(f, x)?;
+        write_u32_as_u16(f, y)?;
+    }
+
+    // Per-city resource profiles
+    for cr in &h.city_resources {
+        write_i16(f, cr.wood)?;
+        write_i16(f, cr.stone)?;
+        write_i16(f, cr.food)?;
+        write_i16(f, cr.metal)?;
+        write_i16(f, cr.favor)?;
+        write_u8(f, cr.gold_nodes)?;
+        write_u8(f, cr.dominant_biome)?;
+    }
+
+    // Villages
+    // Layout per village: x(2) y(2) region_id(4) biome(1) offers(1) demands(1) = 11 bytes
+    write_len(f, h.villages.len())?;
+    for v in &h.villages {
+        write_u16(f, v.x)?;
+        write_u16(f, v.y)?;
+        write_u32(f, v.region_id)?;
+        write_u8(f, v.biome)?;
+        write_u8(f, v.trade.offers.to_u8())?;
+        write_u8(f, v.trade.demands.to_u8())?;
+    }
+
+    // Metadata
+    let metadata = h.metadata.clone().unwrap_or_default();
+    write_metadata(f, &metadata)?;
+
+    // LOD levels
+    let num_lods = u8::try_from(h.lod_levels.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} LOD levels do not fit in 1 byte", h.lod_levels.len()),
+        )
+    })?;
+    write_u8(f, num_lods)?;
+    for level in &h.lod_levels {
+        write_u32(f, level.factor)?;
+        write_u32_as_u16(f, level.width)?;
+        write_u32_as_u16(f, level.height)?;
+    }
+
+    // Island registry
+    write_islands(f, h.islands.as_deref().unwrap_or(&[]))?;
+
+    // City regions, parallel to the city slots
+    let city_regions = h.city_regions.as_deref().unwrap_or(&[]);
+    for i in 0..h.city_slots.len() {
+        write_u32(f, city_regions.get(i).copied().unwrap_or(0))?;
+    }
+
+    // City sites, parallel to the city slots
+    // Layout per city: facing(1) harbor_quality(1) water_body_size(4) coast(1)
+    // elevation(2) hinterland(2) = 11 bytes
+    let city_sites = h.city_sites.as_deref().unwrap_or(&[]);
+    for i in 0..h.city_slots.len() {
+        let site = city_sites.get(i);
+        write_u8(f, site.map_or(0, |s| s.facing.to_u8()))?;
+        write_u8(f, site.map_or(0, |s| s.harbor_quality))?;
+        write_u32(f, site.map_or(0, |s| s.water_body_size))?;
+        write_u8(f, site.map_or(0, |s| s.coast.to_u8()))?;
+        write_u16(f, encode_elevation(site.map_or(0.0, |s| s.elevation)))?;
+        write_u16(f, site.map_or(0, |s| s.hinterland))?;
+    }
+
+    // City sea neighbours: radius(4), then per city link_count(4) and its
+    // links, each to(4) distance(4) travel_time(4) = 12 bytes
+    let distances = h.city_distances.clone().unwrap_or_default();
+    write_f32(f, distances.radius)?;
+    for i in 0..h.city_slots.len() {
+        let links = distances.neighbors(i);
+        write_len(f, links.len())?;
+        for link in links {
+            write_u32(f, link.city)?;
+            write_f32(f, link.distance)?;
+            write_f32(f, link.travel_time)?;
+        }
+    }
+
+    // Ocean sectors
+    let sectors = h.sectors.clone().unwrap_or_default();
+    write_sectors(f, &sectors)?;
+
+    // Names: style(1), then islands, seas and villages, each as count(4)
+    // and one string per entry
+    write_u8(f, h.config.name_style.to_u8())?;
+    let islands = h.islands.as_deref().unwrap_or(&[]);
+    write_names(f, islands.iter().map(|i| i.name.as_str()))?;
+    write_names(f, sectors.sectors().iter().map(|s| s.name.as_str()))?;
+    let village_names = h.village_names.as_deref().unwrap_or(&[]);
+    write_names(f, village_names.iter().map(String::as_str))?;
+
+    // Village rules: min_ocean_distance(4) · spacing(4)
+    write_u32(f, h.config.village_min_ocean_distance)?;
+    write_u32(f, h.config.village_spacing)?;
+
+    // City placement mode
+    write_u8(f, h.config.city_placement.to_u8())?;
+    Ok(())
+}
+
+fn write_index_entry(w: &mut impl Write, entry: &ChunkIndexEntry) -> io::Result<()> {
+    write_u64(w, entry.offset)?;
+    write_u32(w, entry.compressed_len)?;
+    write_u32(w, entry.uncompressed_len)
+}
+
+/// Pack a LOD level into its per-pixel wire layout and Deflate-compress it.
+fn encode_lod(level: &LodLevel) -> io::Result<EncodedChunk> {
+    let mut raw = Vec::with_capacity(level.terrain.len() * LOD_PIXEL_BYTES);
+    for i in 0..level.terrain.len() {
+        raw.push(level.terrain[i]);
+        raw.extend_from_slice(&encode_elevation(level.elevation[i]).to_le_bytes());
+        raw.push(level.biomes[i]);
+    }
+    compress(&raw)
+}
+
+/// Pack a chunk into its per-tile wire layout and Deflate-compress it.
+pub(crate) fn encode_chunk(chunk: &ChunkData) -> io::Result<EncodedChunk> {
+    let pixels = (chunk.width * chunk.height) as usize;
+    let mut raw = Vec::with_capacity(pixels * TILE_BYTES);
+    for i in 0..pixels {
+        raw.push(chunk.terrain[i]);
+        raw.extend_from_slice(&encode_elevation(chunk.elevation[i]).to_le_bytes());
+        raw.extend_from_slice(&chunk.region_labels[i].to_le_bytes());
+        raw.push(chunk.biomes[i]);
+        raw.extend_from_slice(&chunk.territory[i].to_le_bytes());
+    }
+    compress(&raw)
+}
+
+/// Quantize an elevation to the 16-bit fixed point stored on disk.
+///
+/// Rounds rather than truncates so that decode -> encode is lossless.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_elevation(e: f32) -> u16 {
+    (e.clamp(0.0, 1.0) * 65535.0).round() as u16
+}
+
+fn decode_elevation(v: u16) -> f32 {
+    f32::from(v) / 65535.0
+}
+
+/// `e` as it reads back after a save/load round trip.
+pub(crate) fn stored_elevation(e: f32) -> f32 {
+    decode_elevation(encode_elevation(e))
+}
+
+fn compress(raw: &[u8]) -> io::Result<EncodedChunk> {
+    let uncompressed_len = u32::try_from(raw.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("payload of {} bytes does not fit in 4 bytes", raw.len()),
+        )
+    })?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(raw)?;
+    Ok(EncodedChunk {
+        data: encoder.finish()?,
+        uncompressed_len,
+    })
+}
+
+impl ChunkedWorldHeader {
+    /// Build the file header for a freshly generated world.
+    #[must_use]
+    pub fn from_data(data: &WorldData) -> Self {
+        let chunk_size = u32::from(data.config.chunk_size);
+        Self {
+            config: data.config.clone(),
+            width: data.width,
+            height: data.height,
+            chunks_x: data.width.div_ceil(chunk_size),
+            chunks_y: data.height.div_ceil(chunk_size),
+            city_slots: data.city_slots.clone(),
+            city_regions: Some(data.city_regions.clone()),
+            format_version: FORMAT_VERSION,
+            city_resources: data.city_resources.clone(),
+            city_sites: Some(data.city_sites.clone()),
+            city_distances: Some(data.city_distances.clone()),
+            sectors: Some(data.sectors.clone()),
+            villages: data.villages.clone(),
+            village_names: Some(data.village_names.clone()),
+            metadata: Some(data.metadata.clone()),
+            lod_levels: lod::lod_levels_for(data.width, data.height),
+            islands: Some(data.islands.clone()),
+        }
+    }
+
+    /// Map and chunk dimensions for coordinate conversions.
+    #[must_use]
+    pub fn geometry(&self) -> MapGeometry {
+        MapGeometry::new(self.width, self.height, u32::from(self.config.chunk_size))
+    }
+
+    /// Width and height in tiles of the chunk at `(cx, cy)`.
+    ///
+    /// Edge chunks are smaller when the map size is not a multiple of the
+    /// chunk size.
+    #[must_use]
+    pub fn chunk_dims(&self, cx: u32, cy: u32) -> (u32, u32) {
+        let chunk_size = u32::from(self.config.chunk_size);
+        (
+            chunk_size.min(self.width - cx * chunk_size),
+            chunk_size.min(self.height - cy * chunk_size),
+        )
+    }
+}
+
+impl WorldData {
+    /// Region label under every city slot, parallel to `city_slots`.
+    #[must_use]
+    pub fn city_region_labels(&self) -> Vec<u32> {
+        self.city_slots
+            .iter()
+            .map(|&(x, y)| self.region_labels[(y * self.width + x) as usize])
+            .collect()
+    }
+
+    /// Copy the tiles of chunk `(cx, cy)` out of the flat world grids.
+    #[must_use]
+    pub fn extract_chunk(&self, cx: u32, cy: u32) -> ChunkData {
+        let chunk_size = u32::from(self.config.chunk_size);
+        let cw = chunk_size.min(self.width - cx * chunk_size);
+        let ch = chunk_size.min(self.height - cy * chunk_size);
+        let pixels = (cw * ch) as usize;
+
+        let mut chunk = ChunkData {
+            width: cw,
+            height: ch,
+            terrain: Vec::with_capacity(pixels),
+            elevation: Vec::with_capacity(pixels),
+            region_labels: Vec::with_capacity(pixels),
+            biomes: Vec::with_capacity(pixels),
+            territory: Vec::with_capacity(pixels),
+        };
+        for ly in 0..ch {
+            let gy = (cy * chunk_size + ly) as usize;
+            let row = gy * self.width as usize + (cx * chunk_size) as usize;
+            let span = row..row + cw as usize;
+            chunk.terrain.extend_from_slice(&self.terrain[span.clone()]);
+            chunk
+                .elevation
+                .extend_from_slice(&self.elevation[span.clone()]);
+            chunk
+                .region_labels
+                .extend_from_slice(&self.region_labels[span.clone()]);
+            chunk.biomes.extend_from_slice(&self.biomes[span.clone()]);
+            chunk.territory.extend_from_slice(&self.territory[span]);
+        }
+        chunk
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reading
+// ---------------------------------------------------------------------------
+
+/// Read the seed from an existing world file without loading the full index.
+///
+/// Returns `None` if the file is missing or invalid.
+pub fn read_seed_from_file(path: &str) -> Option<u32> {
+    let mut f = BufReader::new(File::open(path).ok()?);
+
+    let mut magic = [0u8; 4];
+    f.read_exact(&mut magic).ok()?;
+    if &magic != MAGIC {
+        return None;
+    }
+
+    let version = read_u8(&mut f).ok()?;
+    if version == 0 || version > FORMAT_VERSION {
+        return None;
+    }
+
+    let config = read_config(&mut f).ok()?;
+    Some(config.seed)
+}
+
+impl ChunkedWorldReader {
+    /// Open a chunked world file and read its header + index.
+    pub fn open(path: &str) -> io::Result<Self> {
+        let mut f = BufReader::new(File::open(path)?);
+        let header = read_header(&mut f)?;
+
+        // LOD index — empty before version 3.
+        let mut lod_index = Vec::with_capacity(header.lod_levels.len());
+        for _ in 0..header.lod_levels.len() {
+            lod_index.push(read_index_entry(&mut f)?);
+        }
+
+        // Chunk index — summary offsets added in version 4.
+        let has_summaries = header.format_version >= 4;
+        let mut pos = f.stream_position()?;
+        let index_offset = pos;
+        let num_chunks = (header.chunks_x * header.chunks_y) as usize;
+        let mut index = Vec::with_capacity(num_chunks);
+        let mut summary_offsets = Vec::new();
+        for _ in 0..num_chunks {
+            index.push(read_index_entry(&mut f)?);
+            pos += INDEX_ENTRY_LEN;
+            if has_summaries {
+                summary_offsets.push(read_u64(&mut f)?);
+                pos += 8;
+            }
+        }
+
+        // Chunk summaries. Freshly written files store them contiguously;
+        // patched chunks have theirs appended at the end of the file.
+        let mut summaries = Vec::with_capacity(summary_offsets.len());
+        for offset in summary_offsets {
+            if offset != pos {
+                f.seek(SeekFrom::Start(offset))?;
+            }
+            let summary = summary::read_summary(&mut f)?;
+            pos = offset + summary.encoded_len();
+            summaries.push(summary);
+        }
+
+        Ok(Self {
+            header,
+            index,
+            index_offset,
+            lod_index,
+            summaries,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Path of the file this reader was opened from.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Summary of chunk `(cx, cy)`.
+    ///
+    /// Returns `None` for files older than version 4 or if the coordinates
+    /// are outside the chunk grid.
+    #[must_use]
+    pub fn chunk_summary(&self, cx: u32, cy: u32) -> Option<&ChunkSummary> {
+        let idx = self.index_of(cx, cy).ok()?;
+        self.summaries.get(idx)
+    }
+
+    /// Position of `(cx, cy)` in the row-major chunk index.
+    fn index_of(&self, cx: u32, cy: u32) -> io::Result<usize> {
+        let h = &self.header;
+        if cx >= h.chunks_x || cy >= h.chunks_y {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid chunk ({cx},{cy}) for grid {}x{}",
+                    h.chunks_x, h.chunks_y
+                ),
+            ));
+        }
+        Ok((cy * h.chunks_x + cx) as usize)
+    }
+
+    /// Read the compressed payload of chunk `(cx, cy)` without decoding it.
+    pub(crate) fn load_raw_chunk(&self, cx: u32, cy: u32) -> io::Result<EncodedChunk> {
+        self.load_raw(&self.index[self.index_of(cx, cy)?])
+    }
+
+    /// Read the compressed payload described by `entry`.
+    fn load_raw(&self, entry: &ChunkIndexEntry) -> io::Result<EncodedChunk> {
+        let mut f = BufReader::new(File::open(&self.path)?);
+        f.seek(SeekFrom::Start(entry.offset))?;
+
+        let mut data = vec![0u8; entry.compressed_len as usize];
+        f.read_exact(&mut data)?;
+        Ok(EncodedChunk {
+            data,
+            uncompressed_len: entry.uncompressed_len,
+        })
+    }
+
+    /// Decompress and return LOD level `level` (index into
+    /// [`ChunkedWorldHeader::lod_levels`]).
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if `level` is out of
+    /// range, or any error reading or decompressing the level.
+    pub fn load_lod_level(&self, level: usize) -> io::Result<LodLevel> {
+        let (Some(&info), Some(entry)) =
+            (self.header.lod_levels.get(level), self.lod_index.get(level))
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid LOD level {level}"),
+            ));
+        };
+        let raw = decompress(&self.load_raw(entry)?)?;
+
+        let pixels = (info.width * info.height) as usize;
+        let mut level = LodLevel {
+            info,
+            terrain: Vec::with_capacity(pixels),
+            elevation: Vec::with_capacity(pixels),
+            biomes: Vec::with_capacity(pixels),
+        };
+        let mut cursor = Cursor::new(&raw);
+        for _ in 0..pixels {
+            level.terrain.push(read_u8(&mut cursor)?);
+            level
+                .elevation
+                .push(decode_elevation(read_u16(&mut cursor)?));
+            level.biomes.push(read_u8(&mut cursor)?);
+        }
+        Ok(level)
+    }
+
+    /// Decompress and return the chunk at `(cx, cy)`.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if `(cx, cy)` is outside
+    /// the chunk grid, or any error reading or decompressing the chunk.
+    pub fn load_chunk(&self, cx: u32, cy: u32) -> io::Result<ChunkData> {
+        let raw = decompress(&self.load_raw_chunk(cx, cy)?)?;
+
+        let (cw, ch) = self.header.chunk_dims(cx, cy);
+        let pixels = (cw * ch) as usize;
+
+        let mut terrain = Vec::with_capacity(pixels);
+        let mut elevation = Vec::with_capacity(pixels);
+        let mut region_labels = Vec::with_capacity(pixels);
+        let mut biomes = Vec::with_capacity(pixels);
+        let mut territory = Vec::with_capacity(pixels);
+
+        let has_territory = self.header.format_version >= 8;
+        let tile_bytes = if has_territory {
+            TILE_BYTES
+        } else {
+            LEGACY_TILE_BYTES
+        };
+        if raw.len() != pixels * tile_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "chunk ({cx},{cy}) holds {} bytes, expected {}",
+                    raw.len(),
+                    pixels * tile_bytes
+                ),
+            ));
+        }
+        let mut cursor = Cursor::new(&raw);
+        for _ in 0..pixels {
+            terrain.push(read_u8(&mut cursor)?);
+            elevation.push(decode_elevation(read_u16(&mut cursor)?));
+            region_labels.push(read_u32(&mut cursor)?);
+            biomes.push(read_u8(&mut cursor)?);
+            territory.push(if has_territory {
+                read_u32(&mut cursor)?
+            } else {
+                UNOWNED
+            });
+        }
+
+        Ok(ChunkData {
+            width: cw,
+            height: ch,
+            terrain,
+            elevation,
+            region_labels,
+            biomes,
+            territory,
+        })
+    }
+
+    /// Decompress every chunk and reassemble the full-resolution [`WorldData`].
+    ///
+    /// Header sections missing from older format versions are left at their
+    /// defaults (empty island registry, default metadata); see
+    /// [`migrate`](crate::migrate) for filling them in.
+    ///
+    /// # Errors
+    ///
+    /// Any error reading or decompressing a chunk.
+    pub fn load_world_data(&self) -> io::Result<WorldData> {
+        let h = &self.header;
+        let (width, height) = (h.width as usize, h.height as usize);
+        let chunk_size = h.config.chunk_size as usize;
+
+        let mut data = WorldData {
+            config: h.config.clone(),
+            width: h.width,
+            height: h.height,
+            elevation: vec![0.0; width * height],
+            terrain: vec![0; width * height],
+            region_labels: vec![0; width * height],
+            city_slots: h.city_slots.clone(),
+            city_regions: h.city_regions.clone().unwrap_or_default(),
+            biomes: vec![0; width * height],
+            territory: vec![UNOWNED; width * height],
+            city_resources: h.city_resources.clone(),
+            city_sites: h.city_sites.clone().unwrap_or_default(),
+            city_distances: h.city_distances.clone().unwrap_or_default(),
+            sectors: h.sectors.clone().unwrap_or_default(),
+            villages: h.villages.clone(),
+            village_names: h.village_names.clone().unwrap_or_default(),
+            metadata: h.metadata.clone().unwrap_or_default(),
+            islands: h.islands.clone().unwrap_or_default(),
+            city_diagnostics: None,
+        };
+
+        for cy in 0..h.chunks_y {
+            for cx in 0..h.chunks_x {
+                let chunk = self.load_chunk(cx, cy)?;
+                let cw = chunk.width as usize;
+                for ly in 0..chunk.height as usize {
+                    let src = ly * cw..(ly + 1) * cw;
+                    let row = (cy as usize * chunk_size + ly) * width + cx as usize * chunk_size;
+                    let dst = row..row + cw;
+                    data.terrain[dst.clone()].copy_from_slice(&chunk.terrain[src.clone()]);
+                    data.elevation[dst.clone()].copy_from_slice(&chunk.elevation[src.clone()]);
+                    data.region_labels[dst.clone()]
+                        .copy_from_slice(&chunk.region_labels[src.clone()]);
+                    data.biomes[dst.clone()].copy_from_slice(&chunk.biomes[src.clone()]);
+                    data.territory[dst].copy_from_slice(&chunk.territory[src]);
+                }
+            }
+        }
+        Ok(data)
+    }
+}
+
+fn decompress(encoded: &EncodedChunk) -> io::Result<Vec<u8>> {
+    let mut raw = vec![0u8; encoded.uncompressed_len as usize];
+    DeflateDecoder::new(&encoded.data[..]).read_exact(&mut raw)?;
+    Ok(raw)
+}
+
+fn read_index_entry(r: &mut impl Read) -> io::Result<ChunkIndexEntry> {
+    Ok(ChunkIndexEntry {
+        offset: read_u64(r)?,
+        compressed_len: read_u32(r)?,
+        uncompressed_len: read_u32(r)?,
+    })
+}
+
+/// Read and validate everything that precedes the chunk index.
+#[allow(clippy::too_many_lines)]
+fn read_header(f: &mut impl Read) -> io::Result<ChunkedWorldHeader> {
+    // Magic
+    let mut magic = [0u8; 4];
+    f.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a WGCH file",
+        ));
+    }
+
+    // Version
+    let version = read_u8(f)?;
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported format version {version}"),
+        ));
+    }
+
+    // Config
+    let mut config = read_config(f)?;
+    let width = u32::from(read_u16(f)?);
+    let height = u32::from(read_u16(f)?);
+    let chunk_size = read_u16(f)?;
+    config.chunk_size = chunk_size;
+    let chunks_x = u32::from(read_u16(f)?);
+    let chunks_y = u32::from(read_u16(f)?);
+
+    // Cities
+    let num_cities = read_u32(f)?;
+    let mut city_slots = Vec::with_capacity(num_cities as usize);
+    for _ in 0..num_cities {
+        let x = u32::from(read_u16(f)?);
+        let y = u32::from(read_u16(f)?);
+        city_slots.push((x, y));
+    }
+
+    // Per-city resource profiles
+    let mut city_resources = Vec::with_capacity(num_cities as usize);
+    for _ in 0..num_cities {
+        city_resources.push(CityResources {
+            wood: read_i16(f)?,
+            stone: read_i16(f)?,
+            food: read_i16(f)?,
+            metal: read_i16(f)?,
+            favor: read_i16(f)?,
+            gold_nodes: read_u8(f)?,
+            dominant_biome: read_u8(f)?,
+        });
+    }
+
+    // Villages — always present in format version 1.
+    let num_villages = read_u32(f)?;
+    let mut villages = Vec::with_capacity(num_villages as usize);
+    for _ in 0..num_villages {
+        villages.push(Village {
+            x: read_u16(f)?,
+            y: read_u16(f)?,
+            region_id: read_u32(f)?,
+            biome: read_u8(f)?,
+            trade: VillageTrade {
+                offers: TradeResource::from_u8(read_u8(f)?),
+                demands: TradeResource::from_u8(read_u8(f)?),
+            },
+        });
+    }
+
+    // Metadata — added in format version 2.
+    let metadata = if version >= 2 {
+        Some(read_metadata(f)?)
+    } else {
+        None
+    };
+
+    // LOD levels — added in format version 3.
+    let mut lod_levels = Vec::new();
+    if version >= 3 {
+        let num_levels = read_u8(f)?;
+        for _ in 0..num_levels {
+            lod_levels.push(LodInfo {
+                factor: read_u32(f)?,
+                width: u32::from(read_u16(f)?),
+                height: u32::from(read_u16(f)?),
+            });
+        }
+    }
+
+    // Island registry — added in format version 5.
+    let mut islands = if version >= 5 {
+        Some(read_islands(f)?)
+    } else {
+        None
+    };
+
+    // City regions — added in format version 6.
+    let city_regions = if version >= 6 {
+        let mut regions = Vec::with_capacity(num_cities as usize);
+        for _ in 0..num_cities {
+            regions.push(read_u32(f)?);
+        }
+        Some(regions)
+    } else {
+        None
+    };
+
+    // City sites — added in format version 7.
+    let city_sites = if version >= 7 {
+        let mut sites = Vec::with_capacity(num_cities as usize);
+        for _ in 0..num_cities {
+            sites.push(CitySite {
+                facing: Facing::from_u8(read_u8(f)?),
+                harbor_quality: read_u8(f)?,
+                water_body_size: read_u32(f)?,
+                coast: CoastType::from_u8(read_u8(f)?),
+                elevation: decode_elevation(read_u16(f)?),
+                hinterland: read_u16(f)?,
+            });
+        }
+        Some(sites)
+    } else {
+        None
+    };
+
+    // City sea neighbours — added in format version 9.
+    let city_distances = if version >= 9 {
+        let radius = read_f32(f)?;
+        let mut lists = Vec::with_capacity(num_cities as usize);
+        for _ in 0..num_cities {
+            let count = read_u32(f)?;
+            let mut links = Vec::with_capacity(count.min(num_cities) as usize);
+            for _ in 0..count {
+                links.push(SeaLink {
+                    city: read_u32(f)?,
+                    distance: read_f32(f)?,
+                    travel_time: read_f32(f)?,
+                });
+            }
+            lists.push(links);
+        }
+        Some(CityDistances::new(radius, lists))
+    } else {
+        None
+    };
+
+    // Ocean sectors — added in format version 10.
+    let mut sectors = if version >= 10 {
+        let grid = read_sectors(f, width, height)?;
+        config.sector_size = u16::try_from(grid.size).unwrap_or(u16::MAX);
+        Some(grid)
+    } else {
+        None
+    };
+
+    // Names — added in format version 11.
+    let village_names = if version >= 11 {
+        config.name_style = NameStyle::from_u8(read_u8(f)?);
+        let island_names = read_names(f)?;
+        let sea_names = read_names(f)?;
+        let village_names = read_names(f)?;
+        for (island, name) in islands.iter_mut().flatten().zip(island_names) {
+            island.name = name;
+        }
+        if let Some(grid) = &mut sectors {
+            for (sector, name) in grid.sectors_mut().iter_mut().zip(sea_names) {
+                sector.name = name;
+            }
+        }
+        config.village_names = !village_names.is_empty();
+        Some(village_names)
+    } else {
+        None
+    };
+
+    // Village placement rules — added in format version 12.
+    if version >= 12 {
+        config.village_min_ocean_distance = read_u32(f)?;
+        config.village_spacing = read_u32(f)?;
+    }
+
+    // City placement mode — added in format version 13.
+    if version >= 13 {
+        config.city_placement = CityPlacement::from_u8(read_u8(f)?);
+    }
+
+    Ok(ChunkedWorldHeader {
+        config,
+        width,
+        height,
+        chunks_x,
+        chunks_y,
+        city_slots,
+        city_regions,
+        format_version: version,
+        city_resources,
+        city_sites,
+        city_distances,
+        sectors,
+        villages,
+        village_names,
+        metadata,
+        lod_levels,
+        islands,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Config serialization
+// ---------------------------------------------------------------------------
+
+/// Write all generation parameters. Types match the wire format directly.
+fn write_config(w: &mut impl Write, c: &WorldConfig) -> io::Result<()> {
+    write_u16(w, c.map_size)?;
+    write_f32(w, c.scale)?;
+    write_u8(w, c.octaves)?;
+    write_f32(w, c.persistence)?;
+    write_f32(w, c.lacunarity)?;
+    write_u32(w, c.seed)?;
+    write_f32(w, c.water_threshold)?;
+    write_u8(w, c.city_spacing)?;
+    write_u8(w, c.min_city_slots_per_island)?;
+    write_u16(w, c.playable_radius)?;
+    write_u16(w, c.farland_margin)?;
+    write_u16(w, c.min_water_body_size)?;
+    write_u8(w, c.min_land_neighbors)?;
+    write_u8(w, c.min_water_neighbors)?;
+    Ok(())
+}
+
+/// Read all generation parameters. Types match the wire format directly.
+fn read_config(r: &mut impl Read) -> io::Result<WorldConfig> {
+    let map_size = read_u16(r)?;
+    let scale = read_f32(r)?;
+    let octaves = read_u8(r)?;
+    let persistence = read_f32(r)?;
+    let lacunarity = read_f32(r)?;
+    let seed = read_u32(r)?;
+    let water_threshold = read_f32(r)?;
+    let city_spacing = read_u8(r)?;
+    let min_city_slots_per_island = read_u8(r)?;
+    let playable_radius = read_u16(r)?;
+    let farland_margin = read_u16(r)?;
+    let min_water_body_size = read_u16(r)?;
+    let min_land_neighbors = read_u8(r)?;
+    let min_water_neighbors = read_u8(r)?;
+
+    Ok(WorldConfig {
+        map_size,
+        chunk_size: 0, // filled by the header reader
+        seed,
+        scale,
+        octaves,
+        persistence,
+        lacunarity,
+        water_threshold,
+        playable_radius,
+        farland_margin,
+        city_spacing,
+        min_city_slots_per_island,
+        min_water_body_size,
+        min_land_neighbors,
+        min_water_neighbors,
+        // Island balancing, resource area and diagnostics not stored in
+        // binary (the slots and resources themselves are)
+        max_city_slots_per_island: 0,
+        island_size_targets: Vec::new(),
+        // Stored after the village rules; files without it get the default
+        city_placement: CityPlacement::default(),
+        city_resource_area: ResourceArea::default(),
+        city_diagnostics: false,
+        // Stored with the lists themselves
+        city_distance_radius: 0.0,
+        // Stored with the sector grid; files without one get the default
+        sector_size: DEFAULT_SECTOR_SIZE,
+        // Stored with the names; files without them get the defaults
+        name_style: NameStyle::default(),
+        village_names: true,
+        // Village count params not stored in binary (the villages are)
+        village_alpha: 1.2,
+        village_beta: 0.60,
+        // Stored after the names; files without them get the defaults
+        village_min_ocean_distance: 12,
+        village_spacing: 30,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Metadata serialization
+// ---------------------------------------------------------------------------
+
+/// Layout:
+///
+/// ```text
+/// version(str) · git_revision(str, empty = unknown) · generated_at(8)
+/// · threads(4) · num_phases(2) · [name(str) · micros(8)] · islands(4)
+/// · cities(4) · villages(4) · gold_nodes(4)
+/// ```
+fn write_metadata(w: &mut impl Write, m: &GenerationMetadata) -> io::Result<()> {
+    write_str(w, &m.generator_version)?;
+    write_str(w, m.git_revision.as_deref().unwrap_or(""))?;
+    write_u64(w, m.generated_at)?;
+    write_u32(w, m.threads)?;
+    let num_phases = u16::try_from(m.phase_timings.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} phase timings do not fit in 2 bytes",
+                m.phase_timings.len()
+            ),
+        )
+    })?;
+    write_u16(w, num_phases)?;
+    for phase in &m.phase_timings {
+        write_str(w, &phase.name)?;
+        write_u64(
+            w,
+            u64::try_from(phase.duration.as_micros()).unwrap_or(u64::MAX),
+        )?;
+    }
+    write_u32(w, m.counts.islands)?;
+    write_u32(w, m.counts.cities)?;
+    write_u32(w, m.counts.villages)?;
+    write_u32(w, m.counts.gold_nodes)
+}
+
+fn read_metadata(r: &mut impl Read) -> io::Result<GenerationMetadata> {
+    let generator_version = read_str(r)?;
+    let git_revision = Some(read_str(r)?).filter(|rev| !rev.is_empty());
+    let generated_at = read_u64(r)?;
+    let threads = read_u32(r)?;
+    let num_phases = read_u16(r)?;
+    let mut phase_timings = Vec::with_capacity(num_phases as usize);
+    for _ in 0..num_phases {
+        phase_timings.push(PhaseTiming {
+            name: read_str(r)?,
+            duration: Duration::from_micros(read_u64(r)?),
+        });
+    }
+    let counts = WorldCounts {
+        islands: read_u32(r)?,
+        cities: read_u32(r)?,
+        villages: read_u32(r)?,
+        gold_nodes: read_u32(r)?,
+    };
+
+    Ok(GenerationMetadata {
+        generator_version,
+        git_revision,
+        generated_at,
+        threads,
+        phase_timings,
+        counts,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Island registry serialization
+// ---------------------------------------------------------------------------
+
+/// Spawn flag bits stored per island.
+const ISLAND_FLAG_WORLD_SPAWN: u8 = 1;
+
+/// Layout:
+///
+/// ```text
+/// num_islands(4) · [id(4) · city_count(4) · centroid(2+2)
+/// · bounds(4 x 2) · area(4) · perimeter(4) · flags(1) · spawn_order(4)]
+/// ```
+fn write_islands(w: &mut impl Write, islands: &[Island]) -> io::Result<()> {
+    write_len(w, islands.len())?;
+    for i in islands {
+        write_u32(w, i.id)?;
+        write_u32(w, i.city_count)?;
+        write_u32_as_u16(w, i.centroid.0)?;
+        write_u32_as_u16(w, i.centroid.1)?;
+        write_u32_as_u16(w, i.bounds.min_x)?;
+        write_u32_as_u16(w, i.bounds.min_y)?;
+        write_u32_as_u16(w, i.bounds.max_x)?;
+        write_u32_as_u16(w, i.bounds.max_y)?;
+        write_u32(w, i.area)?;
+        write_u32(w, i.perimeter)?;
+        let flags = if i.is_world_spawn {
+            ISLAND_FLAG_WORLD_SPAWN
+        } else {
+            0
+        };
+        write_u8(w, flags)?;
+        write_u32(w, i.spawn_order)?;
+    }
+    Ok(())
+}
+
+fn read_islands(r: &mut impl Read) -> io::Result<Vec<Island>> {
+    let num_islands = read_u32(r)?;
+    let mut islands = Vec::with_capacity(num_islands as usize);
+    for _ in 0..num_islands {
+        let id = read_u32(r)?;
+        let city_count = read_u32(r)?;
+        let centroid = (u32::from(read_u16(r)?), u32::from(read_u16(r)?));
+        let bounds = BoundingBox {
+            min_x: u32::from(read_u16(r)?),
+            min_y: u32::from(read_u16(r)?),
+            max_x: u32::from(read_u16(r)?),
+            max_y: u32::from(read_u16(r)?),
+        };
+        let area = read_u32(r)?;
+        let perimeter = read_u32(r)?;
+        let flags = read_u8(r)?;
+        islands.push(Island {
+            id,
+            // Stored with the names (version 11+)
+            name: String::new(),
+            city_count,
+            centroid,
+            bounds,
+            area,
+            perimeter,
+            is_world_spawn: flags & ISLAND_FLAG_WORLD_SPAWN != 0,
+            spawn_order: read_u32(r)?,
+        });
+    }
+    Ok(islands)
+}
+
+// ---------------------------------------------------------------------------
+// Sector grid serialization
+// ---------------------------------------------------------------------------
+
+/// Layout:
+///
+/// ```text
+/// sector_size(2) · per sector, row-major: [land_tiles(4)
+/// · city_count(4) · num_islands(4) · island ids(4 each)]
+/// ```
+///
+/// The number of sectors follows from the map size; a size of `0` has none.
+fn write_sectors(w: &mut impl Write, grid: &SectorGrid) -> io::Result<()> {
+    write_u32_as_u16(w, grid.size)?;
+    for s in grid.sectors() {
+        write_u32(w, s.land_tiles)?;
+        write_u32(w, s.city_count)?;
+        write_len(w, s.islands.len())?;
+        for &id in &s.islands {
+            write_u32(w, id)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_sectors(r: &mut impl Read, width: u32, height: u32) -> io::Result<SectorGrid> {
+    let size = u32::from(read_u16(r)?);
+    let mut grid = SectorGrid::new(width, height, size);
+    for s in grid.sectors_mut() {
+        s.land_tiles = read_u32(r)?;
+        s.city_count = read_u32(r)?;
+        let num_islands = read_u32(r)?;
+        s.islands = (0..num_islands)
+            .map(|_| read_u32(r))
+            .collect::<io::Result<_>>()?;
+    }
+    Ok(grid)
+}
+
+// ---------------------------------------------------------------------------
+// Name list serialization
+// ---------------------------------------------------------------------------
+
+/// Layout: count(4) · [name(str)]
+fn write_names<'a>(
+    w: &mut impl Write,
+    names: impl ExactSizeIterator<Item = &'a str>,
+) -> io::Result<()> {
+    write_len(w, names.len())?;
+    for name in names {
+        write_str(w, name)?;
+    }
+    Ok(())
+}
+
+fn read_names(r: &mut impl Read) -> io::Result<Vec<String>> {
+    let count = read_u32(r)?;
+    (0..count).map(|_| read_str(r)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Binary I/O helpers
+// ---------------------------------------------------------------------------
+
+fn write_u8(w: &mut impl Write, v: u8) -> io::Result<()> {
+    w.write_all(&[v])
+}
+fn write_u16(w: &mut impl Write, v: u16) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+fn write_i16(w: &mut impl Write, v: i16) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+fn write_u32(w: &mut impl Write, v: u32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+/// Write a coordinate or size as a `u16`, failing if it does not fit.
+fn write_u32_as_u16(w: &mut impl Write, v: u32) -> io::Result<()> {
+    let v = u16::try_from(v).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("value {v} does not fit in 2 bytes"),
+        )
+    })?;
+    write_u16(w, v)
+}
+/// Write a length or count as a `u32`, failing if it does not fit.
+fn write_len(w: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("length {len} does not fit in 4 bytes"),
+        )
+    })?;
+    write_u32(w, len)
+}
+fn write_u64(w: &mut impl Write, v: u64) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+fn write_f32(w: &mut impl Write, v: f32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+/// Length-prefixed (u16) UTF-8 string.
+///
+/// Strings longer than 65535 bytes are rejected rather than truncated,
+/// which could split a character and leave the file unreadable.
+fn write_str(w: &mut impl Write, v: &str) -> io::Result<()> {
+    let len = u16::try_from(v.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("string of {} bytes does not fit in 2 bytes", v.len()),
+        )
+    })?;
+    write_u16(w, len)?;
+    w.write_all(v.as_bytes())
+}
+
+fn read_u8(r: &mut impl Read) -> io::Result<u8> {
+    let mut b = [0u8; 1];
+    r.read_exact(&mut b)?;
+    Ok(b[0])
+}
+fn read_u16(r: &mut impl Read) -> io::Result<u16> {
+    let mut b = [0u8; 2];
+    r.read_exact(&mut b)?;
+    Ok(u16::from_le_bytes(b))
+}
+fn read_i16(r: &mut impl Read) -> io::Result<i16> {
+    let mut b = [0u8; 2];
+    r.read_exact(&mut b)?;
+    Ok(i16::from_le_bytes(b))
+}
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut b = [0u8; 8];
+    r.read_exact(&mut b)?;
+    Ok(u64::from_le_bytes(b))
+}
+fn read_f32(r: &mut impl Read) -> io::Result<f32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(f32::from_le_bytes(b))
+}
+fn read_str(r: &mut impl Read) -> io::Result<String> {
+    let len = read_u16(r)? as usize;
+    let mut b = vec![0u8; len];
+    r.read_exact(&mut b)?;
+    String::from_utf8(b).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
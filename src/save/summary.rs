@@ -0,0 +1,198 @@
+//! Per-chunk summary statistics.
+//!
+//! Every chunk index entry (format version 4+) points at a [`ChunkSummary`]
+//! describing the chunk's contents: land tile count, elevation range, biome
+//! histogram and the set of regions it intersects. Summaries are read with
+//! the index when a file is opened, so questions like "does this chunk
+//! contain any land?" or "which islands cross it?" are answered without
+//! decompressing the chunk.
+
+use std::io::{self, Read, Write};
+
+use super::ChunkData;
+use super::{
+    read_f32, read_u16, read_u32, read_u8, write_f32, write_len, write_u16, write_u32, write_u8,
+};
+use crate::biome::Biome;
+use crate::terrain::Terrain;
+
+/// Aggregate statistics over the tiles of one chunk.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChunkSummary {
+    /// Number of [`Land`](Terrain::Land) tiles.
+    pub land_tiles: u32,
+    pub min_elevation: f32,
+    pub max_elevation: f32,
+    /// `(biome, tile count)` for every biome present, sorted by biome id.
+    pub biome_counts: Vec<(u8, u32)>,
+    /// Distinct non-zero region labels, sorted ascending.
+    pub region_ids: Vec<u32>,
+}
+
+impl ChunkSummary {
+    /// Compute the summary of `chunk`.
+    #[must_use]
+    pub fn from_chunk(chunk: &ChunkData) -> Self {
+        let mut histogram = [0u32; 256];
+        for &b in &chunk.biomes {
+            histogram[b as usize] += 1;
+        }
+
+        let mut region_ids = chunk.region_labels.clone();
+        region_ids.retain(|&r| r != 0);
+        region_ids.sort_unstable();
+        region_ids.dedup();
+
+        let (min_elevation, max_elevation) = chunk
+            .elevation
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &e| {
+                (lo.min(e), hi.max(e))
+            });
+
+        Self {
+            land_tiles: chunk
+                .terrain
+                .iter()
+                .fold(0, |n, &t| n + u32::from(t == Terrain::Land.to_u8())),
+            min_elevation: if chunk.elevation.is_empty() {
+                0.0
+            } else {
+                min_elevation
+            },
+            max_elevation: if chunk.elevation.is_empty() {
+                0.0
+            } else {
+                max_elevation
+            },
+            biome_counts: (0..=u8::MAX)
+                .zip(histogram)
+                .filter(|&(_, n)| n > 0)
+                .collect(),
+            region_ids,
+        }
+    }
+
+    /// `true` if the chunk contains at least one land tile.
+    #[must_use]
+    pub const fn has_land(&self) -> bool {
+        self.land_tiles > 0
+    }
+
+    /// `true` if any tile of the chunk belongs to region `id`.
+    #[must_use]
+    pub fn contains_region(&self, id: u32) -> bool {
+        self.region_ids.binary_search(&id).is_ok()
+    }
+
+    /// Number of tiles classified as `biome`.
+    #[must_use]
+    pub fn biome_count(&self, biome: Biome) -> u32 {
+        let id = biome.to_u8();
+        self.biome_counts
+            .iter()
+            .find(|&&(b, _)| b == id)
+            .map_or(0, |&(_, n)| n)
+    }
+
+    /// Size of the serialized summary in bytes.
+    pub(super) const fn encoded_len(&self) -> u64 {
+        (4 + 4 + 4 + 2 + self.biome_counts.len() * 5 + 4 + self.region_ids.len() * 4) as u64
+    }
+}
+
+/// Layout:
+///
+/// ```text
+/// land_tiles(4) · min_elevation(4) · max_elevation(4)
+/// · num_biomes(2) · [biome(1) · count(4)] · num_regions(4) · [region(4)]
+/// ```
+pub(super) fn write_summary(w: &mut impl Write, s: &ChunkSummary) -> io::Result<()> {
+    // One entry per distinct u8, so at most 256.
+    let num_biomes = u16::try_from(s.biome_counts.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} biome counts in one summary", s.biome_counts.len()),
+        )
+    })?;
+    write_u32(w, s.land_tiles)?;
+    write_f32(w, s.min_elevation)?;
+    write_f32(w, s.max_elevation)?;
+    write_u16(w, num_biomes)?;
+    for &(biome, count) in &s.biome_counts {
+        write_u8(w, biome)?;
+        write_u32(w, count)?;
+    }
+    write_len(w, s.region_ids.len())?;
+    for &rid in &s.region_ids {
+        write_u32(w, rid)?;
+    }
+    Ok(())
+}
+
+pub(super) fn read_summary(r: &mut impl Read) -> io::Result<ChunkSummary> {
+    let land_tiles = read_u32(r)?;
+    let min_elevation = read_f32(r)?;
+    let max_elevation = read_f32(r)?;
+
+    let num_biomes = read_u16(r)?;
+    let mut biome_counts = Vec::with_capacity(num_biomes as usize);
+    for _ in 0..num_biomes {
+        biome_counts.push((read_u8(r)?, read_u32(r)?));
+    }
+
+    let num_regions = read_u32(r)?;
+    let mut region_ids = Vec::with_capacity(num_regions as usize);
+    for _ in 0..num_regions {
+        region_ids.push(read_u32(r)?);
+    }
+
+    Ok(ChunkSummary {
+        land_tiles,
+        min_elevation,
+        max_elevation,
+        biome_counts,
+        region_ids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::territory::UNOWNED;
+
+    /// A 3x2 chunk: one island (region 5) of three land tiles, one tile of
+    /// another island (region 9), two water tiles.
+    fn chunk() -> ChunkData {
+        ChunkData {
+            width: 3,
+            height: 2,
+            terrain: vec![1, 1, 0, 1, 0, 1],
+            elevation: vec![0.4, 0.6, -0.2, 0.5, -0.1, 0.3],
+            region_labels: vec![5, 5, 0, 5, 0, 9],
+            biomes: vec![3, 3, 0, 7, 0, 3],
+            territory: vec![UNOWNED; 6],
+        }
+    }
+
+    #[test]
+    fn from_chunk_counts_tiles() {
+        let summary = ChunkSummary::from_chunk(&chunk());
+        assert_eq!(summary.land_tiles, 4);
+        assert!((summary.min_elevation + 0.2).abs() < f32::EPSILON);
+        assert!((summary.max_elevation - 0.6).abs() < f32::EPSILON);
+        assert_eq!(summary.biome_counts, vec![(0, 2), (3, 3), (7, 1)]);
+        assert_eq!(summary.region_ids, vec![5, 9]);
+    }
+
+    #[test]
+    fn encoded_len_matches_written_bytes() -> io::Result<()> {
+        for summary in [ChunkSummary::default(), ChunkSummary::from_chunk(&chunk())] {
+            let mut bytes = Vec::new();
+            write_summary(&mut bytes, &summary)?;
+            assert_eq!(bytes.len() as u64, summary.encoded_len());
+            assert_eq!(read_summary(&mut bytes.as_slice())?, summary);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+//! In-place chunk patching and compaction.
+//!
+//! [`ChunkedWorldWriter`] replaces individual chunks of an existing world
+//! file without rewriting the rest of it: the new chunk is compressed and
+//! appended to the end of the file, then its index entry is overwritten to
+//! point at the new bytes. The old payload is left behind as dead space.
+//!
+//! The chunk's [`ChunkSummary`] is recomputed and appended the same way.
+//! The index entry is only patched once the new payload and summary are
+//! synced to disk, and is then rewritten with a single 24-byte write, so a
+//! crash mid-update leaves the file pointing at either the previous chunk
+//! or the new one.
+//!
+//! [`compact_world`] rewrites a file with every chunk stored contiguously,
+//! reclaiming the dead space left by earlier patches.
+//!
+//! The island registry, city regions and sectors in the header are derived
+//! from each tile's terrain and region label, so a patch must keep both
+//! unchanged; elevation, biomes and territory can be edited freely.
+//! Patching does not refresh the [`lod`](crate::lod) pyramid either;
+//! low-zoom tiles keep showing the original terrain until the file is
+//! migrated or regenerated.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+
+use super::summary::write_summary;
+use super::{
+    encode_chunk, rename_synced, write_index_entry, write_u64, write_world_file, ChunkData,
+    ChunkIndexEntry, ChunkSummary, ChunkedWorldHeader, ChunkedWorldReader, CHUNK_INDEX_ENTRY_LEN,
+    FORMAT_VERSION,
+};
+
+/// Random-access patcher for an existing chunked world file.
+///
+/// Opening a writer reads the header and index exactly like
+/// [`ChunkedWorldReader::open`]; chunks can then be read back, modified and
+/// written with [`replace_chunk`](Self::replace_chunk).
+pub struct ChunkedWorldWriter {
+    reader: ChunkedWorldReader,
+    file: File,
+    dead_bytes: u64,
+}
+
+impl ChunkedWorldWriter {
+    /// Open an existing world file for patching.
+    ///
+    /// Only files in the current format version can be patched.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidData`](io::ErrorKind::InvalidData) if the file is in an older
+    /// format, or any error opening it or reading its header.
+    pub fn open(path: &str) -> io::Result<Self> {
+        let reader = ChunkedWorldReader::open(path)?;
+        ensure_current_version(&reader.header)?;
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let dead_bytes = file.metadata()?.len().saturating_sub(live_bytes(&reader));
+        Ok(Self {
+            reader,
+            file,
+            dead_bytes,
+        })
+    }
+
+    /// Header of the file being patched.
+    #[must_use]
+    pub const fn header(&self) -> &ChunkedWorldHeader {
+        &self.reader.header
+    }
+
+    /// Decompress and return the current contents of chunk `(cx, cy)`,
+    /// including any replacement written through this writer.
+    ///
+    /// # Errors
+    ///
+    /// As [`ChunkedWorldReader::load_chunk`].
+    pub fn load_chunk(&self, cx: u32, cy: u32) -> io::Result<ChunkData> {
+        self.reader.load_chunk(cx, cy)
+    }
+
+    /// Current summary of chunk `(cx, cy)`.
+    #[must_use]
+    pub fn chunk_summary(&self, cx: u32, cy: u32) -> Option<&ChunkSummary> {
+        self.reader.chunk_summary(cx, cy)
+    }
+
+    /// Replace chunk `(cx, cy)` with `chunk`.
+    ///
+    /// The chunk must have the same dimensions as the one it replaces,
+    /// every tile layer must hold exactly `width * height` entries, and the
+    /// terrain and region labels must be those already stored, since the
+    /// island registry and other header sections are derived from them.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if `chunk` breaks one of
+    /// those rules or `(cx, cy)` is outside the chunk grid, or any error
+    /// reading the current chunk or writing the new one.
+    pub fn replace_chunk(&mut self, cx: u32, cy: u32, chunk: &ChunkData) -> io::Result<()> {
+        let idx = self.reader.index_of(cx, cy)?;
+        validate_chunk(&self.reader.header, cx, cy, chunk)?;
+        ensure_same_regions(&self.reader.load_chunk(cx, cy)?, cx, cy, chunk)?;
+
+        let encoded = encode_chunk(chunk)?;
+        let compressed_len = u32::try_from(encoded.data.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("chunk ({cx},{cy}) compresses to more than 4 GiB"),
+            )
+        })?;
+        let summary = ChunkSummary::from_chunk(chunk);
+
+        // Append the new payload and summary first ...
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&encoded.data)?;
+        let summary_offset = offset + u64::from(compressed_len);
+        write_summary(&mut self.file, &summary)?;
+        // The OS may reorder writes: the payload must be on disk before the
+        // index entry points at it.
+        self.file.sync_data()?;
+
+        // ... then point the index entry at it, in one write.
+        let entry = ChunkIndexEntry {
+            offset,
+            compressed_len,
+            uncompressed_len: encoded.uncompressed_len,
+        };
+        let mut entry_bytes = Vec::new();
+        write_index_entry(&mut entry_bytes, &entry)?;
+        write_u64(&mut entry_bytes, summary_offset)?;
+        let entry_pos = self.reader.index_offset + idx as u64 * CHUNK_INDEX_ENTRY_LEN;
+        self.file.seek(SeekFrom::Start(entry_pos))?;
+        self.file.write_all(&entry_bytes)?;
+        self.file.sync_data()?;
+
+        self.dead_bytes += u64::from(self.reader.index[idx].compressed_len)
+            + self.reader.summaries[idx].encoded_len();
+        self.reader.index[idx] = entry;
+        self.reader.summaries[idx] = summary;
+        Ok(())
+    }
+
+    /// Number of bytes occupied by superseded chunk payloads.
+    ///
+    /// Run [`compact_world`] to reclaim them.
+    #[must_use]
+    pub const fn dead_bytes(&self) -> u64 {
+        self.dead_bytes
+    }
+
+    /// Flush all pending writes to disk.
+    ///
+    /// # Errors
+    ///
+    /// Any error flushing or syncing the file.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file.sync_data()
+    }
+}
+
+/// Rewrite the world file at `path` with all chunks stored contiguously.
+///
+/// Chunk payloads are copied as-is (no recompression). The new file is
+/// written next to the original, synced, and renamed over it once complete.
+///
+/// Returns the number of bytes reclaimed.
+///
+/// # Errors
+///
+/// [`InvalidData`](io::ErrorKind::InvalidData) if the file is in an older
+/// format, or any error reading it or writing its replacement (which is
+/// then removed).
+pub fn compact_world(path: &str) -> io::Result<u64> {
+    let reader = ChunkedWorldReader::open(path)?;
+    ensure_current_version(&reader.header)?;
+    let old_len = fs::metadata(path)?.len();
+
+    let tmp_path = format!("{path}.compact.tmp");
+    let written = write_world_file(&tmp_path, &reader.header, &reader)
+        .and_then(|()| rename_synced(&tmp_path, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    let new_len = fs::metadata(path)?.len();
+    Ok(old_len.saturating_sub(new_len))
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Bytes of the file that are still referenced by the header and index.
+fn live_bytes(reader: &ChunkedWorldReader) -> u64 {
+    let index_len = reader.index.len() as u64 * CHUNK_INDEX_ENTRY_LEN;
+    let payload_bytes: u64 = reader
+        .lod_index
+        .iter()
+        .chain(&reader.index)
+        .map(|e| u64::from(e.compressed_len))
+        .sum();
+    let summary_bytes: u64 = reader.summaries.iter().map(ChunkSummary::encoded_len).sum();
+    reader.index_offset + index_len + payload_bytes + summary_bytes
+}
+
+/// Patching and compaction copy chunk payloads verbatim, which is only
+/// valid when the file already uses the current layout.
+fn ensure_current_version(header: &ChunkedWorldHeader) -> io::Result<()> {
+    if header.format_version != FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "format version {} cannot be patched in place (current is {FORMAT_VERSION})",
+                header.format_version
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Reject chunks whose shape does not match the slot they are written to.
+fn validate_chunk(
+    header: &ChunkedWorldHeader,
+    cx: u32,
+    cy: u32,
+    chunk: &ChunkData,
+) -> io::Result<()> {
+    let (cw, ch) = header.chunk_dims(cx, cy);
+    let pixels = (cw * ch) as usize;
+    let layers_ok = chunk.terrain.len() == pixels
+        && chunk.elevation.len() == pixels
+        && chunk.region_labels.len() == pixels
+        && chunk.biomes.len() == pixels
+        && chunk.territory.len() == pixels;
+    if chunk.width != cw || chunk.height != ch || !layers_ok {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("chunk ({cx},{cy}) must be {cw}x{ch} with {pixels} tiles per layer"),
+        ));
+    }
+    Ok(())
+}
+
+/// Reject chunks that change any tile's terrain or region label, which the
+/// header's derived data is built from.
+fn ensure_same_regions(current: &ChunkData, cx: u32, cy: u32, chunk: &ChunkData) -> io::Result<()> {
+    if chunk.terrain != current.terrain || chunk.region_labels != current.region_labels {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("chunk ({cx},{cy}) changes terrain or region labels, which cannot be patched in place"),
+        ));
+    }
+    Ok(())
+}
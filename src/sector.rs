@@ -0,0 +1,216 @@
+//! Ocean sectors -- a coarse numbered grid over the map.
+//!
+//! Players find their way around a world this size by "ocean numbers"
+//! rather than coordinates. The map is cut into square sectors of
+//! `SECTOR_SIZE` tiles, numbered by column then row: with 100-tile sectors
+//! on a 1,000-tile map, tiles `(400..500, 500..600)` lie in sector `45`.
+//! Larger grids pad both halves to the same width, so a 100 × 100 grid runs
+//! from `0000` to `9999`.
+//!
+//! Each [`Sector`] records the islands, cities and land fraction inside it.
+//! [`compute_sectors`] builds the grid at generation time; it is stored in
+//! the world file (format version 10+).
+
+use std::collections::HashSet;
+
+use crate::coords::WorldPos;
+use crate::island::BoundingBox;
+use crate::save::WorldData;
+use crate::terrain::Terrain;
+
+/// Side length of a sector in tiles when `SECTOR_SIZE` is unset, and for
+/// files older than version 10.
+pub const DEFAULT_SECTOR_SIZE: u16 = 100;
+
+/// One square of the sector grid.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sector {
+    pub col: u32,
+    pub row: u32,
+    /// Generated sea [name](crate::names); empty for files older than
+    /// version 11.
+    pub name: String,
+    /// Number of map tiles in the sector; smaller along the right and
+    /// bottom edges when the sector size does not divide the map.
+    pub tiles: u32,
+    /// Number of Land tiles.
+    pub land_tiles: u32,
+    /// Number of city slots.
+    pub city_count: u32,
+    /// Registry ids of the islands with land in the sector, ascending.
+    pub islands: Vec<u32>,
+}
+
+impl Sector {
+    /// Share of the sector's tiles that are Land, in `[0, 1]`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn land_fraction(&self) -> f32 {
+        if self.tiles == 0 {
+            0.0
+        } else {
+            self.land_tiles as f32 / self.tiles as f32
+        }
+    }
+}
+
+/// Every sector of a map, row-major.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectorGrid {
+    /// Side length of a sector in tiles. `0` if there is no grid.
+    pub size: u32,
+    pub cols: u32,
+    pub rows: u32,
+    width: u32,
+    height: u32,
+    sectors: Vec<Sector>,
+}
+
+impl SectorGrid {
+    /// A grid of empty sectors covering a `width` x `height` map.
+    ///
+    /// A `size` of zero gives a grid without sectors.
+    #[must_use]
+    pub fn new(width: u32, height: u32, size: u32) -> Self {
+        if size == 0 {
+            return Self::default();
+        }
+        let (cols, rows) = (width.div_ceil(size), height.div_ceil(size));
+        let sectors = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let w = size.min(width - col * size);
+                let h = size.min(height - row * size);
+                Sector {
+                    col,
+                    row,
+                    tiles: w * h,
+                    ..Sector::default()
+                }
+            })
+            .collect();
+        Self {
+            size,
+            cols,
+            rows,
+            width,
+            height,
+            sectors,
+        }
+    }
+
+    /// `true` if the grid has no sectors.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.sectors.is_empty()
+    }
+
+    /// Every sector, row-major.
+    #[must_use]
+    pub fn sectors(&self) -> &[Sector] {
+        &self.sectors
+    }
+
+    pub(crate) fn sectors_mut(&mut self) -> &mut [Sector] {
+        &mut self.sectors
+    }
+
+    /// The sector in column `col` and row `row`.
+    #[must_use]
+    pub fn get(&self, col: u32, row: u32) -> Option<&Sector> {
+        if col >= self.cols || row >= self.rows {
+            return None;
+        }
+        self.sectors.get((row * self.cols + col) as usize)
+    }
+
+    /// The sector containing `pos`, or `None` off the map.
+    #[must_use]
+    pub fn at(&self, pos: WorldPos) -> Option<&Sector> {
+        if self.size == 0 {
+            return None;
+        }
+        self.get(pos.x / self.size, pos.y / self.size)
+    }
+
+    /// Digits in each half of a sector number.
+    fn digits(&self) -> usize {
+        self.cols.max(self.rows).saturating_sub(1).to_string().len()
+    }
+
+    /// Number of `sector`, e.g. `45` or `0405`.
+    #[must_use]
+    pub fn number(&self, sector: &Sector) -> String {
+        let digits = self.digits();
+        format!("{:0digits$}{:0digits$}", sector.col, sector.row)
+    }
+
+    /// The sector numbered `number` (as returned by [`number`](Self::number)).
+    #[must_use]
+    pub fn find(&self, number: &str) -> Option<&Sector> {
+        let digits = self.digits();
+        if number.len() != digits * 2 || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let col = number[..digits].parse().ok()?;
+        let row = number[digits..].parse().ok()?;
+        self.get(col, row)
+    }
+
+    /// Tiles covered by `sector`.
+    #[must_use]
+    pub fn bounds(&self, sector: &Sector) -> BoundingBox {
+        let (x, y) = (sector.col * self.size, sector.row * self.size);
+        BoundingBox {
+            min_x: x,
+            min_y: y,
+            max_x: (x + self.size).min(self.width) - 1,
+            max_y: (y + self.size).min(self.height) - 1,
+        }
+    }
+}
+
+/// Build the sector grid of freshly generated world data, with
+/// `config.sector_size` tiles per side.
+///
+/// Needs the island registry: regions without cities are not listed.
+#[must_use]
+pub fn compute_sectors(data: &WorldData) -> SectorGrid {
+    let (width, height) = (data.width, data.height);
+    let size = u32::from(data.config.sector_size);
+    let mut grid = SectorGrid::new(width, height, size);
+    if grid.is_empty() {
+        return grid;
+    }
+    let cols = grid.cols as usize;
+    let islands: HashSet<u32> = data.islands.iter().map(|i| i.id).collect();
+    let land = Terrain::Land.to_u8();
+
+    for y in 0..height as usize {
+        let row = y / size as usize;
+        let tiles = y * width as usize..(y + 1) * width as usize;
+        let (terrain, regions) = (&data.terrain[tiles.clone()], &data.region_labels[tiles]);
+        for (i, span) in terrain.chunks(size as usize).enumerate() {
+            let sector = &mut grid.sectors[row * cols + i];
+            let x0 = i * size as usize;
+            for (x, _) in span.iter().enumerate().filter(|&(_, &t)| t == land) {
+                sector.land_tiles += 1;
+                let region = regions[x0 + x];
+                if sector.islands.last() != Some(&region) && islands.contains(&region) {
+                    sector.islands.push(region);
+                }
+            }
+        }
+    }
+    for sector in &mut grid.sectors {
+        sector.islands.sort_unstable();
+        sector.islands.dedup();
+    }
+    for &(x, y) in &data.city_slots {
+        let (col, row) = (x / size, y / size);
+        if col < grid.cols && row < grid.rows {
+            grid.sectors[(row * grid.cols + col) as usize].city_count += 1;
+        }
+    }
+    grid
+}
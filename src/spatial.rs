@@ -0,0 +1,362 @@
+//! Uniform-grid spatial index over point features.
+//!
+//! [`World`](crate::World) builds one [`SpatialIndex`] over its city slots
+//! and one over its villages the first time either is queried. Every query
+//! returns indices into the original slice
+//! ([`World::city_slots`](crate::World::city_slots) or
+//! [`World::villages`](crate::World::villages)), so callers look up whatever
+//! per-point data they need themselves.
+//!
+//! Points are bucketed into square cells sized so that each cell holds a
+//! handful of points on average; a query only visits the cells its area
+//! overlaps.
+
+use crate::coords::WorldPos;
+
+/// Average number of points per cell the grid is sized for.
+const TARGET_POINTS_PER_CELL: u64 = 4;
+
+/// Cell size bounds (in tiles) for very sparse or very dense inputs.
+const MIN_CELL_SIZE: u32 = 8;
+const MAX_CELL_SIZE: u32 = 1024;
+
+/// A point returned by a distance query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    /// Index into the slice the index was built from.
+    pub index: usize,
+    /// Straight-line distance in tiles.
+    pub distance: f64,
+}
+
+/// Bucketed positions of a fixed set of points.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    points: Vec<(u32, u32)>,
+    cell_size: u32,
+    cols: u32,
+    rows: u32,
+    /// `cell_start[c]..cell_start[c + 1]` is the range of `items` in cell `c`.
+    cell_start: Vec<u32>,
+    /// Point indices grouped by cell, ascending within each cell.
+    items: Vec<u32>,
+}
+
+impl SpatialIndex {
+    /// Index `points`, which all lie inside a `width` x `height` map.
+    #[must_use]
+    pub fn new(points: Vec<(u32, u32)>, width: u32, height: u32) -> Self {
+        let area = u64::from(width) * u64::from(height);
+        let cell_size = (area * TARGET_POINTS_PER_CELL / points.len().max(1) as u64)
+            .isqrt()
+            .clamp(u64::from(MIN_CELL_SIZE), u64::from(MAX_CELL_SIZE));
+        let cell_size = u32::try_from(cell_size).unwrap_or(MAX_CELL_SIZE);
+        let cols = width.div_ceil(cell_size).max(1);
+        let rows = height.div_ceil(cell_size).max(1);
+        let num_cells = (cols * rows) as usize;
+
+        let cell_of = |&(x, y): &(u32, u32)| {
+            let cx = (x / cell_size).min(cols - 1);
+            let cy = (y / cell_size).min(rows - 1);
+            (cy * cols + cx) as usize
+        };
+
+        // Counting sort of point indices by cell.
+        let mut cell_start = vec![0u32; num_cells + 1];
+        for p in &points {
+            cell_start[cell_of(p) + 1] += 1;
+        }
+        for c in 0..num_cells {
+            cell_start[c + 1] += cell_start[c];
+        }
+        let mut fill = cell_start.clone();
+        let mut items = vec![0u32; points.len()];
+        for (i, p) in (0..).zip(&points) {
+            let c = cell_of(p);
+            items[fill[c] as usize] = i;
+            fill[c] += 1;
+        }
+
+        Self {
+            points,
+            cell_size,
+            cols,
+            rows,
+            cell_start,
+            items,
+        }
+    }
+
+    /// Number of indexed points.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Indices of the points inside the inclusive box `min..=max`, ascending.
+    #[must_use]
+    pub fn in_bbox(&self, min: WorldPos, max: WorldPos) -> Vec<usize> {
+        let (WorldPos { x: x0, y: y0 }, WorldPos { x: x1, y: y1 }) = (min, max);
+        let mut found = Vec::new();
+        if x0 > x1 || y0 > y1 {
+            return found;
+        }
+        self.for_each_in_cells(x0, y0, x1, y1, |i, (x, y)| {
+            if x >= x0 && x <= x1 && y >= y0 && y <= y1 {
+                found.push(i);
+            }
+        });
+        found.sort_unstable();
+        found
+    }
+
+    /// Points within `radius` tiles of `center`, nearest first.
+    pub fn within_radius(&self, center: WorldPos, radius: u32) -> Vec<Neighbor> {
+        let WorldPos { x, y } = center;
+        let r2 = u64::from(radius) * u64::from(radius);
+        let mut found = Vec::new();
+        self.for_each_in_cells(
+            x.saturating_sub(radius),
+            y.saturating_sub(radius),
+            x.saturating_add(radius),
+            y.saturating_add(radius),
+            |i, p| {
+                let d2 = dist2((x, y), p);
+                if d2 <= r2 {
+                    found.push((d2, i));
+                }
+            },
+        );
+        found.sort_unstable();
+        found.into_iter().map(neighbor).collect()
+    }
+
+    /// The point closest to `pos`, or `None` if the index is empty.
+    ///
+    /// Ties are broken by the lower index.
+    #[must_use]
+    pub fn nearest(&self, pos: WorldPos) -> Option<Neighbor> {
+        self.k_nearest(pos, 1).into_iter().next()
+    }
+
+    /// The `k` points closest to `pos`, nearest first (ties by index).
+    pub fn k_nearest(&self, pos: WorldPos, k: usize) -> Vec<Neighbor> {
+        let WorldPos { x, y } = pos;
+        if k == 0 || self.points.is_empty() {
+            return Vec::new();
+        }
+        let qx = i64::from((x / self.cell_size).min(self.cols - 1));
+        let qy = i64::from((y / self.cell_size).min(self.rows - 1));
+        let max_ring = i64::from(self.cols.max(self.rows));
+        // How far a query beyond the grid edge sits outside its (clamped) cell.
+        let overshoot = u64::from(
+            x.saturating_sub(self.cols * self.cell_size - 1)
+                .max(y.saturating_sub(self.rows * self.cell_size - 1)),
+        );
+
+        // Search square rings of cells outwards. A point in ring `r + 1` is
+        // at least `r` whole cells away, so once the k-th best candidate is
+        // closer than that no further ring can improve the answer.
+        let mut candidates: Vec<(u64, usize)> = Vec::new();
+        for ring in 0..=max_ring {
+            for (cx, cy) in ring_cells(qx, qy, ring) {
+                let (Ok(cx), Ok(cy)) = (u32::try_from(cx), u32::try_from(cy)) else {
+                    continue;
+                };
+                if cx >= self.cols || cy >= self.rows {
+                    continue;
+                }
+                for &i in self.cell_items(cx, cy) {
+                    let i = i as usize;
+                    candidates.push((dist2((x, y), self.points[i]), i));
+                }
+            }
+            if candidates.len() >= k {
+                candidates.select_nth_unstable(k - 1);
+                candidates.truncate(k);
+                let reach =
+                    (ring.unsigned_abs() * u64::from(self.cell_size)).saturating_sub(overshoot);
+                if candidates.iter().all(|&(d2, _)| d2 < reach * reach) {
+                    break;
+                }
+            }
+        }
+
+        candidates.sort_unstable();
+        candidates.into_iter().map(neighbor).collect()
+    }
+
+    /// Call `f(index, position)` for every point in the cells overlapping
+    /// the inclusive box `[x0, x1] x [y0, y1]`.
+    fn for_each_in_cells(
+        &self,
+        x0: u32,
+        y0: u32,
+        x1: u32,
+        y1: u32,
+        mut f: impl FnMut(usize, (u32, u32)),
+    ) {
+        let cx0 = (x0 / self.cell_size).min(self.cols - 1);
+        let cx1 = (x1 / self.cell_size).min(self.cols - 1);
+        let cy0 = (y0 / self.cell_size).min(self.rows - 1);
+        let cy1 = (y1 / self.cell_size).min(self.rows - 1);
+        for cy in cy0..=cy1 {
+            for cx in cx0..=cx1 {
+                for &i in self.cell_items(cx, cy) {
+                    f(i as usize, self.points[i as usize]);
+                }
+            }
+        }
+    }
+
+    fn cell_items(&self, cx: u32, cy: u32) -> &[u32] {
+        let c = (cy * self.cols + cx) as usize;
+        &self.items[self.cell_start[c] as usize..self.cell_start[c + 1] as usize]
+    }
+}
+
+/// Cells on the square ring at Chebyshev distance `ring` from `(cx, cy)`.
+fn ring_cells(cx: i64, cy: i64, ring: i64) -> Vec<(i64, i64)> {
+    if ring == 0 {
+        return vec![(cx, cy)];
+    }
+    let mut cells = Vec::with_capacity(8 * usize::try_from(ring).unwrap_or(0));
+    for d in -ring..=ring {
+        cells.push((cx + d, cy - ring));
+        cells.push((cx + d, cy + ring));
+    }
+    for d in -ring + 1..ring {
+        cells.push((cx - ring, cy + d));
+        cells.push((cx + ring, cy + d));
+    }
+    cells
+}
+
+fn dist2(a: (u32, u32), b: (u32, u32)) -> u64 {
+    let dx = u64::from(a.0.abs_diff(b.0));
+    let dy = u64::from(a.1.abs_diff(b.1));
+    dx * dx + dy * dy
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn neighbor((d2, index): (u64, usize)) -> Neighbor {
+    Neighbor {
+        index,
+        distance: (d2 as f64).sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: u32 = 500;
+    const HEIGHT: u32 = 300;
+
+    /// `n` reproducible points inside the map (a small LCG, so the test
+    /// does not depend on a random number generator's output).
+    fn scattered(n: usize, seed: u64) -> Vec<(u32, u32)> {
+        let mut state = seed;
+        let mut next = |bound: u32| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            u32::try_from((state >> 33) % u64::from(bound)).unwrap_or(0)
+        };
+        (0..n).map(|_| (next(WIDTH), next(HEIGHT))).collect()
+    }
+
+    /// Every point sorted by `(distance², index)`, the order the index
+    /// promises.
+    fn brute_force(points: &[(u32, u32)], pos: WorldPos) -> Vec<(u64, usize)> {
+        let mut all: Vec<(u64, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (dist2((pos.x, pos.y), p), i))
+            .collect();
+        all.sort_unstable();
+        all
+    }
+
+    /// Queries inside the map, on its edges and well beyond them.
+    fn queries() -> Vec<WorldPos> {
+        let xs = [0, 1, 37, 250, WIDTH - 1, WIDTH, WIDTH + 90, 5_000];
+        let ys = [0, 3, 150, HEIGHT - 1, HEIGHT, HEIGHT + 400, 9_000];
+        xs.iter()
+            .flat_map(|&x| ys.iter().map(move |&y| WorldPos::new(x, y)))
+            .collect()
+    }
+
+    fn check_against_brute_force(points: &[(u32, u32)]) {
+        let index = SpatialIndex::new(points.to_vec(), WIDTH, HEIGHT);
+        for pos in queries() {
+            let expected = brute_force(points, pos);
+            for k in [1, 2, 5, 17, points.len(), points.len() + 3] {
+                let found: Vec<usize> = index.k_nearest(pos, k).iter().map(|n| n.index).collect();
+                let want: Vec<usize> = expected.iter().take(k).map(|&(_, i)| i).collect();
+                assert_eq!(found, want, "k_nearest({pos:?}, {k})");
+            }
+            assert_eq!(
+                index.nearest(pos).map(|n| n.index),
+                expected.first().map(|&(_, i)| i)
+            );
+
+            let radius = 60;
+            let found: Vec<usize> = index
+                .within_radius(pos, radius)
+                .iter()
+                .map(|n| n.index)
+                .collect();
+            let want: Vec<usize> = expected
+                .iter()
+                .take_while(|&&(d2, _)| d2 <= u64::from(radius * radius))
+                .map(|&(_, i)| i)
+                .collect();
+            assert_eq!(found, want, "within_radius({pos:?}, {radius})");
+        }
+    }
+
+    #[test]
+    fn k_nearest_matches_brute_force() {
+        for (n, seed) in [(1, 1), (7, 2), (150, 3), (2_000, 4)] {
+            check_against_brute_force(&scattered(n, seed));
+        }
+    }
+
+    #[test]
+    fn k_nearest_breaks_ties_by_index() {
+        // Duplicates, and rings of points equidistant from the queries.
+        let mut points = vec![(100, 100); 4];
+        for (dx, dy) in [(0, 5), (5, 0), (3, 4), (4, 3)] {
+            points.extend([
+                (100 + dx, 100 + dy),
+                (100 - dx, 100 - dy),
+                (100 + dx, 100 - dy),
+                (100 - dx, 100 + dy),
+            ]);
+        }
+        points.extend([(WIDTH - 1, 0), (WIDTH - 1, HEIGHT - 1), (0, HEIGHT - 1)]);
+        points.extend(scattered(40, 5));
+        check_against_brute_force(&points);
+
+        let index = SpatialIndex::new(points, WIDTH, HEIGHT);
+        let nearest = index.k_nearest(WorldPos::new(100, 100), 6);
+        let indices: Vec<usize> = nearest.iter().map(|n| n.index).collect();
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5]);
+        assert!(nearest[4].distance > 4.99 && nearest[4].distance < 5.01);
+    }
+
+    #[test]
+    fn empty_index_finds_nothing() {
+        let index = SpatialIndex::new(Vec::new(), WIDTH, HEIGHT);
+        assert!(index.is_empty());
+        assert!(index.nearest(WorldPos::new(10, 10)).is_none());
+        assert!(index.k_nearest(WorldPos::new(10, 10), 3).is_empty());
+        assert!(index.within_radius(WorldPos::new(10, 10), 50).is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+//! Tile-level territory partition between city slots.
+//!
+//! Every Land tile of an island with cities belongs to exactly one of that
+//! island's cities: the one it is closest to by a path over the island's
+//! own land. Distances use chamfer steps (5 straight, 7 diagonal, a close
+//! match for 1 and √2), so territories grow round rather than diamond- or
+//! square-shaped, and never reach across water to a neighbouring island.
+//!
+//! Equally distant tiles go to the city whose slot comes first in
+//! row-major order, so the partition depends only on where the cities are,
+//! not on the order of the slot list.
+//!
+//! The result is stored per tile in the world file (format version 8+) as
+//! the owning city's index into `city_slots` plus one; [`UNOWNED`] marks
+//! water, `FarLand` and islands without cities.
+
+/// Territory value of tiles no city owns.
+pub const UNOWNED: u32 = 0;
+
+/// Chamfer step costs.
+const STRAIGHT_STEP: u32 = 5;
+const DIAGONAL_STEP: u32 = 7;
+
+/// Territory value of the tiles owned by city `index`.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub const fn owner_value(index: usize) -> u32 {
+    index as u32 + 1
+}
+
+/// The city index stored in territory value `value`, or `None` for
+/// [`UNOWNED`].
+#[must_use]
+pub fn owner_index(value: u32) -> Option<usize> {
+    value.checked_sub(1).map(|i| i as usize)
+}
+
+/// Partition the Land tiles of every island among its city slots.
+///
+/// Returns a grid of territory values (see [`owner_value`]). Multi-source
+/// Dijkstra over 8-connected tiles of equal, non-zero region label (that
+/// is, the Land of one island), with a bucket queue since step costs are
+/// small integers.
+pub fn compute_territory(
+    region_labels: &[Vec<usize>],
+    city_slots: &[(usize, usize)],
+) -> Vec<Vec<u32>> {
+    let height = region_labels.len();
+    let width = region_labels.first().map_or(0, Vec::len);
+    let mut owner = vec![vec![UNOWNED; width]; height];
+    let mut dist = vec![vec![u32::MAX; width]; height];
+
+    // Tie-break key of each city: its slot's row-major position.
+    let key = |value: u32| {
+        owner_index(value).map_or(usize::MAX, |i| {
+            let (x, y) = city_slots[i];
+            y * width + x
+        })
+    };
+
+    // Bucket queue: bucket `d % len` holds tiles at distance `d`. Every
+    // step is at most DIAGONAL_STEP, so pending distances never span more
+    // buckets than that.
+    let num_buckets = DIAGONAL_STEP + 1;
+    let mut buckets: Vec<Vec<(usize, usize)>> = vec![Vec::new(); num_buckets as usize];
+    let mut pending = 0usize;
+    for (i, &(x, y)) in city_slots.iter().enumerate() {
+        if region_labels[y][x] != 0 {
+            dist[y][x] = 0;
+            owner[y][x] = owner_value(i);
+            buckets[0].push((x, y));
+            pending += 1;
+        }
+    }
+
+    let mut d = 0u32;
+    while pending > 0 {
+        let bucket = std::mem::take(&mut buckets[(d % num_buckets) as usize]);
+        pending -= bucket.len();
+        for (x, y) in bucket {
+            if dist[y][x] != d {
+                continue; // Reached more cheaply after being queued.
+            }
+            let region = region_labels[y][x];
+            let value = owner[y][x];
+            for (dx, dy) in NEIGHBORS_8 {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                if nx >= width || ny >= height || region_labels[ny][nx] != region {
+                    continue;
+                }
+                let step = if dx != 0 && dy != 0 {
+                    DIAGONAL_STEP
+                } else {
+                    STRAIGHT_STEP
+                };
+                let nd = d + step;
+                let current = dist[ny][nx];
+                if nd < current {
+                    dist[ny][nx] = nd;
+                    owner[ny][nx] = value;
+                    buckets[(nd % num_buckets) as usize].push((nx, ny));
+                    pending += 1;
+                } else if nd == current && key(value) < key(owner[ny][nx]) {
+                    owner[ny][nx] = value;
+                }
+            }
+        }
+        d += 1;
+    }
+
+    owner
+}
+
+/// Number of tiles owned by each city, indexed like `city_slots`.
+#[must_use]
+pub fn territory_sizes(territory: &[Vec<u32>], num_cities: usize) -> Vec<u32> {
+    let mut sizes = vec![0u32; num_cities];
+    for &value in territory.iter().flatten() {
+        if let Some(size) = owner_index(value).and_then(|i| sizes.get_mut(i)) {
+            *size += 1;
+        }
+    }
+    sizes
+}
+
+const NEIGHBORS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
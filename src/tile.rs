@@ -3,16 +3,34 @@
 //! Produces 256 x 256 PNG tiles from chunk data, compatible with Leaflet
 //! slippy-map tile URLs (`/tile/{z}/{x}/{y}.png`).
 //!
-//! Uses a two-phase approach that is borrow-checker friendly:
-//! 1. Pre-load (`ensure_chunk`) all required chunks into the cache.
-//! 2. Sample pixels via `chunk()` method with shared borrows once cached.
+//! Every pixel is sampled from one world tile, taken from the first source
+//! that applies:
+//! 1. Zoomed-out tiles whose pixels each span several world tiles use the
+//!    coarsest sufficient [`lod`](crate::lod) level, so they never touch
+//!    full-resolution chunks.
+//! 2. Small areas are copied in one [`World::read_rect`].
+//! 3. Anything larger (files without LOD levels) is sampled straight from
+//!    shared chunk handles, one row of chunks at a time, so a zoom-0 tile
+//!    never copies the whole map.
 //!
 //! Rendering modes:
 //! - **Standard**: Biome colors with elevation shading (used by web viewer).
 //! - **Debug**: Adds tile grid, coordinate labels, and gold vein overlay (for dev/debugging).
+//! - **City diagnostics**: Tints every Land tile by its city placement
+//!   outcome (see [`city_diagnostics`](crate::city_diagnostics)).
+//! - **Sectors**: Outlines the numbered ocean [`sector`](crate::sector)
+//!   grid and labels every sector with its number.
+
+use std::sync::Arc;
 
 use crate::biome::{Biome, GoldVeinSampler};
-use crate::font::draw_text;
+use crate::city_diagnostics::{CityDiagnostics, SlotOutcome};
+use crate::coords::{MapGeometry, TileSpan, TileXYZ, WorldPos};
+use crate::font::{draw_text, ADVANCE};
+use crate::lod;
+use crate::rect::Layers;
+use crate::save::ChunkData;
+use crate::sector::SectorGrid;
 use crate::terrain::Terrain;
 use crate::world::World;
 
@@ -21,27 +39,12 @@ use crate::world::World;
 /// All tiles are square; this is both width and height.
 pub const TILE_SIZE: u32 = 256;
 
-/// The world-coordinate region that a single tile image covers.
-///
-/// Used to map pixel coordinates within a tile to world coordinates
-/// for sampling elevation, terrain, and biome data.
-struct TileRegion {
-    /// World X coordinate of tile's left edge
-    x_start: f64,
-    /// World Y coordinate of tile's top edge
-    y_start: f64,
-    /// Width of tile region in world coordinates
-    width: f64,
-    /// Height of tile region in world coordinates
-    height: f64,
-}
-
 /// Render a single map tile at the given zoom level and coordinates.
 ///
 /// # Arguments
-/// - `world`: Mutable reference to world (needed for chunk loading)
-/// - `z`: Zoom level (higher = more zoomed in, more detail)
-/// - `tx`, `ty`: Tile coordinates in the slippy-map system
+/// - `world`: World to sample; chunks are loaded through its cache
+/// - `tile`: Zoom level (higher = more zoomed in, more detail) and tile
+///   coordinates in the slippy-map system
 ///
 /// # Returns
 /// `Some(png_bytes)` if tile coordinate grid, boundaries, and gold vein overlay.
@@ -57,8 +60,8 @@ struct TileRegion {
 /// # Tile coordinates
 /// At zoom level 0, the entire world is a single 256x256 tile.
 /// At zoom level z, the world is divided into 2^z × 2^z tiles.
-pub fn render_tile(world: &mut World, z: u32, tx: u32, ty: u32) -> Option<Vec<u8>> {
-    let (pixels, _) = render_base(world, z, tx, ty)?;
+pub fn render_tile(world: &World, tile: TileXYZ) -> Option<Vec<u8>> {
+    let (pixels, _, _) = render_base(world, tile)?;
     Some(encode_png(&pixels, TILE_SIZE, TILE_SIZE))
 }
 
@@ -71,11 +74,39 @@ pub fn render_tile(world: &mut World, z: u32, tx: u32, ty: u32) -> Option<Vec<u8
 ///
 /// Debug tiles are not cached and are re-rendered on every request,
 /// ensuring fresh visualization of any underlying data changes.
-pub fn render_debug_tile(world: &mut World, z: u32, tx: u32, ty: u32) -> Option<Vec<u8>> {
+pub fn render_debug_tile(world: &World, tile: TileXYZ) -> Option<Vec<u8>> {
     let seed = world.config().seed;
-    let (mut pixels, region) = render_base(world, z, tx, ty)?;
-    draw_gold_overlay(&mut pixels, world, &region, seed);
-    draw_debug_overlays(&mut pixels, z, tx, ty, &region);
+    let (mut pixels, biomes, region) = render_base(world, tile)?;
+    draw_gold_overlay(&mut pixels, &biomes, world, &region, seed);
+    draw_debug_overlays(&mut pixels, tile, &region);
+    Some(encode_png(&pixels, TILE_SIZE, TILE_SIZE))
+}
+
+/// Render a tile tinted by the city placement outcome of every Land tile.
+///
+/// Each pixel takes the [`SlotOutcome::color`] of the world tile under it,
+/// so zoomed-out tiles only hint at the sparse outcomes (placed slots,
+/// dropped islands); zoom in to see individual tiles. Not cached, like
+/// debug tiles.
+pub fn render_city_diagnostics_tile(
+    world: &World,
+    diagnostics: &CityDiagnostics,
+    tile: TileXYZ,
+) -> Option<Vec<u8>> {
+    let (mut pixels, _, region) = render_base(world, tile)?;
+    draw_city_diagnostics(&mut pixels, world.geometry(), diagnostics, &region);
+    Some(encode_png(&pixels, TILE_SIZE, TILE_SIZE))
+}
+
+/// Render a tile with the ocean sector grid and sector numbers on top.
+///
+/// Returns `None` if the world has no [sector grid](World::sectors). Lines
+/// are left out once sectors shrink to a few pixels, labels a little
+/// earlier; sea names join the details when zoomed in. Not cached, like debug tiles.
+pub fn render_sector_tile(world: &World, tile: TileXYZ) -> Option<Vec<u8>> {
+    let grid = world.sectors()?;
+    let (mut pixels, _, region) = render_base(world, tile)?;
+    draw_sector_overlay(&mut pixels, world.geometry(), grid, &region);
     Some(encode_png(&pixels, TILE_SIZE, TILE_SIZE))
 }
 
@@ -89,80 +120,110 @@ pub fn render_debug_tile(world: &mut World, z: u32, tx: u32, ty: u32) -> Option<
 /// of rendering; output can then be modified with overlays (grid, coordinates, gold).
 ///
 /// # Returns
-/// `Some((pixels, region))` with the raw RGB buffer and the tile's world-coordinate region,
-/// or `None` if coordinates are invalid for the zoom level.
-fn render_base(world: &mut World, z: u32, tx: u32, ty: u32) -> Option<(Vec<u8>, TileRegion)> {
-    let tiles_per_axis = 1u32 << z;
-    let max_zoom = world.config().max_zoom();
-    if tx >= tiles_per_axis || ty >= tiles_per_axis || z > max_zoom {
-        return None;
+/// `Some((pixels, biomes, region))` with the raw RGB buffer, the biome sampled for
+/// each pixel and the tile's world-coordinate region, or `None` if coordinates are
+/// invalid for the zoom level.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn render_base(world: &World, tile: TileXYZ) -> Option<(Vec<u8>, Vec<u8>, TileSpan)> {
+    let tile = TileXYZ::checked(tile.z, tile.x, tile.y, world.config().max_zoom())?;
+
+    let geometry = world.geometry();
+    let water_threshold = world.config().water_threshold;
+    let region = geometry.tile_span(tile);
+
+    // Zoomed out far enough: sample the LOD pyramid instead of chunks.
+    let tiles_per_pixel = region.width / f64::from(TILE_SIZE);
+    if let Some(level) = lod::best_level(world.lod_levels(), tiles_per_pixel) {
+        if let Ok(lod) = world.lod_level(level) {
+            let (pixels, biomes) = sample_pixels(&region, geometry, water_threshold, |pos| {
+                let idx = lod.index_of(pos.x, pos.y);
+                Some((lod.terrain[idx], lod.biomes[idx], lod.elevation[idx]))
+            });
+            return Some((pixels, biomes, region));
+        }
     }
 
-    // Copy scalars so we don't borrow `world` across the mutable chunk-loading phase.
-    let width = world.width();
-    let height = world.height();
-    let chunk_size = world.config().chunk_size as u32;
-    let water_threshold = world.config().water_threshold;
-    let chunks_x = world.chunks_x();
-    let chunks_y = world.chunks_y();
-
-    let map_w = width as f64;
-    let map_h = height as f64;
-    let region = TileRegion {
-        x_start: tx as f64 * map_w / tiles_per_axis as f64,
-        y_start: ty as f64 * map_h / tiles_per_axis as f64,
-        width: map_w / tiles_per_axis as f64,
-        height: map_h / tiles_per_axis as f64,
-    };
+    let min = geometry.clamp(region.pixel_pos(0, 0, TILE_SIZE));
+    let max = geometry.clamp(WorldPos::new(
+        (region.x_start + region.width).ceil() as u32,
+        (region.y_start + region.height).ceil() as u32,
+    ));
+    let (w, h) = (max.x - min.x + 1, max.y - min.y + 1);
+
+    // Small enough: copy every world tile this image can sample.
+    if u64::from(w) * u64::from(h) <= MAX_RECT_TILES {
+        let layers = Layers::TERRAIN | Layers::BIOMES | Layers::ELEVATION;
+        let rect = world.read_rect(min, w, h, layers).ok()?;
+        let (pixels, biomes) = sample_pixels(&region, geometry, water_threshold, |pos| {
+            rect.contains(pos).then(|| {
+                let idx = rect.index_of(pos);
+                (rect.terrain[idx], rect.biomes[idx], rect.elevation[idx])
+            })
+        });
+        return Some((pixels, biomes, region));
+    }
 
-    // Determine which chunks overlap this tile.
-    let cx_min = (region.x_start as u32) / chunk_size;
-    let cx_max = (((region.x_start + region.width).ceil() as u32).min(width - 1) / chunk_size)
-        .min(chunks_x - 1);
-    let cy_min = (region.y_start as u32) / chunk_size;
-    let cy_max = (((region.y_start + region.height).ceil() as u32).min(height - 1) / chunk_size)
-        .min(chunks_y - 1);
-
-    // Phase 1: load all needed chunks into the cache.
-    for cy in cy_min..=cy_max {
-        for cx in cx_min..=cx_max {
-            if world.ensure_chunk(cx, cy).is_err() {
+    // Otherwise sample the chunks in place. Pixels go row by row, so only
+    // the chunks under the current pixel row need to be held.
+    let mut row: Vec<Option<Arc<ChunkData>>> = Vec::new();
+    let mut row_cy = None;
+    let mut failed = false;
+    let (pixels, biomes) = sample_pixels(&region, geometry, water_threshold, |pos| {
+        let (chunk_pos, idx) = geometry.tile_index(pos)?;
+        if row_cy != Some(chunk_pos.cy) {
+            row = vec![None; geometry.chunks_x() as usize];
+            row_cy = Some(chunk_pos.cy);
+        }
+        let slot = row.get_mut(chunk_pos.cx as usize)?;
+        if slot.is_none() {
+            if let Ok(chunk) = world.chunk(chunk_pos) {
+                *slot = Some(chunk);
+            } else {
+                failed = true;
                 return None;
             }
         }
+        let chunk = slot.as_ref()?;
+        Some((chunk.terrain[idx], chunk.biomes[idx], chunk.elevation[idx]))
+    });
+    if failed {
+        return None;
     }
 
-    // Phase 2: sample pixels from cached chunks.
+    Some((pixels, biomes, region))
+}
+
+/// Largest area [`render_base`] copies with [`World::read_rect`]: 1024 x
+/// 1024 tiles, about 6 MB of terrain, biomes and elevation.
+const MAX_RECT_TILES: u64 = 1 << 20;
+
+/// Colour every pixel of a tile from `sample`, which maps a world coordinate
+/// to its `(terrain, biome, elevation)`.
+///
+/// Returns the RGB buffer and the biome of each pixel. Pixels with no sample
+/// stay black and are reported as ocean.
+fn sample_pixels(
+    region: &TileSpan,
+    geometry: MapGeometry,
+    water_threshold: f32,
+    mut sample: impl FnMut(WorldPos) -> Option<(u8, u8, f32)>,
+) -> (Vec<u8>, Vec<u8>) {
     let mut pixels = vec![0u8; (TILE_SIZE * TILE_SIZE * 3) as usize];
+    let mut biomes = vec![Biome::Ocean.to_u8(); (TILE_SIZE * TILE_SIZE) as usize];
 
     for py in 0..TILE_SIZE {
         for px in 0..TILE_SIZE {
-            let map_x = (region.x_start + px as f64 * region.width / TILE_SIZE as f64) as u32;
-            let map_y = (region.y_start + py as f64 * region.height / TILE_SIZE as f64) as u32;
-            let map_x = map_x.min(width - 1);
-            let map_y = map_y.min(height - 1);
-
-            let cx = map_x / chunk_size;
-            let cy = map_y / chunk_size;
-
-            if let Some(chunk) = world.chunk(cx, cy) {
-                let lx = (map_x - cx * chunk_size) as usize;
-                let ly = (map_y - cy * chunk_size) as usize;
-                let idx = ly * chunk.width as usize + lx;
-
-                let terrain = Terrain::from_u8(chunk.terrain[idx]);
-                let biome = Biome::from_u8(chunk.biomes[idx]);
-                let color = biome.get_color(terrain, chunk.elevation[idx], water_threshold);
-
-                let off = ((py * TILE_SIZE + px) * 3) as usize;
-                pixels[off] = color[0];
-                pixels[off + 1] = color[1];
-                pixels[off + 2] = color[2];
+            let pos = geometry.clamp(region.pixel_pos(px, py, TILE_SIZE));
+            if let Some((terrain, biome, elevation)) = sample(pos) {
+                biomes[(py * TILE_SIZE + px) as usize] = biome;
+                let terrain = Terrain::from_u8(terrain);
+                let biome = Biome::from_u8(biome);
+                let color = biome.get_color(terrain, elevation, water_threshold);
+                set_pixel(&mut pixels, px, py, color);
             }
         }
     }
-
-    Some((pixels, region))
+    (pixels, biomes)
 }
 
 // ---------------------------------------------------------------------------
@@ -170,7 +231,8 @@ fn render_base(world: &mut World, z: u32, tx: u32, ty: u32) -> Option<(Vec<u8>,
 // ---------------------------------------------------------------------------
 
 /// Draw diagnostic elements on top of a rendered tile.
-fn draw_debug_overlays(pixels: &mut [u8], z: u32, tx: u32, ty: u32, region: &TileRegion) {
+fn draw_debug_overlays(pixels: &mut [u8], tile: TileXYZ, region: &TileSpan) {
+    let TileXYZ { z, x: tx, y: ty } = tile;
     // Border color cycles with zoom level.
     let border: [u8; 3] = match z % 4 {
         0 => [255, 0, 0],
@@ -224,38 +286,153 @@ fn draw_debug_overlays(pixels: &mut [u8], z: u32, tx: u32, ty: u32, region: &Til
     }
 }
 
+// ---------------------------------------------------------------------------
+// City diagnostics overlay
+// ---------------------------------------------------------------------------
+
+/// Share of the outcome colour in a tinted pixel.
+const OUTCOME_TINT: f32 = 0.7;
+
+/// Blend the outcome colour of the world tile under every pixel.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn draw_city_diagnostics(
+    pixels: &mut [u8],
+    geometry: MapGeometry,
+    diagnostics: &CityDiagnostics,
+    region: &TileSpan,
+) {
+    for py in 0..TILE_SIZE {
+        for px in 0..TILE_SIZE {
+            let pos = geometry.clamp(region.pixel_pos(px, py, TILE_SIZE));
+            let Some(color) = diagnostics.outcome_at(pos).and_then(SlotOutcome::color) else {
+                continue;
+            };
+            let offset = ((py * TILE_SIZE + px) * 3) as usize;
+            for (c, &tint) in pixels[offset..offset + 3].iter_mut().zip(&color) {
+                *c =
+                    f32::from(*c).mul_add(1.0 - OUTCOME_TINT, f32::from(tint) * OUTCOME_TINT) as u8;
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sector overlay
+// ---------------------------------------------------------------------------
+
+/// Sectors narrower than this many pixels get no lines.
+const MIN_SECTOR_LINE_PIXELS: f64 = 4.0;
+
+/// Sectors narrower than this many pixels get no number.
+const MIN_SECTOR_LABEL_PIXELS: f64 = 32.0;
+
+/// Sectors at least this many pixels wide also list their cities and land.
+const MIN_SECTOR_DETAIL_PIXELS: f64 = 96.0;
+
+const SECTOR_LINE: [u8; 3] = [235, 235, 235];
+
+/// Draw the sector boundaries crossing the tile and number every sector
+/// whose top-left corner lies in it.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn draw_sector_overlay(
+    pixels: &mut [u8],
+    geometry: MapGeometry,
+    grid: &SectorGrid,
+    region: &TileSpan,
+) {
+    let sector_pixels = f64::from(grid.size) * f64::from(TILE_SIZE) / region.width;
+    if sector_pixels < MIN_SECTOR_LINE_PIXELS {
+        return;
+    }
+    // Sector column (row) under pixel column (row) `p`; `-1` is the last
+    // pixel of the neighbouring tile.
+    let sector_of = |start: f64, span: f64, limit: u32, p: i64| {
+        let tile = (start + p as f64 * span / f64::from(TILE_SIZE)).max(0.0) as u32;
+        tile.min(limit - 1) / grid.size
+    };
+    let col_of = |p: i64| sector_of(region.x_start, region.width, geometry.width, p);
+    let row_of = |p: i64| sector_of(region.y_start, region.height, geometry.height, p);
+
+    for p in 0..TILE_SIZE {
+        let i = i64::from(p);
+        if col_of(i) != col_of(i - 1) {
+            for y in 0..TILE_SIZE {
+                set_pixel(pixels, p, y, SECTOR_LINE);
+            }
+        }
+        if row_of(i) != row_of(i - 1) {
+            for x in 0..TILE_SIZE {
+                set_pixel(pixels, x, p, SECTOR_LINE);
+            }
+        }
+    }
+
+    if sector_pixels < MIN_SECTOR_LABEL_PIXELS {
+        return;
+    }
+    let last = i64::from(TILE_SIZE) - 1;
+    for row in row_of(0)..=row_of(last) {
+        for col in col_of(0)..=col_of(last) {
+            let Some(sector) = grid.get(col, row) else {
+                continue;
+            };
+            let bounds = grid.bounds(sector);
+            let px =
+                (f64::from(bounds.min_x) - region.x_start) * f64::from(TILE_SIZE) / region.width;
+            let py =
+                (f64::from(bounds.min_y) - region.y_start) * f64::from(TILE_SIZE) / region.height;
+            if px < 0.0 || py < 0.0 {
+                continue;
+            }
+            let mut lines = vec![grid.number(sector)];
+            if sector_pixels >= MIN_SECTOR_DETAIL_PIXELS {
+                if !sector.name.is_empty() {
+                    lines.push(sector.name.clone());
+                }
+                lines.push(format!("{} cities", sector.city_count));
+                lines.push(format!("{:.0}% land", sector.land_fraction() * 100.0));
+            }
+            let (x, y) = (px as u32 + 4, py as u32 + 4);
+            for (i, text) in lines.iter().enumerate() {
+                let y = y + i as u32 * 10;
+                // draw_text wraps past the right edge instead of clipping.
+                if x + text.len() as u32 * ADVANCE >= TILE_SIZE || y + 10 >= TILE_SIZE {
+                    continue;
+                }
+                draw_text(pixels, TILE_SIZE, x + 1, y + 1, text, [0, 0, 0]);
+                draw_text(pixels, TILE_SIZE, x, y, text, [255, 255, 255]);
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Gold overlay
 // ---------------------------------------------------------------------------
 
 /// Draw a bright marker on every tile that lies on a gold vein.
-fn draw_gold_overlay(pixels: &mut [u8], world: &World, region: &TileRegion, seed: u32) {
-    let width = world.width();
-    let height = world.height();
-    let chunk_size = world.config().chunk_size as u32;
+fn draw_gold_overlay(
+    pixels: &mut [u8],
+    biomes: &[u8],
+    world: &World,
+    region: &TileSpan,
+    seed: u32,
+) {
+    let geometry = world.geometry();
     let sampler = GoldVeinSampler::new(seed);
 
     for py in 0..TILE_SIZE {
         for px in 0..TILE_SIZE {
-            let map_x = (region.x_start + px as f64 * region.width / TILE_SIZE as f64) as u32;
-            let map_y = (region.y_start + py as f64 * region.height / TILE_SIZE as f64) as u32;
-            let map_x = map_x.min(width - 1);
-            let map_y = map_y.min(height - 1);
-
-            let cx = map_x / chunk_size;
-            let cy = map_y / chunk_size;
-
-            if let Some(chunk) = world.chunk(cx, cy) {
-                let lx = (map_x - cx * chunk_size) as usize;
-                let ly = (map_y - cy * chunk_size) as usize;
-                let idx = ly * chunk.width as usize + lx;
-
-                let biome = Biome::from_u8(chunk.biomes[idx]);
-                if sampler.is_gold(map_x as usize, map_y as usize, biome) {
-                    // Gold vein pixel
-                    let gold: [u8; 3] = [255, 215, 0];
-                    set_pixel(pixels, px, py, gold);
-                }
+            let pos = geometry.clamp(region.pixel_pos(px, py, TILE_SIZE));
+            let biome = Biome::from_u8(biomes[(py * TILE_SIZE + px) as usize]);
+            if sampler.is_gold(pos.x as usize, pos.y as usize, biome) {
+                // Gold vein pixel
+                let gold: [u8; 3] = [255, 215, 0];
+                set_pixel(pixels, px, py, gold);
             }
         }
     }
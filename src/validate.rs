@@ -0,0 +1,919 @@
+//! World invariant checks.
+//!
+//! The generator promises a handful of rules about what it writes: cities
+//! sit on coastal Land next to open sea, villages lie well inland and apart,
+//! per-city tables line up with the city list, and every stored region id
+//! agrees with the chunks. [`validate_world`] re-checks all of them against
+//! a finished [`World`] and returns a [`Report`] listing every violation,
+//! rather than stopping at the first one.
+//!
+//! | Rule | Violation |
+//! |------|-----------|
+//! | `city_resources` / city regions / city sites / sea distances parallel to `city_slots` | [`Violation::CityTableLength`] |
+//! | City on Land inside the playable radius | [`Violation::CityNotOnLand`], [`Violation::CityOutsidePlayableArea`] |
+//! | City has enough land and water neighbours | [`Violation::CityNeighbors`] |
+//! | One water neighbour in a body of `min_water_body_size` tiles | [`Violation::CityNotCoastal`] |
+//! | Cities at least `city_spacing` apart (Chebyshev) | [`Violation::CitySpacing`] |
+//! | Villages at least `village_min_ocean_distance` inland (version 12+) | [`Violation::VillageNearOcean`] |
+//! | Villages on one island `village_spacing` apart (Chebyshev, version 12+) | [`Violation::VillageSpacing`] |
+//! | Village `offers != demands` | [`Violation::VillageTrade`] |
+//! | Stored region ids and island city counts match the chunks | [`Violation::RegionMismatch`], [`Violation::IslandCityCount`] |
+//! | Land tiles labelled, water unlabelled, summaries up to date | [`Violation::ChunkLabels`], [`Violation::ChunkSummary`] |
+//! | Each city owns its own tile; the land of islands with cities, and only that, is owned by a city of the same island (version 8+) | [`Violation::CityTerritory`], [`Violation::ChunkTerritory`] |
+//! | Sea links lead to another city within the stored radius | [`Violation::SeaLink`] |
+//! | Sector city counts match the cities inside each sector (version 10+) | [`Violation::SectorCityCount`] |
+//! | Island, sea and village names present and unique (version 11+) | [`Violation::Name`] |
+//!
+//! Checking reads every chunk once, so it takes about as long as a full
+//! scan of the world.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::coords::{ChunkPos, WorldPos};
+use crate::rect::Layers;
+use crate::save::{ChunkData, ChunkSummary};
+use crate::terrain::Terrain;
+use crate::territory;
+use crate::village::TradeResource;
+use crate::world::World;
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+/// Which stored record a [`Violation::RegionMismatch`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Index into [`World::city_slots`].
+    City(usize),
+    /// Index into [`World::villages`].
+    Village(usize),
+}
+
+/// One broken rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A per-city table does not have one entry per city slot.
+    CityTableLength {
+        table: &'static str,
+        len: usize,
+        cities: usize,
+    },
+    /// A city or village lies outside the map.
+    OutOfBounds {
+        feature: Feature,
+        pos: WorldPos,
+    },
+    CityNotOnLand {
+        city: usize,
+        pos: WorldPos,
+        terrain: Terrain,
+    },
+    CityOutsidePlayableArea {
+        city: usize,
+        pos: WorldPos,
+    },
+    /// Fewer land or water 4-neighbours than the config requires.
+    CityNeighbors {
+        city: usize,
+        pos: WorldPos,
+        land: u32,
+        water: u32,
+    },
+    /// No water neighbour belongs to a large enough water body.
+    CityNotCoastal {
+        city: usize,
+        pos: WorldPos,
+    },
+    /// Two cities closer than `city_spacing` (Chebyshev distance).
+    CitySpacing {
+        a: usize,
+        b: usize,
+        distance: u32,
+    },
+    VillageNotOnLand {
+        village: usize,
+        pos: WorldPos,
+        terrain: Terrain,
+    },
+    /// A non-Land tile within `village_min_ocean_distance` (Manhattan
+    /// distance) of a village.
+    VillageNearOcean {
+        village: usize,
+        pos: WorldPos,
+        distance: u32,
+    },
+    /// Two villages of one island closer than `village_spacing` (Chebyshev
+    /// distance).
+    VillageSpacing {
+        a: usize,
+        b: usize,
+        distance: u32,
+    },
+    /// A village placed on a city slot.
+    VillageOnCity {
+        village: usize,
+        city: usize,
+    },
+    /// A village offering the resource it demands.
+    VillageTrade {
+        village: usize,
+        resource: TradeResource,
+    },
+    /// A stored region id differs from the label in the chunks.
+    RegionMismatch {
+        feature: Feature,
+        stored: u32,
+        chunk: u32,
+    },
+    /// A city's region has no entry in the island registry.
+    MissingIsland {
+        city: usize,
+        region: u32,
+    },
+    /// An island's stored city count differs from the cities on it.
+    IslandCityCount {
+        island: u32,
+        stored: u32,
+        actual: u32,
+    },
+    /// A city lies outside its island's bounding box.
+    IslandBounds {
+        island: u32,
+        city: usize,
+    },
+    /// Land tiles with region `0`, or water tiles with a region.
+    ChunkLabels {
+        chunk: ChunkPos,
+        tiles: u32,
+    },
+    /// The stored summary disagrees with the chunk's tiles.
+    ChunkSummary {
+        chunk: ChunkPos,
+    },
+    /// The tile of a city slot belongs to another city's territory (or to
+    /// none).
+    CityTerritory {
+        city: usize,
+        owner: Option<usize>,
+    },
+    /// Tiles owned by no city of their island: water or `FarLand` with an
+    /// owner, owners on another island or past the city list, and unowned
+    /// land on islands with cities.
+    ChunkTerritory {
+        chunk: ChunkPos,
+        tiles: u32,
+    },
+    ChunkUnreadable {
+        chunk: ChunkPos,
+        error: String,
+    },
+    /// A stored sea link leads past the city list or back to its own city,
+    /// or takes longer than the radius it was computed with.
+    SeaLink {
+        from: usize,
+        to: u32,
+        travel_time: f32,
+    },
+    /// A sector's stored city count differs from the cities inside it.
+    SectorCityCount {
+        sector: String,
+        stored: u32,
+        actual: u32,
+    },
+    /// An island, sea or village without a name, or with the name of
+    /// another of its kind.
+    Name {
+        kind: &'static str,
+        index: usize,
+        name: String,
+    },
+}
+
+impl fmt::Display for Violation {
+    #[allow(clippy::too_many_lines)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CityTableLength { table, len, cities } => {
+                write!(f, "{table} has {len} entries for {cities} cities")
+            }
+            Self::OutOfBounds { feature, pos } => {
+                write!(f, "{feature} at ({}, {}) is off the map", pos.x, pos.y)
+            }
+            Self::CityNotOnLand { city, pos, terrain } => {
+                write!(f, "city {city} at ({}, {}) is on {terrain:?}", pos.x, pos.y)
+            }
+            Self::CityOutsidePlayableArea { city, pos } => write!(
+                f,
+                "city {city} at ({}, {}) is outside the playable radius",
+                pos.x, pos.y
+            ),
+            Self::CityNeighbors {
+                city,
+                pos,
+                land,
+                water,
+            } => write!(
+                f,
+                "city {city} at ({}, {}) has {land} land and {water} water neighbours",
+                pos.x, pos.y
+            ),
+            Self::CityNotCoastal { city, pos } => write!(
+                f,
+                "city {city} at ({}, {}) does not border a large water body",
+                pos.x, pos.y
+            ),
+            Self::CitySpacing { a, b, distance } => {
+                write!(f, "cities {a} and {b} are {distance} tiles apart")
+            }
+            Self::VillageNotOnLand {
+                village,
+                pos,
+                terrain,
+            } => write!(
+                f,
+                "village {village} at ({}, {}) is on {terrain:?}",
+                pos.x, pos.y
+            ),
+            Self::VillageNearOcean {
+                village,
+                pos,
+                distance,
+            } => write!(
+                f,
+                "village {village} at ({}, {}) is {distance} tiles from the sea",
+                pos.x, pos.y
+            ),
+            Self::VillageSpacing { a, b, distance } => {
+                write!(f, "villages {a} and {b} are {distance} tiles apart")
+            }
+            Self::VillageOnCity { village, city } => {
+                write!(f, "village {village} sits on city {city}")
+            }
+            Self::VillageTrade { village, resource } => write!(
+                f,
+                "village {village} offers and demands {}",
+                resource.name()
+            ),
+            Self::RegionMismatch {
+                feature,
+                stored,
+                chunk,
+            } => write!(f, "{feature} stores region {stored}, chunks say {chunk}"),
+            Self::MissingIsland { city, region } => {
+                write!(
+                    f,
+                    "city {city} is on region {region}, which is not an island"
+                )
+            }
+            Self::IslandCityCount {
+                island,
+                stored,
+                actual,
+            } => write!(f, "island {island} stores {stored} cities, has {actual}"),
+            Self::IslandBounds { island, city } => {
+                write!(f, "city {city} lies outside the bounds of island {island}")
+            }
+            Self::ChunkLabels { chunk, tiles } => write!(
+                f,
+                "chunk ({}, {}) has {tiles} tiles whose region label contradicts their terrain",
+                chunk.cx, chunk.cy
+            ),
+            Self::ChunkSummary { chunk } => write!(
+                f,
+                "chunk ({}, {}) summary does not match its tiles",
+                chunk.cx, chunk.cy
+            ),
+            Self::CityTerritory {
+                city,
+                owner: Some(owner),
+            } => write!(f, "city {city} lies in the territory of city {owner}"),
+            Self::CityTerritory { city, owner: None } => {
+                write!(f, "city {city} lies outside every territory")
+            }
+            Self::ChunkTerritory { chunk, tiles } => write!(
+                f,
+                "chunk ({}, {}) has {tiles} tiles whose owner contradicts their island",
+                chunk.cx, chunk.cy
+            ),
+            Self::ChunkUnreadable { chunk, error } => {
+                write!(f, "chunk ({}, {}) unreadable: {error}", chunk.cx, chunk.cy)
+            }
+            Self::SeaLink {
+                from,
+                to,
+                travel_time,
+            } => write!(
+                f,
+                "sea link from city {from} to city {to} takes {travel_time}, past the radius or the city list"
+            ),
+            Self::SectorCityCount {
+                sector,
+                stored,
+                actual,
+            } => write!(f, "sector {sector} stores {stored} cities, has {actual}"),
+            Self::Name { kind, index, name } if name.is_empty() => {
+                write!(f, "{kind} {index} has no name")
+            }
+            Self::Name { kind, index, name } => {
+                write!(f, "{kind} {index} is named \"{name}\" like an earlier one")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::City(i) => write!(f, "city {i}"),
+            Self::Village(i) => write!(f, "village {i}"),
+        }
+    }
+}
+
+/// Result of [`validate_world`].
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Every violation found, grouped by check in the order of the table in
+    /// the [module docs](self).
+    pub violations: Vec<Violation>,
+    pub cities_checked: usize,
+    pub villages_checked: usize,
+    pub chunks_checked: usize,
+}
+
+impl Report {
+    /// `true` if no rule is broken.
+    #[must_use]
+    pub const fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+/// Check every generator invariant against `world`.
+///
+/// Unreadable chunks are reported as violations; the rules that need them
+/// are skipped for the affected tiles.
+pub fn validate_world(world: &World) -> Report {
+    let mut report = Report {
+        cities_checked: world.city_slots().len(),
+        villages_checked: world.villages().len(),
+        chunks_checked: (world.chunks_x() * world.chunks_y()) as usize,
+        ..Report::default()
+    };
+    let v = &mut report.violations;
+    check_city_tables(world, v);
+    check_cities(world, v);
+    check_villages(world, v);
+    check_regions(world, v);
+    check_chunks(world, v);
+    if world.format_version() >= 8 {
+        check_territory(world, v);
+    }
+    check_city_distances(world, v);
+    check_sectors(world, v);
+    if world.format_version() >= 11 {
+        check_names(world, v);
+    }
+    report
+}
+
+fn check_city_tables(world: &World, v: &mut Vec<Violation>) {
+    let cities = world.city_slots().len();
+    let mut tables = vec![
+        ("city_resources", world.city_resources().len()),
+        ("city_regions", world.city_regions().len()),
+    ];
+    // Absent before format version 7.
+    if !world.city_sites().is_empty() {
+        tables.push(("city_sites", world.city_sites().len()));
+    }
+    // Only stored when computed.
+    if let Some(distances) = world.city_distances() {
+        tables.push(("city_distances", distances.city_count()));
+    }
+    for (table, len) in tables {
+        if len != cities {
+            v.push(Violation::CityTableLength { table, len, cities });
+        }
+    }
+}
+
+fn check_cities(world: &World, v: &mut Vec<Violation>) {
+    let config = world.config();
+    let center = f64::from(config.map_size / 2);
+    let radius = f64::from(config.playable_radius);
+    let min_body = config.min_water_body_size as usize;
+    let terrain = TerrainLookup::new(world);
+    // Water tiles already known to belong to a large body.
+    let mut open_sea = HashSet::new();
+
+    for (city, &slot) in world.city_slots().iter().enumerate() {
+        let pos = WorldPos::from(slot);
+        let Some(t) = terrain.at(pos) else {
+            v.push(Violation::OutOfBounds {
+                feature: Feature::City(city),
+                pos,
+            });
+            continue;
+        };
+        if t != Terrain::Land {
+            v.push(Violation::CityNotOnLand {
+                city,
+                pos,
+                terrain: t,
+            });
+        }
+        let (dx, dy) = (f64::from(pos.x) - center, f64::from(pos.y) - center);
+        if dx.hypot(dy) > radius {
+            v.push(Violation::CityOutsidePlayableArea { city, pos });
+        }
+
+        let (mut land, mut water_count) = (0, 0);
+        let mut water = Vec::new();
+        for n in neighbors_4(pos) {
+            match terrain.at(n) {
+                Some(Terrain::Land) => land += 1,
+                Some(Terrain::Water) => {
+                    water_count += 1;
+                    water.push(n);
+                }
+                _ => {}
+            }
+        }
+        if land < u32::from(config.min_land_neighbors)
+            || water_count < u32::from(config.min_water_neighbors)
+        {
+            v.push(Violation::CityNeighbors {
+                city,
+                pos,
+                land,
+                water: water_count,
+            });
+        }
+        if !water
+            .iter()
+            .any(|&w| is_large_water(&terrain, w, min_body, &mut open_sea))
+        {
+            v.push(Violation::CityNotCoastal { city, pos });
+        }
+    }
+
+    let spacing = u32::from(config.city_spacing);
+    check_spacing(
+        world.city_slots(),
+        spacing,
+        |_, _| true,
+        |a, b, distance| v.push(Violation::CitySpacing { a, b, distance }),
+    );
+}
+
+fn check_villages(world: &World, v: &mut Vec<Violation>) {
+    let config = world.config();
+    let geometry = world.geometry();
+    // Files older than version 12 do not store the rules their villages
+    // were placed with, so neither rule is checked for them.
+    let rules = world.format_version() >= 12;
+    let min_ocean = if rules {
+        config.village_min_ocean_distance
+    } else {
+        0
+    };
+    let villages = world.villages();
+    let cities: HashMap<(u32, u32), usize> = world
+        .city_slots()
+        .iter()
+        .enumerate()
+        .map(|(i, &slot)| (slot, i))
+        .collect();
+
+    for (village, vil) in villages.iter().enumerate() {
+        let pos = WorldPos::new(u32::from(vil.x), u32::from(vil.y));
+        if !geometry.contains(pos) {
+            v.push(Violation::OutOfBounds {
+                feature: Feature::Village(village),
+                pos,
+            });
+            continue;
+        }
+        if vil.trade.offers == vil.trade.demands {
+            v.push(Violation::VillageTrade {
+                village,
+                resource: vil.trade.offers,
+            });
+        }
+        if let Some(&city) = cities.get(&(pos.x, pos.y)) {
+            v.push(Violation::VillageOnCity { village, city });
+        }
+
+        // Every tile closer than `min_ocean` (Manhattan) must be Land.
+        let reach = min_ocean.saturating_sub(1);
+        let min = WorldPos::new(pos.x.saturating_sub(reach), pos.y.saturating_sub(reach));
+        let max = geometry.clamp(WorldPos::new(
+            pos.x.saturating_add(reach),
+            pos.y.saturating_add(reach),
+        ));
+        let Ok(rect) = world.read_rect(min, max.x - min.x + 1, max.y - min.y + 1, Layers::TERRAIN)
+        else {
+            // Reported by the chunk scan.
+            continue;
+        };
+        let here = Terrain::from_u8(rect.terrain[rect.index_of(pos)]);
+        if here != Terrain::Land {
+            v.push(Violation::VillageNotOnLand {
+                village,
+                pos,
+                terrain: here,
+            });
+            continue;
+        }
+        let nearest = (0..rect.terrain.len())
+            .filter(|&idx| rect.terrain[idx] != Terrain::Land.to_u8())
+            .map(|idx| {
+                let t = rect.tile(idx).pos;
+                t.x.abs_diff(pos.x) + t.y.abs_diff(pos.y)
+            })
+            .min();
+        if let Some(distance) = nearest.filter(|&d| d < min_ocean) {
+            v.push(Violation::VillageNearOcean {
+                village,
+                pos,
+                distance,
+            });
+        }
+    }
+
+    if !rules {
+        return;
+    }
+    let points: Vec<(u32, u32)> = villages
+        .iter()
+        .map(|v| (u32::from(v.x), u32::from(v.y)))
+        .collect();
+    check_spacing(
+        &points,
+        config.village_spacing,
+        |a, b| villages[a].region_id == villages[b].region_id,
+        |a, b, distance| v.push(Violation::VillageSpacing { a, b, distance }),
+    );
+}
+
+/// Stored region ids of cities and villages against the chunks, and the
+/// island registry against the cities.
+fn check_regions(world: &World, v: &mut Vec<Violation>) {
+    let geometry = world.geometry();
+    for (i, (&slot, &stored)) in world
+        .city_slots()
+        .iter()
+        .zip(world.city_regions())
+        .enumerate()
+    {
+        let pos = WorldPos::from(slot);
+        let chunk = world.region_label_at(pos);
+        if geometry.contains(pos) && chunk != stored {
+            v.push(Violation::RegionMismatch {
+                feature: Feature::City(i),
+                stored,
+                chunk,
+            });
+        }
+    }
+    for (i, village) in world.villages().iter().enumerate() {
+        let pos = WorldPos::new(u32::from(village.x), u32::from(village.y));
+        let chunk = world.region_label_at(pos);
+        if geometry.contains(pos) && chunk != village.region_id {
+            v.push(Violation::RegionMismatch {
+                feature: Feature::Village(i),
+                stored: village.region_id,
+                chunk,
+            });
+        }
+    }
+
+    let islands = world.islands();
+    let mut actual: HashMap<u32, u32> = HashMap::new();
+    for (city, (&region, &(x, y))) in world
+        .city_regions()
+        .iter()
+        .zip(world.city_slots())
+        .enumerate()
+    {
+        let Ok(i) = islands.binary_search_by_key(&region, |island| island.id) else {
+            v.push(Violation::MissingIsland { city, region });
+            continue;
+        };
+        *actual.entry(region).or_default() += 1;
+        let bb = islands[i].bounds;
+        if x < bb.min_x || x > bb.max_x || y < bb.min_y || y > bb.max_y {
+            v.push(Violation::IslandBounds {
+                island: region,
+                city,
+            });
+        }
+    }
+    for island in islands {
+        let count = actual.get(&island.id).copied().unwrap_or(0);
+        if count != island.city_count {
+            v.push(Violation::IslandCityCount {
+                island: island.id,
+                stored: island.city_count,
+                actual: count,
+            });
+        }
+    }
+}
+
+/// Region labels against terrain, stored summaries and territory against
+/// tiles, for every chunk.
+fn check_chunks(world: &World, v: &mut Vec<Violation>) {
+    // Files older than version 8 store no territory.
+    let territory = (world.format_version() >= 8).then(|| TerritoryRules::new(world));
+    let positions: Vec<ChunkPos> = (0..world.chunks_y())
+        .flat_map(|cy| (0..world.chunks_x()).map(move |cx| ChunkPos::new(cx, cy)))
+        .collect();
+    let found: Vec<Vec<Violation>> = positions
+        .into_par_iter()
+        .map(|pos| {
+            let chunk = match world.chunk(pos) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    return vec![Violation::ChunkUnreadable {
+                        chunk: pos,
+                        error: e.to_string(),
+                    }]
+                }
+            };
+            let mut found = Vec::new();
+            let land = Terrain::Land.to_u8();
+            let tiles = count_u32(
+                chunk
+                    .terrain
+                    .iter()
+                    .zip(&chunk.region_labels)
+                    .filter(|&(&t, &r)| (t == land) != (r != 0))
+                    .count(),
+            );
+            if tiles > 0 {
+                found.push(Violation::ChunkLabels { chunk: pos, tiles });
+            }
+            if let Some(rules) = &territory {
+                let tiles = rules.bad_tiles(&chunk);
+                if tiles > 0 {
+                    found.push(Violation::ChunkTerritory { chunk: pos, tiles });
+                }
+            }
+            if let Some(stored) = world.chunk_summary(pos) {
+                // Summaries are computed before elevations are quantized for
+                // storage, so only the exact fields are compared.
+                let fresh = ChunkSummary::from_chunk(&chunk);
+                if stored.land_tiles != fresh.land_tiles
+                    || stored.biome_counts != fresh.biome_counts
+                    || stored.region_ids != fresh.region_ids
+                {
+                    found.push(Violation::ChunkSummary { chunk: pos });
+                }
+            }
+            found
+        })
+        .collect();
+    v.extend(found.into_iter().flatten());
+}
+
+/// Every city's own tile against its territory.
+fn check_territory(world: &World, v: &mut Vec<Violation>) {
+    let geometry = world.geometry();
+    for (city, &slot) in world.city_slots().iter().enumerate() {
+        // Off-map cities and unreadable chunks are reported elsewhere.
+        let Some((pos, idx)) = geometry.tile_index(slot.into()) else {
+            continue;
+        };
+        let Ok(chunk) = world.chunk(pos) else {
+            continue;
+        };
+        let owner = territory::owner_index(chunk.territory[idx]);
+        if owner != Some(city) {
+            v.push(Violation::CityTerritory { city, owner });
+        }
+    }
+}
+
+/// Every stored sea link against the city list and the radius.
+fn check_city_distances(world: &World, v: &mut Vec<Violation>) {
+    let Some(distances) = world.city_distances() else {
+        return;
+    };
+    let cities = world.city_slots().len();
+    for (from, link) in distances.iter() {
+        let valid = (link.city as usize) < cities
+            && link.city as usize != from
+            && link.travel_time <= distances.radius;
+        if !valid {
+            v.push(Violation::SeaLink {
+                from,
+                to: link.city,
+                travel_time: link.travel_time,
+            });
+        }
+    }
+}
+
+/// Every sector's stored city count against the city list.
+fn check_sectors(world: &World, v: &mut Vec<Violation>) {
+    let Some(grid) = world.sectors() else {
+        return;
+    };
+    let mut actual = HashMap::new();
+    for &slot in world.city_slots() {
+        if let Some(s) = grid.at(slot.into()) {
+            *actual.entry((s.col, s.row)).or_insert(0u32) += 1;
+        }
+    }
+    for s in grid.sectors() {
+        let actual = actual.get(&(s.col, s.row)).copied().unwrap_or(0);
+        if s.city_count != actual {
+            v.push(Violation::SectorCityCount {
+                sector: grid.number(s),
+                stored: s.city_count,
+                actual,
+            });
+        }
+    }
+}
+
+/// Every stored name against the others of its kind. Islands are
+/// reported by id, seas and villages by index.
+fn check_names(world: &World, v: &mut Vec<Violation>) {
+    let islands: Vec<(usize, &str)> = world
+        .islands()
+        .iter()
+        .map(|i| (i.id as usize, i.name.as_str()))
+        .collect();
+    let seas: Vec<(usize, &str)> = world
+        .sectors()
+        .map(|g| {
+            g.sectors()
+                .iter()
+                .map(|s| s.name.as_str())
+                .enumerate()
+                .collect()
+        })
+        .unwrap_or_default();
+    let villages: Vec<(usize, &str)> = world
+        .village_names()
+        .iter()
+        .map(String::as_str)
+        .enumerate()
+        .collect();
+
+    for (kind, names) in [("island", islands), ("sea", seas), ("village", villages)] {
+        let mut seen = HashSet::new();
+        for (index, name) in names {
+            if name.is_empty() || !seen.insert(name) {
+                v.push(Violation::Name {
+                    kind,
+                    index,
+                    name: name.to_owned(),
+                });
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// What the territory of a tile must look like, from the city list.
+struct TerritoryRules<'a> {
+    city_regions: &'a [u32],
+    /// Regions with at least one city.
+    settled: HashSet<u32>,
+}
+
+impl<'a> TerritoryRules<'a> {
+    fn new(world: &'a World) -> Self {
+        let city_regions = world.city_regions();
+        Self {
+            city_regions,
+            settled: city_regions.iter().copied().collect(),
+        }
+    }
+
+    /// Tiles of `chunk` whose owner breaks the rules.
+    #[allow(clippy::cast_possible_truncation)]
+    fn bad_tiles(&self, chunk: &ChunkData) -> u32 {
+        let land = Terrain::Land.to_u8();
+        count_u32(
+            (0..chunk.territory.len())
+                .filter(|&idx| {
+                    let region = chunk.region_labels[idx];
+                    territory::owner_index(chunk.territory[idx]).map_or_else(
+                        || chunk.terrain[idx] == land && self.settled.contains(&region),
+                        |owner| {
+                            chunk.terrain[idx] != land
+                                || self.city_regions.get(owner) != Some(&region)
+                        },
+                    )
+                })
+                .count(),
+        )
+    }
+}
+
+/// A tile count as stored in a [`Violation`]; chunks never come close to
+/// `u32::MAX` tiles.
+fn count_u32(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+/// Terrain of single tiles, read through the world's chunk cache.
+struct TerrainLookup<'a> {
+    world: &'a World,
+}
+
+impl<'a> TerrainLookup<'a> {
+    const fn new(world: &'a World) -> Self {
+        Self { world }
+    }
+
+    /// `None` if `pos` is off the map or its chunk cannot be read.
+    fn at(&self, pos: WorldPos) -> Option<Terrain> {
+        let (chunk, idx) = self.world.geometry().tile_index(pos)?;
+        let chunk = self.world.chunk(chunk).ok()?;
+        Some(Terrain::from_u8(chunk.terrain[idx]))
+    }
+}
+
+/// In-bounds 4-neighbours of `pos` (the far edges are filtered by the lookup).
+fn neighbors_4(pos: WorldPos) -> impl Iterator<Item = WorldPos> {
+    [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .into_iter()
+        .filter_map(move |(dx, dy)| pos.offset(dx, dy))
+}
+
+/// `true` if the water body containing `start` has at least `min_body`
+/// tiles.
+///
+/// Floods from `start` and stops as soon as `min_body` tiles are reached,
+/// so open sea costs `min_body` lookups at most. Every tile of a body found
+/// to be large is added to `open_sea`, which later floods stop at.
+fn is_large_water(
+    terrain: &TerrainLookup,
+    start: WorldPos,
+    min_body: usize,
+    open_sea: &mut HashSet<WorldPos>,
+) -> bool {
+    if open_sea.contains(&start) {
+        return true;
+    }
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(pos) = queue.pop_front() {
+        if open_sea.contains(&pos) || seen.len() >= min_body {
+            open_sea.extend(seen);
+            return true;
+        }
+        for n in neighbors_4(pos) {
+            if terrain.at(n) == Some(Terrain::Water) && seen.insert(n) {
+                queue.push_back(n);
+            }
+        }
+    }
+    false
+}
+
+/// Report every pair of `points` with `related(a, b)` that lies closer than
+/// `spacing` in Chebyshev distance.
+fn check_spacing(
+    points: &[(u32, u32)],
+    spacing: u32,
+    related: impl Fn(usize, usize) -> bool,
+    mut report: impl FnMut(usize, usize, u32),
+) {
+    if spacing == 0 {
+        return;
+    }
+    // Sort by x so each point only compares against a sliding window.
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_unstable_by_key(|&i| points[i]);
+    for (k, &a) in order.iter().enumerate() {
+        let (ax, ay) = points[a];
+        for &b in &order[k + 1..] {
+            let (bx, by) = points[b];
+            if bx - ax >= spacing {
+                break;
+            }
+            let distance = (bx - ax).max(ay.abs_diff(by));
+            if distance < spacing && related(a, b) {
+                report(a.min(b), a.max(b), distance);
+            }
+        }
+    }
+}
@@ -1,130 +1,745 @@
 //! High-level world access.
 //!
-//! [`World`] wraps a [`ChunkedWorldReader`](crate::save::ChunkedWorldReader)
-//! with a chunk cache and lazy island discovery.
+//! [`World`] wraps a [`WorldBackend`] -- a world file on disk or generated
+//! data held in memory -- with a bounded [`SharedChunkCache`], a LOD level
+//! cache and the island registry. Chunks are returned as `Arc<ChunkData>` so
+//! the cache can evict them while callers are still using them. Files older than format
+//! version 5 do not store the registry; it is discovered lazily from the
+//! chunks instead.
+//!
+//! [`World::tile_at`] answers "what is at this tile?" in one call, returning a
+//! [`TileInfo`] with everything the file (and the world seed) knows about it.
+//!
+//! [`World::read_rect`] copies chosen layers of any world rectangle into one
+//! contiguous buffer, hiding chunk boundaries.
+//!
+//! [`World::island`] gathers the cities, villages and resources of one
+//! island; [`World::sector_at`] finds the numbered ocean sector of a tile.
+//!
+//! [`World::city_index`] and [`World::village_index`] answer bbox, radius and
+//! nearest-neighbour queries without scanning every city or village.
+//!
+//! Every query takes `&self` and the caches use interior mutability, so a
+//! `World` is `Sync`: wrap it in an [`Arc`] and share it between threads.
 
 use std::collections::HashMap;
 use std::io;
+use std::sync::{Arc, Mutex, OnceLock};
 
-use crate::biome::CityResources;
+use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::backend::{MemoryBackend, WorldBackend};
+use crate::biome::{Biome, CityResources, GoldVeinSampler, ResourceModifiers};
+use crate::cache::{lock, CacheBudget, CacheStats, SharedChunkCache};
+use crate::city::CitySite;
 use crate::config::WorldConfig;
-use crate::island::{self, Island};
-use crate::save::{ChunkData, ChunkedWorldReader};
+use crate::coords::{ChunkPos, MapGeometry, WorldPos};
+use crate::island::{self, Island, IslandDetails, ResourceTotals};
+use crate::lod::{LodInfo, LodLevel};
+use crate::navigation::{self, CityDistances, LandOptions, NavGrid, NavOptions, NavPath};
+use crate::rect::{Layers, RectData, RectTile};
+use crate::save::{ChunkData, ChunkSummary, ChunkedWorldReader, WorldData};
+use crate::sector::{Sector, SectorGrid};
+use crate::spatial::SpatialIndex;
+use crate::terrain::Terrain;
+use crate::territory;
 use crate::village::Village;
 
+/// Everything known about a single world tile.
+#[derive(Debug, Clone, Copy)]
+pub struct TileInfo {
+    pub pos: WorldPos,
+    pub terrain: Terrain,
+    pub biome: Biome,
+    /// Raw elevation in `[0, 1]`.
+    pub elevation: f32,
+    /// Region label (landmass id), `0` for water.
+    pub region_id: u32,
+    /// `true` if the tile lies on a gold vein (see [`GoldVeinSampler`]).
+    pub gold_vein: bool,
+    /// Passive resource modifiers of the tile's biome.
+    pub resources: ResourceModifiers,
+    /// Closest city slot, or `None` if the world has no cities.
+    pub nearest_city: Option<NearestCity>,
+    /// Index into [`World::city_slots`] of the city whose territory the
+    /// tile belongs to, or `None` for water and islands without cities.
+    pub owner: Option<usize>,
+}
+
+/// A city slot found by a proximity query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NearestCity {
+    /// Index into [`World::city_slots`].
+    pub index: usize,
+    pub pos: WorldPos,
+    /// Straight-line distance in tiles.
+    pub distance: f64,
+}
+
 pub struct World {
-    reader: ChunkedWorldReader,
-    chunk_cache: HashMap<(u32, u32), ChunkData>,
-    islands: Option<Vec<Island>>,
+    backend: Box<dyn WorldBackend>,
+    chunk_cache: SharedChunkCache,
+    lod_cache: Mutex<HashMap<usize, Arc<LodLevel>>>,
+    islands: OnceLock<Vec<Island>>,
+    city_regions: OnceLock<Vec<u32>>,
+    gold_sampler: OnceLock<GoldVeinSampler>,
+    city_index: OnceLock<SpatialIndex>,
+    village_index: OnceLock<SpatialIndex>,
+    nav_grid: Mutex<Option<Arc<NavGrid>>>,
 }
 
+// `World` is shared across threads by the viewer and game servers.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<World>();
+};
+
 impl World {
-    /// Open a world file and read its header.
+    /// Open a world file and read its header, with the default cache budget.
+    ///
+    /// # Errors
+    ///
+    /// Any error opening the file or reading its header.
     pub fn open(path: &str) -> io::Result<Self> {
+        Self::open_with_cache(path, CacheBudget::default())
+    }
+
+    /// Open a world file with a chunk cache bounded by `budget`.
+    ///
+    /// # Errors
+    ///
+    /// Any error opening the file or reading its header.
+    pub fn open_with_cache(path: &str, budget: CacheBudget) -> io::Result<Self> {
         let reader = ChunkedWorldReader::open(path)?;
-        Ok(Self {
-            reader,
-            chunk_cache: HashMap::new(),
-            islands: None,
-        })
+        Ok(Self::from_backend(Box::new(reader), budget))
+    }
+
+    /// Wrap freshly generated data without writing it to disk.
+    ///
+    /// Queries return exactly what they would on a saved and reopened copy.
+    #[must_use]
+    pub fn from_data(data: WorldData) -> Self {
+        Self::from_backend(Box::new(MemoryBackend::new(data)), CacheBudget::default())
+    }
+
+    /// Wrap any backend with a chunk cache bounded by `budget`.
+    #[must_use]
+    pub fn from_backend(backend: Box<dyn WorldBackend>, budget: CacheBudget) -> Self {
+        let islands = OnceLock::new();
+        if let Some(stored) = &backend.header().islands {
+            let _ = islands.set(stored.clone());
+        }
+        let city_regions = OnceLock::new();
+        if let Some(stored) = &backend.header().city_regions {
+            let _ = city_regions.set(stored.clone());
+        }
+        Self {
+            backend,
+            chunk_cache: SharedChunkCache::new(budget),
+            lod_cache: Mutex::new(HashMap::new()),
+            islands,
+            city_regions,
+            gold_sampler: OnceLock::new(),
+            city_index: OnceLock::new(),
+            village_index: OnceLock::new(),
+            nav_grid: Mutex::new(None),
+        }
     }
 
     // -- Header accessors ---------------------------------------------------
 
     pub fn config(&self) -> &WorldConfig {
-        &self.reader.header.config
+        &self.backend.header().config
     }
     pub fn width(&self) -> u32 {
-        self.reader.header.width
+        self.backend.header().width
     }
     pub fn height(&self) -> u32 {
-        self.reader.header.height
+        self.backend.header().height
     }
     pub fn chunks_x(&self) -> u32 {
-        self.reader.header.chunks_x
+        self.backend.header().chunks_x
     }
     pub fn chunks_y(&self) -> u32 {
-        self.reader.header.chunks_y
+        self.backend.header().chunks_y
+    }
+
+    /// Format version of the file the world was read from.
+    pub fn format_version(&self) -> u8 {
+        self.backend.header().format_version
+    }
+
+    /// Map and chunk dimensions, for converting between coordinate types.
+    pub fn geometry(&self) -> MapGeometry {
+        self.backend.header().geometry()
     }
 
     /// All city slot positions from the file header.
     pub fn city_slots(&self) -> &[(u32, u32)] {
-        &self.reader.header.city_slots
+        &self.backend.header().city_slots
+    }
+
+    /// Region label (island id) of every city slot, parallel to
+    /// [`city_slots`](Self::city_slots).
+    ///
+    /// Files older than version 6 do not store them: the first call looks
+    /// each one up in the chunks.
+    pub fn city_regions(&self) -> &[u32] {
+        self.city_regions.get_or_init(|| {
+            self.city_slots()
+                .iter()
+                .map(|&slot| self.region_label_at(slot.into()))
+                .collect()
+        })
     }
 
     /// Per-city aggregated resource profiles, parallel to [`city_slots`](Self::city_slots).
     pub fn city_resources(&self) -> &[CityResources] {
-        &self.reader.header.city_resources
+        &self.backend.header().city_resources
+    }
+
+    /// Per-city surroundings (facing, harbor quality, coast type),
+    /// parallel to [`city_slots`](Self::city_slots).
+    ///
+    /// Empty for files older than version 7; [`migrate`](crate::migrate)
+    /// them to fill it in.
+    pub fn city_sites(&self) -> &[CitySite] {
+        self.backend.header().city_sites.as_deref().unwrap_or(&[])
     }
+
+    /// Sea neighbours of every city, if the file stores them (version 9+,
+    /// generated with `CITY_DISTANCE_RADIUS` or updated with
+    /// [`store_city_distances`](navigation::store_city_distances)).
+    pub fn city_distances(&self) -> Option<&CityDistances> {
+        self.backend
+            .header()
+            .city_distances
+            .as_ref()
+            .filter(|d| d.radius > 0.0)
+    }
+
     /// All villages stored in the world file header.
     /// Returns an empty slice for version-1 files.
     pub fn villages(&self) -> &[Village] {
-        &self.reader.header.villages
+        &self.backend.header().villages
+    }
+
+    /// Generated name of every village, parallel to
+    /// [`villages`](Self::villages).
+    ///
+    /// Empty for files older than version 11 and for worlds generated
+    /// without `VILLAGE_NAMES`.
+    pub fn village_names(&self) -> &[String] {
+        self.backend
+            .header()
+            .village_names
+            .as_deref()
+            .unwrap_or(&[])
+    }
+
+    /// Spatial index over [`city_slots`](Self::city_slots), built on first use.
+    ///
+    /// Query results are indices into `city_slots()` and `city_resources()`.
+    pub fn city_index(&self) -> &SpatialIndex {
+        self.city_index.get_or_init(|| {
+            SpatialIndex::new(self.city_slots().to_vec(), self.width(), self.height())
+        })
+    }
+
+    /// Spatial index over [`villages`](Self::villages), built on first use.
+    ///
+    /// Query results are indices into `villages()`.
+    pub fn village_index(&self) -> &SpatialIndex {
+        self.village_index.get_or_init(|| {
+            let points = self
+                .villages()
+                .iter()
+                .map(|v| (u32::from(v.x), u32::from(v.y)))
+                .collect();
+            SpatialIndex::new(points, self.width(), self.height())
+        })
     }
 
     // -- Chunk management ---------------------------------------------------
 
-    /// Load a chunk into the cache if it isn't already there.
-    pub fn ensure_chunk(&mut self, cx: u32, cy: u32) -> io::Result<()> {
-        if !self.chunk_cache.contains_key(&(cx, cy)) {
-            let chunk = self.reader.load_chunk(cx, cy)?;
-            self.chunk_cache.insert((cx, cy), chunk);
-        }
-        Ok(())
+    /// Get chunk `pos`, loading it into the cache if needed.
+    ///
+    /// The returned handle stays valid even if the cache later evicts the
+    /// chunk.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if `pos` is outside the
+    /// chunk grid, or any error reading the chunk.
+    pub fn chunk(&self, pos: ChunkPos) -> io::Result<Arc<ChunkData>> {
+        let ChunkPos { cx, cy } = pos;
+        self.chunk_cache
+            .get_or_load((cx, cy), || self.backend.load_chunk(cx, cy))
     }
 
-    /// Get a reference to a cached chunk.
+    /// Get chunk `pos` only if it is already cached.
     ///
-    /// Returns `None` if the chunk has not been loaded yet - call
-    /// [`ensure_chunk`](Self::ensure_chunk) first.
-    pub fn chunk(&self, cx: u32, cy: u32) -> Option<&ChunkData> {
-        self.chunk_cache.get(&(cx, cy))
+    /// Does not load anything or affect eviction order.
+    pub fn cached_chunk(&self, pos: ChunkPos) -> Option<Arc<ChunkData>> {
+        self.chunk_cache.peek((pos.cx, pos.cy))
     }
 
-    // -- Island discovery ---------------------------------------------------
+    /// Load chunk `pos` and pin it so it is never evicted.
+    ///
+    /// The pin is held once this returns `Ok`, even with other threads
+    /// loading chunks. Pins nest; release each with
+    /// [`unpin_chunk`](Self::unpin_chunk).
+    ///
+    /// # Errors
+    ///
+    /// As [`chunk`](Self::chunk); nothing is pinned on failure.
+    pub fn pin_chunk(&self, pos: ChunkPos) -> io::Result<Arc<ChunkData>> {
+        let ChunkPos { cx, cy } = pos;
+        self.chunk_cache
+            .get_or_load_pinned((cx, cy), || self.backend.load_chunk(cx, cy))
+    }
 
-    /// Discover all islands (loads every chunk into the cache).
+    /// Release one pin taken by [`pin_chunk`](Self::pin_chunk).
+    pub fn unpin_chunk(&self, pos: ChunkPos) {
+        self.chunk_cache.unpin((pos.cx, pos.cy));
+    }
+
+    /// Hit/miss counters and current occupancy of the chunk cache.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.chunk_cache.stats()
+    }
+
+    /// Change the chunk cache budget, evicting immediately if needed.
+    pub fn set_cache_budget(&self, budget: CacheBudget) {
+        self.chunk_cache.set_budget(budget);
+    }
+
+    /// Summary statistics of chunk `pos`, available without loading it.
     ///
-    /// This is a no-op if islands have already been computed.
-    pub fn ensure_islands_computed(&mut self) {
-        if self.islands.is_none() {
-            self.islands = Some(island::discover_islands(
-                &self.reader,
-                &mut self.chunk_cache,
-            ));
+    /// Returns `None` for files older than version 4.
+    pub fn chunk_summary(&self, pos: ChunkPos) -> Option<&ChunkSummary> {
+        self.backend.chunk_summary(pos.cx, pos.cy)
+    }
+
+    // -- LOD pyramid --------------------------------------------------------
+
+    /// Stored LOD levels, finest first. Empty for files older than version 3.
+    pub fn lod_levels(&self) -> &[LodInfo] {
+        &self.backend.header().lod_levels
+    }
+
+    /// Get LOD level `level`, loading it into the cache if needed.
+    ///
+    /// LOD levels are small and never evicted.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if there is no such
+    /// level, or any error reading it.
+    pub fn lod_level(&self, level: usize) -> io::Result<Arc<LodLevel>> {
+        if let Some(lod) = lock(&self.lod_cache).get(&level) {
+            return Ok(Arc::clone(lod));
         }
+        let lod = Arc::new(self.backend.load_lod_level(level)?);
+        Ok(Arc::clone(
+            lock(&self.lod_cache).entry(level).or_insert(lod),
+        ))
     }
 
-    /// Return the discovered islands.
+    // -- Islands ------------------------------------------------------------
+
+    /// Return the island registry, sorted by id, with the generated island
+    /// names (empty before version 11).
     ///
-    /// Returns an empty slice if [`ensure_islands_computed`](Self::ensure_islands_computed)
-    /// has not been called yet.
+    /// Files older than version 5 do not store the registry: the first call
+    /// discovers it from the chunks (decompressing every chunk that contains
+    /// a city-bearing island) and later calls reuse the result.
     pub fn islands(&self) -> &[Island] {
-        self.islands.as_deref().unwrap_or(&[])
+        self.islands
+            .get_or_init(|| island::discover_islands(self.backend.as_ref()))
+    }
+
+    /// Cities, villages, resource totals and biome histogram of island `id`.
+    ///
+    /// Returns `Ok(None)` if `id` is not in the [registry](Self::islands).
+    /// Reads every chunk the island's bounding box overlaps.
+    ///
+    /// # Errors
+    ///
+    /// Any error reading those chunks.
+    pub fn island(&self, id: u32) -> io::Result<Option<IslandDetails>> {
+        let islands = self.islands();
+        let Ok(pos) = islands.binary_search_by_key(&id, |i| i.id) else {
+            return Ok(None);
+        };
+        let island = islands[pos].clone();
+
+        let city_resources = self.city_resources();
+        let mut resources = ResourceTotals::default();
+        let cities: Vec<usize> = self
+            .city_regions()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &rid)| rid == id)
+            .map(|(i, _)| i)
+            .collect();
+        for &i in &cities {
+            if let Some(cr) = city_resources.get(i) {
+                resources.add(cr);
+            }
+        }
+
+        let villages = self
+            .villages()
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.region_id == id)
+            .map(|(i, _)| i)
+            .collect();
+
+        let bb = island.bounds;
+        let rect = self.read_rect(
+            WorldPos::new(bb.min_x, bb.min_y),
+            bb.max_x - bb.min_x + 1,
+            bb.max_y - bb.min_y + 1,
+            Layers::REGION_LABELS | Layers::BIOMES,
+        )?;
+        let gold = self.gold_sampler();
+        let mut histogram = [0u32; 256];
+        let mut gold_vein_tiles = 0;
+        for (idx, (&rid, &b)) in rect.region_labels.iter().zip(&rect.biomes).enumerate() {
+            if rid != id {
+                continue;
+            }
+            histogram[b as usize] += 1;
+            let tile = rect.tile(idx);
+            if gold.is_gold(tile.pos.x as usize, tile.pos.y as usize, tile.biome) {
+                gold_vein_tiles += 1;
+            }
+        }
+        let biome_counts = histogram
+            .iter()
+            .zip(0..=u8::MAX)
+            .filter(|(&n, _)| n > 0)
+            .map(|(&n, b)| (b, n))
+            .collect();
+
+        Ok(Some(IslandDetails {
+            island,
+            cities,
+            villages,
+            resources,
+            gold_vein_tiles,
+            biome_counts,
+        }))
+    }
+
+    // -- Ocean sectors ------------------------------------------------------
+
+    /// The numbered ocean sector grid, if the file stores one (version 10+,
+    /// `SECTOR_SIZE` not `0`).
+    pub fn sectors(&self) -> Option<&SectorGrid> {
+        self.backend
+            .header()
+            .sectors
+            .as_ref()
+            .filter(|g| !g.is_empty())
+    }
+
+    /// The sector containing `pos`, or `None` off the map or without a
+    /// [sector grid](Self::sectors).
+    pub fn sector_at(&self, pos: WorldPos) -> Option<&Sector> {
+        self.sectors()?.at(pos)
+    }
+
+    // -- Navigation ---------------------------------------------------------
+
+    /// The coarse naval navigation grid, built on first use.
+    ///
+    /// Building reads every chunk once (seconds on a 10,000-tile map); every
+    /// later call, from any thread, shares the same grid.
+    ///
+    /// # Errors
+    ///
+    /// Any error reading a chunk while building the grid; the next call
+    /// tries again.
+    pub fn nav_grid(&self) -> io::Result<Arc<NavGrid>> {
+        let mut cached = lock(&self.nav_grid);
+        if let Some(grid) = &*cached {
+            return Ok(Arc::clone(grid));
+        }
+        let grid = Arc::new(NavGrid::build(self)?);
+        *cached = Some(Arc::clone(&grid));
+        drop(cached);
+        Ok(grid)
+    }
+
+    /// Sea route from `from` to `to` (see [`navigation`]).
+    ///
+    /// Each end must be a water tile or a land tile with a water
+    /// 4-neighbour, such as a city slot. Returns `Ok(None)` if either end
+    /// has no water or the two are not connected by sea.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if either end is off
+    /// the map or `options` fail [`NavOptions::validate`], or any error
+    /// reading the chunks along the route.
+    pub fn naval_path(
+        &self,
+        from: WorldPos,
+        to: WorldPos,
+        options: &NavOptions,
+    ) -> io::Result<Option<NavPath>> {
+        options.validate()?;
+        self.locate(from)?;
+        self.locate(to)?;
+        let grid = self.nav_grid()?;
+        navigation::find_naval_path(self, &grid, from, to, options)
+    }
+
+    /// Sea route between city slots `a` and `b` (indices into
+    /// [`city_slots`](Self::city_slots)), as [`naval_path`](Self::naval_path).
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if either index is out
+    /// of range, otherwise as [`naval_path`](Self::naval_path).
+    pub fn city_naval_path(
+        &self,
+        a: usize,
+        b: usize,
+        options: &NavOptions,
+    ) -> io::Result<Option<NavPath>> {
+        self.naval_path(self.city_pos(a)?, self.city_pos(b)?, options)
     }
 
-    // -- Coordinate helpers -------------------------------------------------
+    /// Overland route from `from` to `to` (see [`navigation`]).
+    ///
+    /// Returns `Ok(None)` unless both ends are land tiles of the same
+    /// region.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if either end is off
+    /// the map, or any error reading the chunks along the route.
+    pub fn land_path(
+        &self,
+        from: WorldPos,
+        to: WorldPos,
+        options: &LandOptions,
+    ) -> io::Result<Option<NavPath>> {
+        navigation::find_land_path(self, from, to, options)
+    }
 
-    /// Look up the region label at `(x, y)` from **cached** chunks only.
+    /// Overland route between city slots `a` and `b` (indices into
+    /// [`city_slots`](Self::city_slots)), as [`land_path`](Self::land_path).
+    ///
+    /// # Errors
     ///
-    /// Returns `0` if the containing chunk is not in the cache.
-    pub fn region_label_at_cached(&self, x: u32, y: u32) -> u32 {
-        let cs = self.reader.header.config.chunk_size as u32;
-        let (cx, cy) = (x / cs, y / cs);
-        self.chunk(cx, cy)
-            .map(|chunk| {
-                let lx = (x - cx * cs) as usize;
-                let ly = (y - cy * cs) as usize;
-                chunk.region_labels[ly * chunk.width as usize + lx]
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if either index is out
+    /// of range, otherwise as [`land_path`](Self::land_path).
+    pub fn city_land_path(
+        &self,
+        a: usize,
+        b: usize,
+        options: &LandOptions,
+    ) -> io::Result<Option<NavPath>> {
+        self.land_path(self.city_pos(a)?, self.city_pos(b)?, options)
+    }
+
+    /// Position of city slot `i`, or
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if there is none.
+    fn city_pos(&self, i: usize) -> io::Result<WorldPos> {
+        self.city_slots()
+            .get(i)
+            .map(|&slot| WorldPos::from(slot))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("no city {i} ({} cities)", self.city_slots().len()),
+                )
             })
+    }
+
+    // -- Tile queries -------------------------------------------------------
+
+    /// Return everything known about tile `pos`.
+    ///
+    /// Loads the containing chunk if needed.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if `pos` is outside the
+    /// map, or any error reading the chunk.
+    pub fn tile_at(&self, pos: WorldPos) -> io::Result<TileInfo> {
+        let (chunk, idx) = self.locate(pos)?;
+        let chunk = self.chunk(chunk)?;
+        Ok(self.tile_in(&chunk, idx, pos))
+    }
+
+    /// [`tile_at`](Self::tile_at) for a list of points, in the same order.
+    ///
+    /// Each chunk is fetched once however many points fall inside it, so
+    /// large batches are not slowed down by cache evictions in between.
+    ///
+    /// # Errors
+    ///
+    /// As [`tile_at`](Self::tile_at), for the first point that fails.
+    pub fn tiles_at(&self, points: &[WorldPos]) -> io::Result<Vec<TileInfo>> {
+        let mut chunks: HashMap<ChunkPos, Arc<ChunkData>> = HashMap::new();
+        let mut tiles = Vec::with_capacity(points.len());
+        for &pos in points {
+            let (key, idx) = self.locate(pos)?;
+            let chunk = if let Some(chunk) = chunks.get(&key) {
+                Arc::clone(chunk)
+            } else {
+                let chunk = self.chunk(key)?;
+                chunks.insert(key, Arc::clone(&chunk));
+                chunk
+            };
+            tiles.push(self.tile_in(&chunk, idx, pos));
+        }
+        Ok(tiles)
+    }
+
+    /// Closest city slot to `pos`, or `None` if the world has no cities.
+    pub fn nearest_city(&self, pos: WorldPos) -> Option<NearestCity> {
+        self.city_index().nearest(pos).map(|n| NearestCity {
+            index: n.index,
+            pos: self.city_slots()[n.index].into(),
+            distance: n.distance,
+        })
+    }
+
+    fn gold_sampler(&self) -> &GoldVeinSampler {
+        self.gold_sampler
+            .get_or_init(|| GoldVeinSampler::new(self.backend.header().config.seed))
+    }
+
+    /// The chunk containing `pos` and the index of `pos` in its per-tile
+    /// arrays, or [`InvalidInput`](io::ErrorKind::InvalidInput) if `pos` is
+    /// outside the map.
+    fn locate(&self, pos: WorldPos) -> io::Result<(ChunkPos, usize)> {
+        self.geometry().tile_index(pos).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "tile ({}, {}) is outside the {}x{} map",
+                    pos.x,
+                    pos.y,
+                    self.width(),
+                    self.height()
+                ),
+            )
+        })
+    }
+
+    /// Build the [`TileInfo`] of world tile `pos`, at index `idx` of `chunk`.
+    fn tile_in(&self, chunk: &ChunkData, idx: usize, pos: WorldPos) -> TileInfo {
+        let biome = Biome::from_u8(chunk.biomes[idx]);
+        TileInfo {
+            pos,
+            terrain: Terrain::from_u8(chunk.terrain[idx]),
+            biome,
+            elevation: chunk.elevation[idx],
+            region_id: chunk.region_labels[idx],
+            gold_vein: self
+                .gold_sampler()
+                .is_gold(pos.x as usize, pos.y as usize, biome),
+            resources: *biome.resource_modifiers(),
+            nearest_city: self.nearest_city(pos),
+            owner: territory::owner_index(chunk.territory[idx]),
+        }
+    }
+
+    // -- Rectangle reads ----------------------------------------------------
+
+    /// Copy `layers` of the `w` x `h` rectangle whose top-left tile is
+    /// `origin` into one row-major buffer.
+    ///
+    /// Every overlapping chunk is loaded (in parallel) through the chunk
+    /// cache.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if the rectangle extends
+    /// past the map, or any error reading a chunk.
+    pub fn read_rect(
+        &self,
+        origin: WorldPos,
+        w: u32,
+        h: u32,
+        layers: Layers,
+    ) -> io::Result<RectData> {
+        if w == 0 || h == 0 {
+            return Ok(RectData::new(origin, w, h, layers));
+        }
+        // Saturates to a position off the map rather than wrapping onto it.
+        let far = WorldPos::new(
+            origin.x.saturating_add(w - 1),
+            origin.y.saturating_add(h - 1),
+        );
+        self.locate(origin)?;
+        self.locate(far)?;
+        let mut rect = RectData::new(origin, w, h, layers);
+
+        let geometry = self.geometry();
+        let chunks = geometry
+            .chunks_in(origin, far)
+            .into_par_iter()
+            .map(|pos| {
+                let chunk = self.chunk(pos)?;
+                Ok((geometry.chunk_origin(pos), chunk))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        for (chunk_origin, chunk) in chunks {
+            if let Some(chunk_origin) = chunk_origin {
+                rect.copy_from_chunk(&chunk, chunk_origin);
+            }
+        }
+        Ok(rect)
+    }
+
+    /// Every tile of the `w` x `h` rectangle whose top-left tile is `origin`,
+    /// with its world position, as a parallel iterator in row-major order.
+    ///
+    /// All layers are read up front with [`read_rect`](Self::read_rect).
+    ///
+    /// # Errors
+    ///
+    /// As [`read_rect`](Self::read_rect).
+    pub fn par_tiles_in(
+        &self,
+        origin: WorldPos,
+        w: u32,
+        h: u32,
+    ) -> io::Result<impl IndexedParallelIterator<Item = RectTile>> {
+        let rect = self.read_rect(origin, w, h, Layers::ALL)?;
+        let len = w as usize * h as usize;
+        Ok((0..len).into_par_iter().map(move |idx| rect.tile(idx)))
+    }
+
+    // -- Region lookups -----------------------------------------------------
+
+    /// Look up the region label at `pos` from **cached** chunks only.
+    ///
+    /// Returns `0` if `pos` is off the map or its chunk is not in the cache.
+    pub fn region_label_at_cached(&self, pos: WorldPos) -> u32 {
+        self.geometry()
+            .tile_index(pos)
+            .and_then(|(chunk, idx)| Some(self.cached_chunk(chunk)?.region_labels[idx]))
             .unwrap_or(0)
     }
 
-    /// Look up the region label at `(x, y)`, loading the chunk if needed.
-    pub fn region_label_at(&mut self, x: u32, y: u32) -> u32 {
-        let cs = self.reader.header.config.chunk_size as u32;
-        let (cx, cy) = (x / cs, y / cs);
-        let _ = self.ensure_chunk(cx, cy);
-        self.region_label_at_cached(x, y)
+    /// Look up the region label at `pos`, loading the chunk if needed.
+    ///
+    /// Returns `0` if `pos` is off the map or its chunk cannot be read.
+    pub fn region_label_at(&self, pos: WorldPos) -> u32 {
+        self.geometry()
+            .tile_index(pos)
+            .and_then(|(chunk, idx)| Some(self.chunk(chunk).ok()?.region_labels[idx]))
+            .unwrap_or(0)
     }
 }
@@ -1,6 +1,8 @@
 const MAP_SIZE = {{ MAP_SIZE }};
 const TILE_SIZE = {{ TILE_SIZE }};
 const MAX_ZOOM = {{ MAX_ZOOM }};
+const CITY_DIAGNOSTICS = {{ CITY_DIAGNOSTICS }};
+const SECTORS = {{ SECTORS }};
 const factor = TILE_SIZE / MAP_SIZE;
 const WORLD_FP = document.body.getAttribute('data-world-fingerprint') || '0';
 const SESSION = Math.floor(Date.now() / 900000);
@@ -124,7 +126,8 @@ function renderSpawnOverlay() {
 function buildSpawnPopup(isl) {
     let isSpawn = isl[8] === 1;
     let spawnOrder = isl[9];
-    let html = '<b>' + (isSpawn ? '\u2605 World Spawn Island' : 'Island #' + isl[0]) + '</b><br>';
+    let title = isl[12] ? isl[12] + ' (#' + isl[0] + ')' : 'Island #' + isl[0];
+    let html = '<b>' + (isSpawn ? '\u2605 World Spawn Island' : title) + '</b><br>';
     html += 'Cities: ' + isl[3] + '<br>';
     if (!isSpawn) html += 'Spawn order: <b>#' + spawnOrder + '</b><br>';
     html += 'Centroid: (' + isl[1] + ', ' + isl[2] + ')';
@@ -247,6 +250,48 @@ tileLayer.on('tileerror', function (e) {
     console.error('Tile error z=' + e.coords.z + ' x=' + e.coords.x + ' y=' + e.coords.y);
 });
 
+// ---------------------------------------------------------------------------
+// Alternative base layers
+//
+// Ocean sectors (/stile/) outline and number the sector grid of worlds that
+// store one (format version 10+).
+//
+// City placement is only offered when the world was generated with
+// CITY_DIAGNOSTICS=true. The /ctile/ layer tints every Land tile by why it
+// did (or did not) get a city; /city_diagnostics.json has the totals and the
+// dropped islands.
+// ---------------------------------------------------------------------------
+
+function extraTileLayer(prefix) {
+    return L.tileLayer(prefix + '{z}/{x}/{y}.png?v=' + WORLD_FP + '.' + SESSION, {
+        minZoom: 0,
+        maxZoom: MAX_ZOOM,
+        tileSize: TILE_SIZE,
+        noWrap: true,
+        bounds: bounds
+    });
+}
+
+let baseLayers = { 'Debug grid': tileLayer };
+if (SECTORS) {
+    baseLayers['Ocean sectors'] = extraTileLayer('/stile/');
+}
+if (CITY_DIAGNOSTICS) {
+    baseLayers['City placement'] = extraTileLayer('/ctile/');
+}
+if (Object.keys(baseLayers).length > 1) {
+    L.control.layers(baseLayers, {}, { collapsed: false }).addTo(map);
+}
+
+if (CITY_DIAGNOSTICS) {
+    fetch('/city_diagnostics.json')
+        .then(function (r) { return r.json(); })
+        .then(function (report) {
+            console.info('City diagnostics', report.totals,
+                report.dropped_islands.length + ' islands dropped');
+        });
+}
+
 // Inject debug island icon styles into the page at runtime.
 (function () {
     let style = document.createElement('style');
@@ -164,10 +164,11 @@ function renderVillages(villages, vb) {
         let offers = villages[i][3];
         let demands = villages[i][4];
         let biome = villages[i][5];
+        let name = villages[i][6];
         if (!vb.contains(L.latLng(vy, vx))) continue;
         let marker = L.marker(L.latLng(vy, vx), { icon: villageIcon });
         marker.bindPopup(
-            buildVillagePopup(vx, vy, rid, offers, demands, biome),
+            buildVillagePopup(vx, vy, rid, offers, demands, biome, name),
             { className: 'village-popup', minWidth: 190 }
         );
         villageLayer.addLayer(marker);
@@ -204,20 +205,26 @@ function buildCityPopup(cx, cy, rid, res) {
         html += '<tr><td>\u{1FA99} Gold nodes</td><td><span class="res-gold">'
             + res.gold_nodes + '</span></td></tr>';
     }
-    html += '</table></div>';
+    html += '</table>';
+    // Site details are absent for worlds saved before format version 7.
+    if (res.facing !== undefined) {
+        html += '<div class="city-popup-sub">\u{2693} ' + res.coast + ' harbor facing '
+            + res.facing + ' &middot; quality ' + res.harbor + '/100</div>';
+    }
+    html += '</div>';
     return html;
 }
 
-function buildVillagePopup(vx, vy, rid, offers, demands, biome) {
+function buildVillagePopup(vx, vy, rid, offers, demands, biome, name) {
     let offerIcon = RES_ICON[offers] || '?';
     let demandIcon = RES_ICON[demands] || '?';
     let offerColor = RES_COLOR[offers] || '#333';
     let demandColor = RES_COLOR[demands] || '#333';
 
     let html = '<div class="village-popup-inner">';
-    // Title row with village name derived from what it offers
+    // Title row with the village name, or one derived from what it offers
     html += '<div class="village-popup-title">'
-        + offerIcon + ' ' + offers + ' Village</div>';
+        + offerIcon + ' ' + (name || offers + ' Village') + '</div>';
     html += '<div class="village-popup-sub">'
         + 'Island #' + rid + ' &middot; ' + biome + '</div>';
 
@@ -277,6 +284,11 @@ function makeIslandIcon(count, isSpawn, t) {
     });
 }
 
+// Name and id of an island; files without names show the id alone.
+function islandTitle(island) {
+    return island[12] ? island[12] + ' (#' + island[0] + ')' : 'Island #' + island[0];
+}
+
 function updateIslandView() {
     islandLayer.clearLayers();
     if (!allIslands) return;
@@ -300,7 +312,8 @@ function updateIslandView() {
         let marker = L.marker(latlng, { icon: makeIslandIcon(count, isSpawn, t) });
         let label = isSpawn
             ? '★ World Spawn &mdash; ' + count + ' cities'
-            : 'Island #' + island[0] + ' &mdash; ' + count + ' cities'
+            : islandTitle(island) + ' &mdash; ' + count + ' cities'
+            + ' &middot; ' + island[10] + ' tiles'
             + ' &middot; spawn order #' + island[9];
         marker.bindPopup(label);
         marker.on('click', () => drawIslandHighlight(island));
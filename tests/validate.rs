@@ -0,0 +1,107 @@
+//! Generated worlds obey their own placement rules, whatever the seed.
+
+use world_generator::config::WorldConfig;
+use world_generator::generate::generate_world;
+use world_generator::save::save_world_chunked;
+use world_generator::terrain::Terrain;
+use world_generator::validate::{validate_world, Violation};
+use world_generator::World;
+
+const MAP_SIZE: u16 = 600;
+
+fn config(seed: u32) -> WorldConfig {
+    WorldConfig {
+        map_size: MAP_SIZE,
+        chunk_size: WorldConfig::optimal_chunk_size(MAP_SIZE),
+        seed,
+        playable_radius: (f32::from(MAP_SIZE) / 2.0 * 0.975) as u16,
+        ..WorldConfig::default()
+    }
+}
+
+#[test]
+fn generated_worlds_are_valid() {
+    let mut cities = 0;
+    for seed in [1, 7, 42, 1234, 99_999, 314_159, 2_718_281, 4_000_000_000] {
+        let world = World::from_data(generate_world(&config(seed)));
+        let report = validate_world(&world);
+        let listed: Vec<String> = report
+            .violations
+            .iter()
+            .map(std::string::ToString::to_string)
+            .collect();
+        assert!(report.is_ok(), "seed {seed}:\n{}", listed.join("\n"));
+        cities += report.cities_checked;
+    }
+    assert!(
+        cities > 0,
+        "no seed produced any city; the checks ran on nothing"
+    );
+}
+
+#[test]
+fn saved_world_is_checked_with_its_own_village_rules() {
+    let config = WorldConfig {
+        village_min_ocean_distance: 4,
+        village_spacing: 8,
+        ..config(42)
+    };
+    let data = generate_world(&config);
+    // Only a meaningful check if the default rules would have failed it.
+    let v = &data.villages;
+    assert!((0..v.len()).any(|i| (i + 1..v.len()).any(|j| {
+        v[i].region_id == v[j].region_id
+            && v[i].x.abs_diff(v[j].x).max(v[i].y.abs_diff(v[j].y)) < 30
+    })));
+
+    let path = std::env::temp_dir().join(format!("village-rules-{}.world", std::process::id()));
+    let path = path.to_str().unwrap();
+    save_world_chunked(path, &data).unwrap();
+    let world = World::open(path).unwrap();
+    let report = validate_world(&world);
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(world.config().village_min_ocean_distance, 4);
+    assert_eq!(world.config().village_spacing, 8);
+    let listed: Vec<String> = report
+        .violations
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect();
+    assert!(report.is_ok(), "{}", listed.join("\n"));
+}
+
+#[test]
+fn tampered_world_is_reported() {
+    let mut data = generate_world(&config(7));
+    assert!(!data.city_slots.is_empty() && !data.villages.is_empty());
+
+    // A city in open water, a village trading with itself, a missing
+    // resource profile.
+    let water = (0..data.terrain.len())
+        .find(|&i| data.terrain[i] == Terrain::Water.to_u8() && data.region_labels[i] == 0)
+        .map(|i| (i as u32 % data.width, i as u32 / data.width))
+        .unwrap();
+    data.city_slots[0] = water;
+    data.villages[0].trade.demands = data.villages[0].trade.offers;
+    data.city_resources.pop();
+
+    let report = validate_world(&World::from_data(data));
+    let has = |f: fn(&Violation) -> bool| report.violations.iter().any(f);
+    assert!(has(|v| matches!(
+        v,
+        Violation::CityNotOnLand { city: 0, .. }
+    )));
+    assert!(has(|v| matches!(
+        v,
+        Violation::VillageTrade { village: 0, .. }
+    )));
+    assert!(has(|v| matches!(
+        v,
+        Violation::CityTableLength {
+            table: "city_resources",
+            ..
+        }
+    )));
+    assert!(has(|v| matches!(v, Violation::RegionMismatch { .. })));
+}
@@ -0,0 +1,76 @@
+//! Chunks patched in place read back, survive reopening and compaction.
+
+use std::io;
+
+use world_generator::config::WorldConfig;
+use world_generator::generate::generate_world;
+use world_generator::save::{compact_world, save_world_chunked, ChunkSummary, ChunkedWorldWriter};
+use world_generator::validate::validate_world;
+use world_generator::World;
+
+const MAP_SIZE: u16 = 300;
+
+#[test]
+fn patched_chunk_round_trips_through_compaction() {
+    let config = WorldConfig {
+        map_size: MAP_SIZE,
+        chunk_size: WorldConfig::optimal_chunk_size(MAP_SIZE),
+        seed: 42,
+        playable_radius: (f32::from(MAP_SIZE) / 2.0 * 0.975) as u16,
+        ..WorldConfig::default()
+    };
+    let path = std::env::temp_dir().join(format!("writer-{}.world", std::process::id()));
+    let path = path.to_str().unwrap();
+    save_world_chunked(path, &generate_world(&config)).unwrap();
+
+    let mut writer = ChunkedWorldWriter::open(path).unwrap();
+    let (chunks_x, chunks_y) = (writer.header().chunks_x, writer.header().chunks_y);
+    let (cx, cy) = (0..chunks_y)
+        .flat_map(|cy| (0..chunks_x).map(move |cx| (cx, cy)))
+        .find(|&(cx, cy)| writer.chunk_summary(cx, cy).unwrap().has_land())
+        .unwrap();
+
+    // Edit the biomes, which the header does not depend on.
+    let mut chunk = writer.load_chunk(cx, cy).unwrap();
+    chunk.biomes.reverse();
+    writer.replace_chunk(cx, cy, &chunk).unwrap();
+    let dead = writer.dead_bytes();
+    assert!(dead > 0);
+    assert_eq!(writer.load_chunk(cx, cy).unwrap().biomes, chunk.biomes);
+    assert_eq!(
+        writer.chunk_summary(cx, cy),
+        Some(&ChunkSummary::from_chunk(&chunk))
+    );
+
+    // Terrain feeds the island registry, so changing it is refused.
+    let mut flooded = chunk.clone();
+    flooded.terrain.fill(0);
+    let err = writer.replace_chunk(cx, cy, &flooded).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(writer.dead_bytes(), dead);
+    drop(writer);
+
+    let reopened = ChunkedWorldWriter::open(path).unwrap();
+    assert_eq!(reopened.dead_bytes(), dead);
+    assert_eq!(reopened.load_chunk(cx, cy).unwrap().biomes, chunk.biomes);
+    drop(reopened);
+
+    assert_eq!(compact_world(path).unwrap(), dead);
+    let compacted = ChunkedWorldWriter::open(path).unwrap();
+    assert_eq!(compacted.dead_bytes(), 0);
+    assert_eq!(compacted.load_chunk(cx, cy).unwrap().biomes, chunk.biomes);
+    assert_eq!(
+        compacted.chunk_summary(cx, cy),
+        Some(&ChunkSummary::from_chunk(&chunk))
+    );
+    drop(compacted);
+
+    let report = validate_world(&World::open(path).unwrap());
+    std::fs::remove_file(path).unwrap();
+    let listed: Vec<String> = report
+        .violations
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect();
+    assert!(report.is_ok(), "{}", listed.join("\n"));
+}